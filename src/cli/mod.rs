@@ -1,8 +1,11 @@
 use crate::{
     core::company::CompanySymbol,
+    headless::HeadlessOptions,
+    replay::ReplayOptions,
     simulation::settings::{SimulationSettings, SimulationSettingsBuilder},
     storage::config_file::StorageConfigFileImpl,
 };
+use chrono::NaiveDate;
 use clap::{ArgAction, Command};
 use prettytable::{row, Table};
 use std::fmt::{self, Display, Formatter};
@@ -10,9 +13,35 @@ use std::fmt::{self, Display, Formatter};
 pub struct Cli;
 
 pub enum Action {
+    Replay(SimulationSettings, ReplayOptions),
+    RunHeadless(SimulationSettings, HeadlessOptions),
     StartServer(SimulationSettings),
 }
 
+fn parse_arg<T: std::str::FromStr>(sub_matches: &clap::ArgMatches, name: &str) -> Option<T> {
+    sub_matches.get_one::<String>(name).map(|s| {
+        s.parse::<T>().unwrap_or_else(|_| {
+            eprintln!("Invalid value for {}", name);
+            std::process::exit(1);
+        })
+    })
+}
+
+/// Without the flag, the value of the settings file is used
+fn get_skip_closed_time(sub_matches: &clap::ArgMatches) -> Option<bool> {
+    sub_matches
+        .get_one::<bool>("skip-closed-time")
+        .cloned()
+        .filter(|skip| *skip)
+}
+
+fn skip_closed_time_arg() -> clap::Arg {
+    clap::Arg::new("skip-closed-time")
+        .long("skip-closed-time")
+        .help("Jump over the time when all the exchanges are closed")
+        .action(ArgAction::SetTrue)
+}
+
 impl Display for CompanySymbol {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -26,6 +55,7 @@ impl Display for SimulationSettings {
         table.add_row(row![cH2 -> "Simulation settings"]);
         table.add_row(row!["Max orders per tick", r -> self.max_orders_per_tick]);
         table.add_row(row!["Flush storage", r -> self.flush_storage]);
+        table.add_row(row!["Skip closed time", r -> self.skip_closed_time]);
         table.add_row(row![
             "URL",
             format!("http://{}:{}", self.address, self.port)
@@ -68,6 +98,7 @@ impl Cli {
                             .required(false),
                     )
                     .arg(clap::Arg::new("port").long("port").required(false))
+                    .arg(skip_closed_time_arg())
                     .arg(
                         clap::Arg::new("redis-url")
                             .long("redis-url")
@@ -84,6 +115,81 @@ impl Cli {
                             .required(false),
                     ),
             )
+            .subcommand(
+                Command::new("run")
+                    .about("Run the simulation without the server nor the waits between ticks")
+                    .arg(
+                        clap::Arg::new("days")
+                            .short('d')
+                            .long("days")
+                            .help("Virtual days to simulate")
+                            .default_value("365"),
+                    )
+                    .arg(
+                        clap::Arg::new("start")
+                            .long("start")
+                            .help("First day, as YYYY-MM-DD")
+                            .required(false),
+                    )
+                    .arg(clap::Arg::new("seed").long("seed").required(false))
+                    .arg(
+                        clap::Arg::new("keep-closed-time")
+                            .long("keep-closed-time")
+                            .help("Tick through the time when all the exchanges are closed, which is skipped by default")
+                            .action(ArgAction::SetTrue),
+                    )
+                    .arg(
+                        clap::Arg::new("output")
+                            .long("output")
+                            .help("File for the JSON summary")
+                            .required(false),
+                    )
+                    .arg(
+                        clap::Arg::new("journal")
+                            .long("journal")
+                            .help("File for the JSONL journal of the events")
+                            .required(false),
+                    )
+                    .arg(
+                        clap::Arg::new("max_orders_per_tick")
+                            .short('o')
+                            .long("max_orders_per_tick")
+                            .required(false),
+                    )
+                    .arg(
+                        clap::Arg::new("time-to-wait-millis")
+                            .long("time-to-wait-millis")
+                            .help("Only changes the virtual time of each tick")
+                            .required(false),
+                    ),
+            )
+            .subcommand(
+                Command::new("replay")
+                    .about("Run a journal again from its seed, verifying that it's deterministic")
+                    .arg(
+                        clap::Arg::new("journal")
+                            .long("journal")
+                            .help("JSONL journal of a run, the one in Redis by default")
+                            .required(false),
+                    )
+                    .arg(
+                        clap::Arg::new("to")
+                            .long("to")
+                            .help("Sequence number of the checkpoint to stop at, the last one by default")
+                            .required(false),
+                    )
+                    .arg(
+                        clap::Arg::new("output")
+                            .long("output")
+                            .help("File for the JSON of the rebuilt state")
+                            .required(false),
+                    )
+                    .arg(
+                        clap::Arg::new("redis-url")
+                            .long("redis-url")
+                            .required(false),
+                    ),
+            )
             .get_matches();
 
         match matches.subcommand() {
@@ -99,6 +205,7 @@ impl Cli {
                     });
 
                 let flush_storage = sub_matches.get_one::<bool>("flush_storage").cloned();
+                let skip_closed_time = get_skip_closed_time(sub_matches);
 
                 let address = sub_matches.get_one::<String>("address").cloned();
                 let port = sub_matches.get_one::<String>("port").cloned();
@@ -125,15 +232,32 @@ impl Cli {
 
                 let simulation_settings = SimulationSettingsBuilder {
                     address,
+                    delisting_min_price: None,
+                    demographics: None,
+                    etfs: None,
+                    exchanges: None,
+                    factor_loadings: None,
                     flush_storage,
+                    funds: None,
+                    fx: None,
+                    index: None,
+                    investor_profile: None,
                     max_duration_seconds,
                     max_investor_age: None,
+                    max_ipo_subscriptions_per_tick: None,
                     max_orders_per_tick,
+                    max_suspension_days: None,
                     port,
+                    price_impact: None,
+                    price_model: None,
                     prometheus_job_name: None,
                     prometheus_url,
                     redis_url,
+                    skip_closed_time,
+                    strategy_mix: None,
+                    symbol_price_models: None,
                     time_to_wait_millis,
+                    wealth: None,
                 }
                 .load_from_storage(&StorageConfigFileImpl)
                 .await
@@ -144,6 +268,59 @@ impl Cli {
 
                 Action::StartServer(simulation_settings)
             }
+            Some(("run", sub_matches)) => {
+                let start = sub_matches.get_one::<String>("start").map(|s| {
+                    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap_or_else(|_| {
+                        eprintln!("Invalid value for start");
+                        std::process::exit(1);
+                    })
+                });
+                let options = HeadlessOptions {
+                    days: parse_arg(sub_matches, "days").unwrap_or(365),
+                    journal: sub_matches.get_one::<String>("journal").cloned(),
+                    output: sub_matches.get_one::<String>("output").cloned(),
+                    seed: parse_arg(sub_matches, "seed"),
+                    start,
+                };
+
+                let simulation_settings = SimulationSettingsBuilder {
+                    max_orders_per_tick: parse_arg(sub_matches, "max_orders_per_tick"),
+                    // Nothing trades while closed, so a run only spends time on it
+                    // when asked
+                    skip_closed_time: Some(!sub_matches.get_flag("keep-closed-time")),
+                    time_to_wait_millis: parse_arg(sub_matches, "time-to-wait-millis"),
+                    ..Default::default()
+                }
+                .load_from_storage(&StorageConfigFileImpl)
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Failed to load simulation settings: {}", e);
+                    std::process::exit(1);
+                });
+
+                Action::RunHeadless(simulation_settings, options)
+            }
+            Some(("replay", sub_matches)) => {
+                let options = ReplayOptions {
+                    journal: sub_matches.get_one::<String>("journal").cloned(),
+                    output: sub_matches.get_one::<String>("output").cloned(),
+                    to: parse_arg(sub_matches, "to"),
+                };
+
+                // The rest of the settings are the ones in the journal
+                let simulation_settings = SimulationSettingsBuilder {
+                    redis_url: sub_matches.get_one::<String>("redis-url").cloned(),
+                    ..Default::default()
+                }
+                .load_from_storage(&StorageConfigFileImpl)
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Failed to load simulation settings: {}", e);
+                    std::process::exit(1);
+                });
+
+                Action::Replay(simulation_settings, options)
+            }
             _ => {
                 std::process::exit(1);
             }
@@ -0,0 +1,270 @@
+use super::{
+    company::CompanySymbol,
+    price::Prices,
+    time::{TimeHandler, Timezone},
+};
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, str::FromStr};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    Minute,
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl std::fmt::Display for CandleInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CandleInterval::Minute => write!(f, "1m"),
+            CandleInterval::Hour => write!(f, "1h"),
+            CandleInterval::Day => write!(f, "1d"),
+        }
+    }
+}
+
+impl FromStr for CandleInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1m" => Ok(CandleInterval::Minute),
+            "1h" => Ok(CandleInterval::Hour),
+            "1d" => Ok(CandleInterval::Day),
+            _ => Err(format!("Unknown candle interval: {}", s)),
+        }
+    }
+}
+
+impl CandleInterval {
+    pub fn all() -> [CandleInterval; 3] {
+        [
+            CandleInterval::Minute,
+            CandleInterval::Hour,
+            CandleInterval::Day,
+        ]
+    }
+
+    /// Start of the bar that contains the timestamp. Daily bars start at the
+    /// local midnight of the timezone, the one of the exchange.
+    pub fn get_bar_start(&self, timestamp: u64, timezone: &Timezone) -> u64 {
+        match self {
+            CandleInterval::Minute => timestamp - timestamp % 60,
+            CandleInterval::Hour => timestamp - timestamp % 3600,
+            CandleInterval::Day => {
+                let tz = timezone.get_tz();
+                let local_midnight = tz
+                    .timestamp_opt(timestamp as i64, 0)
+                    .unwrap()
+                    .date_naive()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap();
+
+                tz.from_local_datetime(&local_midnight)
+                    .earliest()
+                    .map_or(timestamp - timestamp % 86400, |d| d.timestamp() as u64)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Candle {
+    pub close: f64,
+    pub high: f64,
+    pub low: f64,
+    pub open: f64,
+    pub start: u64,
+    /** Traded value, used for the VWAP */
+    pub turnover: f64,
+    pub volume: u64,
+    pub vwap: Option<f64>,
+}
+
+impl Candle {
+    fn new(start: u64, price: f64) -> Self {
+        Self {
+            close: price,
+            high: price,
+            low: price,
+            open: price,
+            start,
+            turnover: 0.0,
+            volume: 0,
+            vwap: None,
+        }
+    }
+
+    fn update(&mut self, price: f64, traded: &TradedVolume) {
+        self.close = price;
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.turnover += traded.turnover;
+        self.volume += traded.shares;
+
+        if self.volume > 0 {
+            self.vwap = Some(self.turnover / self.volume as f64);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TradedVolume {
+    pub shares: u64,
+    pub turnover: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClosedCandle {
+    pub candle: Candle,
+    pub interval: CandleInterval,
+    pub symbol: CompanySymbol,
+}
+
+/// Builds the OHLCV bars of every symbol from the prices of each tick and the
+/// trades executed in it
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CandleAggregator {
+    pub open_candles: BTreeMap<CandleInterval, BTreeMap<CompanySymbol, Candle>>,
+
+    #[serde(skip)]
+    tick_trades: BTreeMap<CompanySymbol, TradedVolume>,
+}
+
+impl CandleAggregator {
+    pub fn record_trade(&mut self, symbol: &CompanySymbol, shares: u64, turnover: f64) {
+        let traded = self.tick_trades.entry(symbol.clone()).or_default();
+
+        traded.shares += shares;
+        traded.turnover += turnover;
+    }
+
+    /// Adds the tick to the open bars and returns the bars that it closed. The
+    /// daily bars of each symbol follow the timezone of its exchange.
+    pub fn record_tick(
+        &mut self,
+        prices: &Prices,
+        timezones: &BTreeMap<CompanySymbol, Timezone>,
+        time: &TimeHandler,
+    ) -> Vec<ClosedCandle> {
+        let now = time.get_now_unix_timestamp();
+        let tick_trades = std::mem::take(&mut self.tick_trades);
+        let mut closed_candles = Vec::new();
+
+        for interval in CandleInterval::all() {
+            let get_bar_start = |symbol: &CompanySymbol| {
+                let timezone = timezones.get(symbol).copied().unwrap_or(time.tz);
+
+                interval.get_bar_start(now, &timezone)
+            };
+            let open_candles = self.open_candles.entry(interval).or_default();
+
+            // Symbols without a price (e.g. delisted, or out of session)
+            // close their bar
+            let finished_symbols = open_candles
+                .iter()
+                .filter(|(symbol, candle)| {
+                    candle.start != get_bar_start(symbol) || !prices.0.contains_key(symbol)
+                })
+                .map(|(symbol, _)| symbol.clone())
+                .collect::<Vec<_>>();
+
+            for symbol in finished_symbols {
+                let candle = open_candles.remove(&symbol).unwrap();
+
+                closed_candles.push(ClosedCandle {
+                    candle,
+                    interval,
+                    symbol,
+                });
+            }
+
+            for (symbol, price) in prices.0.iter() {
+                let Ok(price) = price.get_average().map(|price| price.to_f64()) else {
+                    continue;
+                };
+                let traded = tick_trades.get(symbol).cloned().unwrap_or_default();
+
+                open_candles
+                    .entry(symbol.clone())
+                    .or_insert_with(|| Candle::new(get_bar_start(symbol), price))
+                    .update(price, &traded);
+            }
+        }
+
+        closed_candles
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_fixtures::{build_money, build_price};
+
+    fn build_prices(symbol: &CompanySymbol, value: f64) -> Prices {
+        Prices(BTreeMap::from([(
+            symbol.clone(),
+            build_price(build_money(value)),
+        )]))
+    }
+
+    #[test]
+    fn test_record_tick() {
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let mut time = TimeHandler::new(3600 * 24 * 1000, Some(60 * 20), 1000);
+        let mut aggregator = CandleAggregator::default();
+
+        for (value, shares) in [(10.0, 100), (12.0, 0), (9.0, 300)] {
+            aggregator.record_trade(&symbol, shares, value * shares as f64);
+            aggregator.record_tick(&build_prices(&symbol, value), &BTreeMap::new(), &time);
+            time.tick();
+        }
+
+        let closed = aggregator.record_tick(&build_prices(&symbol, 11.0), &BTreeMap::new(), &time);
+        let hourly = closed
+            .iter()
+            .find(|c| c.interval == CandleInterval::Hour)
+            .unwrap();
+
+        assert_eq!(closed.len(), 2);
+        assert_eq!(hourly.candle.open, 10.0);
+        assert_eq!(hourly.candle.high, 12.0);
+        assert_eq!(hourly.candle.low, 9.0);
+        assert_eq!(hourly.candle.close, 9.0);
+        assert_eq!(hourly.candle.volume, 400);
+        assert_eq!(hourly.candle.vwap, Some(9.25));
+    }
+
+    #[test]
+    fn test_daily_bars_in_the_exchange_timezone() {
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let new_york = Timezone::try_from("America/New_York".to_string()).unwrap();
+        // 2024-12-23 10:00 HKT, which is still 2024-12-22 in New York
+        let time = TimeHandler::new(1_734_919_200, Some(1), 100);
+        let mut aggregator = CandleAggregator::default();
+
+        assert_eq!(
+            CandleInterval::Day.get_bar_start(1_734_919_200, &time.tz),
+            1_734_883_200
+        );
+        assert_eq!(
+            CandleInterval::Day.get_bar_start(1_734_919_200, &new_york),
+            1_734_843_600
+        );
+
+        aggregator.record_tick(
+            &build_prices(&symbol, 10.0),
+            &BTreeMap::from([(symbol.clone(), new_york)]),
+            &time,
+        );
+
+        assert_eq!(
+            aggregator.open_candles[&CandleInterval::Day][&symbol].start,
+            1_734_843_600
+        );
+    }
+}
@@ -1,4 +1,32 @@
-use super::{Company, CompanyVerifyError};
+use super::{
+    Companies, Company, CompanyStatus, CompanyStatusChange, CompanyTransitionError,
+    CompanyVerifyError,
+};
+use crate::core::time::TimeHandler;
+
+impl CompanyStatus {
+    pub fn can_transition_to(&self, next: &CompanyStatus) -> bool {
+        use CompanyStatus::*;
+
+        matches!(
+            (self, next),
+            // A direct listing doesn't go through an IPO
+            (Private, Listed)
+                | (Private, IpoPending)
+                | (IpoPending, Listed)
+                // Withdrawn IPO
+                | (IpoPending, Private)
+                | (Listed, Suspended)
+                | (Listed, Delisted)
+                | (Suspended, Listed)
+                | (Suspended, Delisted)
+        )
+    }
+
+    pub fn is_trading(&self) -> bool {
+        *self == CompanyStatus::Listed
+    }
+}
 
 impl Company {
     pub fn verify(&self) -> Result<(), CompanyVerifyError> {
@@ -11,4 +39,74 @@ impl Company {
 
         Ok(())
     }
+
+    pub fn transition(
+        &mut self,
+        status: CompanyStatus,
+        time: &TimeHandler,
+    ) -> Result<(), CompanyTransitionError> {
+        if !self.status.can_transition_to(&status) {
+            return Err(CompanyTransitionError::Invalid {
+                from: self.status,
+                to: status,
+            });
+        }
+
+        self.status = status;
+        self.status_changes.push(CompanyStatusChange {
+            status,
+            timestamp: time.get_now_unix_timestamp(),
+        });
+
+        Ok(())
+    }
+
+    /// UNIX timestamp of the last status change
+    pub fn get_status_since(&self) -> Option<u64> {
+        self.status_changes.last().map(|change| change.timestamp)
+    }
+
+    pub fn get_status_days(&self, time: &TimeHandler) -> u64 {
+        self.get_status_since().map_or(0, |since| {
+            time.get_now_unix_timestamp().saturating_sub(since) / (24 * 60 * 60)
+        })
+    }
+}
+
+impl Companies {
+    pub fn count_with_status(&self, status: CompanyStatus) -> usize {
+        self.mapping
+            .values()
+            .filter(|company| company.status == status)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::company::CompanySymbol;
+
+    #[test]
+    fn test_transition() {
+        let time = TimeHandler::new(0, None, 1000);
+        let mut company = Company {
+            industry: "Software".to_string(),
+            name: "Foo".to_string(),
+            sector: crate::core::company::Sector::InformationTechnology,
+            status: CompanyStatus::Private,
+            status_changes: Vec::new(),
+            symbol: CompanySymbol::new("FOO".to_string()),
+        };
+
+        assert!(company.transition(CompanyStatus::Suspended, &time).is_err());
+        assert!(company.transition(CompanyStatus::IpoPending, &time).is_ok());
+        assert!(company.transition(CompanyStatus::Listed, &time).is_ok());
+        assert!(company.transition(CompanyStatus::Suspended, &time).is_ok());
+        assert!(company.transition(CompanyStatus::Delisted, &time).is_ok());
+        assert!(company.transition(CompanyStatus::Listed, &time).is_err());
+
+        assert_eq!(company.status, CompanyStatus::Delisted);
+        assert_eq!(company.status_changes.len(), 4);
+    }
 }
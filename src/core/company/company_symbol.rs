@@ -1,12 +1,25 @@
 use super::{CompanySymbol, CompanySymbolVerifyError};
+use crate::core::stock_exchange::ExchangeId;
 
 impl CompanySymbol {
     pub fn new(symbol: String) -> Self {
         CompanySymbol(symbol)
     }
 
+    /// Symbol of the secondary listing in another exchange, e.g. `ABC.SH`
+    pub fn get_secondary(&self, exchange_id: &ExchangeId) -> Self {
+        CompanySymbol(format!("{}.{}", self.0, exchange_id.0))
+    }
+
     pub fn verify(&self) -> Result<(), CompanySymbolVerifyError> {
-        if self.0.is_empty() || self.0.contains(|c: char| !c.is_alphabetic()) {
+        let is_valid =
+            |part: &str| !part.is_empty() && !part.contains(|c: char| !c.is_alphabetic());
+        let is_valid = match self.0.split_once('.') {
+            Some((symbol, suffix)) => is_valid(symbol) && is_valid(suffix),
+            None => is_valid(&self.0),
+        };
+
+        if !is_valid {
             return Err(CompanySymbolVerifyError::Symbol);
         }
 
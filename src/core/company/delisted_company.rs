@@ -0,0 +1,11 @@
+use super::{CompanySymbol, DelistedCompanies, DelistedCompany};
+
+impl DelistedCompanies {
+    pub fn get(&self, symbol: &CompanySymbol) -> Option<&DelistedCompany> {
+        self.mapping.get(symbol)
+    }
+
+    pub fn contains(&self, symbol: &CompanySymbol) -> bool {
+        self.mapping.contains_key(symbol)
+    }
+}
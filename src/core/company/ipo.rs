@@ -1,3 +1,308 @@
-use super::Ipos;
+use super::{Ipo, IpoAllotmentMethod, IpoStatus, IpoSubscribeError, IpoSubscription, Ipos};
+use crate::core::{
+    investor::{Investor, InvestorId},
+    money::{Money, MoneyError},
+    time::TimeHandler,
+};
+use rand::{rngs::StdRng, seq::SliceRandom};
 
-impl Ipos {}
+impl Ipo {
+    pub fn is_offer_open(&self, time: &TimeHandler) -> bool {
+        let now = time.get_now_unix_timestamp();
+
+        self.status == IpoStatus::Offering && now >= self.offer_start && now < self.offer_end
+    }
+
+    pub fn get_total_lots(&self) -> u64 {
+        self.shares / self.lot_size
+    }
+
+    pub fn get_subscribed_lots(&self) -> u64 {
+        self.subscriptions.iter().map(|s| s.lots).sum()
+    }
+
+    pub fn get_oversubscription_ratio(&self) -> f64 {
+        self.get_subscribed_lots() as f64 / self.offered_lots as f64
+    }
+
+    pub fn get_lots_price(&self, lots: u64) -> Result<Money, MoneyError> {
+        self.offer_price.checked_mul(lots * self.lot_size)
+    }
+
+    pub fn subscribe(
+        &mut self,
+        investor: &mut Investor,
+        lots: u64,
+        time: &TimeHandler,
+    ) -> Result<(), IpoSubscribeError> {
+        if !self.is_offer_open(time) {
+            return Err(IpoSubscribeError::OfferClosed);
+        }
+
+        if lots == 0 {
+            return Err(IpoSubscribeError::NoLots);
+        }
+
+        if self
+            .subscriptions
+            .iter()
+            .any(|s| s.investor_id == investor.id)
+        {
+            return Err(IpoSubscribeError::AlreadySubscribed);
+        }
+
+        let locked_cash = self
+            .get_lots_price(lots)
+            .map_err(IpoSubscribeError::MoneyError)?;
+
+        if investor.liquid_cash.value < locked_cash.value {
+            return Err(IpoSubscribeError::InsufficientCash);
+        }
+
+        investor
+            .subtract_cash(&locked_cash)
+            .map_err(IpoSubscribeError::MoneyError)?;
+
+        self.subscriptions.push(IpoSubscription {
+            allotted_lots: 0,
+            investor_id: investor.id,
+            locked_cash,
+            lots,
+        });
+
+        Ok(())
+    }
+
+    /// Closes the offer period and allots the lots. Returns the cash that has
+    /// to be refunded to each subscriber for the lots they didn't get.
+    pub fn allot(&mut self, rng: &mut StdRng) -> Result<Vec<(InvestorId, Money)>, MoneyError> {
+        let total_lots = self.get_total_lots();
+        let subscribed_lots = self.get_subscribed_lots();
+
+        if subscribed_lots <= total_lots {
+            for subscription in self.subscriptions.iter_mut() {
+                subscription.allotted_lots = subscription.lots;
+            }
+        } else {
+            match self.allotment_method {
+                IpoAllotmentMethod::Ballot => {
+                    self.ballot_lots(total_lots, rng);
+                }
+                IpoAllotmentMethod::ProRata => {
+                    for subscription in self.subscriptions.iter_mut() {
+                        subscription.allotted_lots =
+                            (subscription.lots as u128 * total_lots as u128
+                                / subscribed_lots as u128) as u64;
+                    }
+
+                    let allotted_lots = self.get_allotted_lots();
+
+                    self.ballot_lots(total_lots - allotted_lots, rng);
+                }
+            }
+        }
+
+        let allotted_lots = self.get_allotted_lots();
+
+        // When undersubscribed only the allotted shares are issued
+        self.shares = allotted_lots * self.lot_size;
+        self.status = if allotted_lots == 0 {
+            IpoStatus::Withdrawn
+        } else {
+            IpoStatus::Allotted
+        };
+
+        let mut refunds = Vec::new();
+
+        for subscription in self.subscriptions.iter_mut() {
+            let cost = self
+                .offer_price
+                .checked_mul(subscription.allotted_lots * self.lot_size)?;
+            let refund = subscription.locked_cash.checked_sub(&cost)?;
+
+            subscription.locked_cash = cost;
+
+            if !refund.value.is_zero() {
+                refunds.push((subscription.investor_id, refund));
+            }
+        }
+
+        Ok(refunds)
+    }
+
+    /// Removes the subscription of the investor, returning its locked cash.
+    /// When the lots were already allotted, they are not issued anymore.
+    pub fn cancel_subscription(&mut self, investor_id: &InvestorId) -> Option<Money> {
+        let position = self
+            .subscriptions
+            .iter()
+            .position(|s| &s.investor_id == investor_id)?;
+        let subscription = self.subscriptions.remove(position);
+
+        if self.status == IpoStatus::Allotted {
+            self.shares -= subscription.allotted_lots * self.lot_size;
+        }
+
+        Some(subscription.locked_cash)
+    }
+
+    /// Moves the subscription of an investor to another one, merging them if
+    /// the other investor also subscribed
+    pub fn transfer_subscription(
+        &mut self,
+        from: &InvestorId,
+        to: &InvestorId,
+    ) -> Result<(), MoneyError> {
+        let Some(position) = self
+            .subscriptions
+            .iter()
+            .position(|s| &s.investor_id == from)
+        else {
+            return Ok(());
+        };
+        let subscription = self.subscriptions.remove(position);
+
+        match self.subscriptions.iter_mut().find(|s| &s.investor_id == to) {
+            Some(existing) => {
+                existing.locked_cash = existing
+                    .locked_cash
+                    .checked_add(&subscription.locked_cash)?;
+                existing.allotted_lots += subscription.allotted_lots;
+                existing.lots += subscription.lots;
+            }
+            None => self.subscriptions.push(IpoSubscription {
+                investor_id: *to,
+                ..subscription
+            }),
+        }
+
+        Ok(())
+    }
+
+    fn get_allotted_lots(&self) -> u64 {
+        self.subscriptions.iter().map(|s| s.allotted_lots).sum()
+    }
+
+    /// Distributes the lots one at a time, in a random order of the
+    /// subscribers, until there are no lots left or all requests are filled.
+    fn ballot_lots(&mut self, lots: u64, rng: &mut StdRng) {
+        let mut order = (0..self.subscriptions.len()).collect::<Vec<_>>();
+        order.shuffle(rng);
+
+        let mut remaining_lots = lots;
+
+        while remaining_lots > 0 {
+            let mut allotted_in_round = false;
+
+            for idx in order.iter() {
+                if remaining_lots == 0 {
+                    break;
+                }
+
+                let subscription = &mut self.subscriptions[*idx];
+
+                if subscription.allotted_lots < subscription.lots {
+                    subscription.allotted_lots += 1;
+                    remaining_lots -= 1;
+                    allotted_in_round = true;
+                }
+            }
+
+            if !allotted_in_round {
+                break;
+            }
+        }
+    }
+}
+
+impl Ipos {
+    pub fn get_pending_count(&self) -> usize {
+        self.mapping
+            .values()
+            .filter(|ipo| ipo.status == IpoStatus::Offering || ipo.status == IpoStatus::Allotted)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_fixtures::{build_money, build_symbol};
+    use rand::SeedableRng;
+
+    fn build_ipo(allotment_method: IpoAllotmentMethod, requested_lots: &[u64]) -> Ipo {
+        let mut investor_id = InvestorId::init();
+
+        Ipo {
+            allotment_method,
+            date: 0,
+            exchange: Default::default(),
+            first_day_close: None,
+            free_float: 1.0,
+            lot_size: 100,
+            offer_end: 0,
+            offer_price: build_money(2.0),
+            offer_start: 0,
+            offered_lots: 10,
+            shares: 1_000,
+            status: IpoStatus::Offering,
+            subscriptions: requested_lots
+                .iter()
+                .map(|lots| {
+                    investor_id = InvestorId::new(&investor_id);
+
+                    IpoSubscription {
+                        allotted_lots: 0,
+                        investor_id,
+                        locked_cash: build_money((lots * 200) as f64),
+                        lots: *lots,
+                    }
+                })
+                .collect(),
+            symbol: build_symbol("FOO"),
+        }
+    }
+
+    #[test]
+    fn test_allot_oversubscribed() {
+        let mut rng = StdRng::from_seed([0; 32]);
+
+        for method in [IpoAllotmentMethod::Ballot, IpoAllotmentMethod::ProRata] {
+            let mut ipo = build_ipo(method, &[10, 5, 5]);
+            let refunds = ipo.allot(&mut rng).unwrap();
+            let refunded: u64 = refunds.iter().map(|(_, m)| m.to_f64() as u64).sum();
+
+            assert_eq!(ipo.status, IpoStatus::Allotted);
+            assert_eq!(ipo.get_allotted_lots(), 10);
+            assert_eq!(ipo.shares, 1_000);
+            assert_eq!(refunded, 10 * 200);
+        }
+
+        let mut ipo = build_ipo(IpoAllotmentMethod::ProRata, &[10, 6, 4]);
+        ipo.allot(&mut rng).unwrap();
+
+        let allotted = ipo
+            .subscriptions
+            .iter()
+            .map(|s| s.allotted_lots)
+            .collect::<Vec<_>>();
+
+        assert_eq!(allotted, vec![5, 3, 2]);
+    }
+
+    #[test]
+    fn test_allot_undersubscribed() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let mut ipo = build_ipo(IpoAllotmentMethod::Ballot, &[3, 2]);
+        let refunds = ipo.allot(&mut rng).unwrap();
+
+        assert!(refunds.is_empty());
+        assert_eq!(ipo.shares, 500);
+        assert_eq!(ipo.get_oversubscription_ratio(), 0.5);
+
+        let mut ipo = build_ipo(IpoAllotmentMethod::Ballot, &[]);
+        ipo.allot(&mut rng).unwrap();
+
+        assert_eq!(ipo.status, IpoStatus::Withdrawn);
+    }
+}
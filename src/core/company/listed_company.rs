@@ -2,10 +2,13 @@ use super::{ListedCompanies, ListedCompany, ListedCompanyVerifyError};
 
 impl ListedCompany {
     pub fn verify(&self) -> Result<(), ListedCompanyVerifyError> {
+        if self.free_float <= 0.0 || self.free_float > 1.0 {
+            return Err(ListedCompanyVerifyError::FreeFloat);
+        }
         if self.lot_size == 0 {
             return Err(ListedCompanyVerifyError::LotSize);
         }
-        if self.total_stocks == 0 || self.total_stocks % self.lot_size != 0 {
+        if self.total_stocks == 0 || !self.total_stocks.is_multiple_of(self.lot_size) {
             return Err(ListedCompanyVerifyError::TotalStocks);
         }
         self.symbol
@@ -16,6 +19,12 @@ impl ListedCompany {
     }
 }
 
+impl ListedCompany {
+    pub fn get_free_float_shares(&self) -> u64 {
+        (self.total_stocks as f64 * self.free_float).floor() as u64
+    }
+}
+
 impl ListedCompanies {
     pub fn get_list(&self) -> Vec<ListedCompany> {
         self.mapping.values().cloned().collect::<Vec<_>>()
@@ -1,29 +1,65 @@
 use std::collections::BTreeMap;
 
+use super::{
+    investor::InvestorId,
+    money::{Money, MoneyError},
+    price::Price,
+    stock_exchange::ExchangeId,
+};
 use serde::{Deserialize, Serialize};
 
 mod base;
 mod company_symbol;
+mod delisted_company;
 mod ipo;
 mod listed_company;
+mod sector;
 
-#[derive(Clone, Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Ord)]
+#[derive(Clone, Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd)]
 #[serde(transparent)]
 pub struct CompanySymbol(pub(crate) String);
 
-impl PartialOrd for CompanySymbol {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 pub enum CompanySymbolVerifyError {
     Symbol,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CompanyStatus {
+    Private,
+    IpoPending,
+    Listed,
+    Suspended,
+    Delisted,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompanyStatusChange {
+    pub status: CompanyStatus,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sector {
+    CommunicationServices,
+    ConsumerDiscretionary,
+    ConsumerStaples,
+    Energy,
+    Financials,
+    HealthCare,
+    Industrials,
+    InformationTechnology,
+    Materials,
+    RealEstate,
+    Utilities,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Company {
+    pub industry: String,
     pub name: String,
+    pub sector: Sector,
+    pub status: CompanyStatus,
+    pub status_changes: Vec<CompanyStatusChange>,
     pub symbol: CompanySymbol,
 }
 
@@ -32,20 +68,43 @@ pub enum CompanyVerifyError {
     Symbol,
 }
 
+#[derive(Debug)]
+pub enum CompanyTransitionError {
+    Invalid {
+        from: CompanyStatus,
+        to: CompanyStatus,
+    },
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Companies {
     pub mapping: BTreeMap<CompanySymbol, Company>,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentKind {
+    Etf,
+    #[default]
+    Stock,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ListedCompany {
+    pub exchange: ExchangeId,
+    /** Ratio of the shares available for trading, excluding the strategic holdings */
+    pub free_float: f64,
+    pub instrument: InstrumentKind,
     pub lot_size: u64,
+    /** For a secondary listing, the one of the company in its home exchange */
+    pub primary_symbol: Option<CompanySymbol>,
     pub symbol: CompanySymbol,
     pub total_stocks: u64,
 }
 
 #[derive(Debug)]
 pub enum ListedCompanyVerifyError {
+    FreeFloat,
     LotSize,
     Name,
     Symbol,
@@ -57,12 +116,77 @@ pub struct ListedCompanies {
     pub mapping: BTreeMap<CompanySymbol, ListedCompany>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum DelistingReason {
+    LowPrice,
+    Privatization { offer_price: Money },
+    Suspension,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DelistedCompany {
+    pub delisted_at: u64,
+    pub last_price: Option<Price>,
+    pub listed_company: ListedCompany,
+    pub reason: DelistingReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DelistedCompanies {
+    pub mapping: BTreeMap<CompanySymbol, DelistedCompany>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum IpoStatus {
+    Offering,
+    Allotted,
+    Listed,
+    Withdrawn,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub enum IpoAllotmentMethod {
+    #[default]
+    Ballot,
+    ProRata,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IpoSubscription {
+    pub allotted_lots: u64,
+    pub investor_id: InvestorId,
+    pub locked_cash: Money,
+    pub lots: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Ipo {
-    pub symbol: CompanySymbol,
-    pub shares: u64,
-    pub lot_size: u64,
+    pub allotment_method: IpoAllotmentMethod,
+    /** Listing date, the first trading day */
     pub date: u64,
+    /** Exchange where the company lists */
+    pub exchange: ExchangeId,
+    pub first_day_close: Option<Money>,
+    pub free_float: f64,
+    pub lot_size: u64,
+    pub offer_end: u64,
+    pub offer_price: Money,
+    /** Lots of the offer, which remain after issuing fewer when undersubscribed */
+    pub offered_lots: u64,
+    pub offer_start: u64,
+    pub shares: u64,
+    pub status: IpoStatus,
+    pub subscriptions: Vec<IpoSubscription>,
+    pub symbol: CompanySymbol,
+}
+
+#[derive(Debug)]
+pub enum IpoSubscribeError {
+    AlreadySubscribed,
+    InsufficientCash,
+    MoneyError(MoneyError),
+    NoLots,
+    OfferClosed,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
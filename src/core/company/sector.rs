@@ -0,0 +1,35 @@
+use super::Sector;
+
+impl Sector {
+    pub fn all() -> [Sector; 11] {
+        [
+            Sector::CommunicationServices,
+            Sector::ConsumerDiscretionary,
+            Sector::ConsumerStaples,
+            Sector::Energy,
+            Sector::Financials,
+            Sector::HealthCare,
+            Sector::Industrials,
+            Sector::InformationTechnology,
+            Sector::Materials,
+            Sector::RealEstate,
+            Sector::Utilities,
+        ]
+    }
+
+    pub fn get_industries(&self) -> &'static [&'static str] {
+        match self {
+            Sector::CommunicationServices => &["Telecommunications", "Media", "Entertainment"],
+            Sector::ConsumerDiscretionary => &["Automobiles", "Retail", "Hotels", "Apparel"],
+            Sector::ConsumerStaples => &["Food Products", "Beverages", "Household Products"],
+            Sector::Energy => &["Oil & Gas", "Coal", "Energy Equipment"],
+            Sector::Financials => &["Banks", "Insurance", "Capital Markets"],
+            Sector::HealthCare => &["Pharmaceuticals", "Biotechnology", "Health Care Providers"],
+            Sector::Industrials => &["Construction", "Machinery", "Transportation"],
+            Sector::InformationTechnology => &["Software", "Semiconductors", "Hardware"],
+            Sector::Materials => &["Chemicals", "Metals & Mining", "Paper"],
+            Sector::RealEstate => &["Real Estate Development", "REITs"],
+            Sector::Utilities => &["Electric Utilities", "Gas Utilities", "Water Utilities"],
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use super::{
+    company::{CompanySymbol, Sector},
+    money::Money,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord,
+)]
+#[serde(transparent)]
+pub struct EtfId(u64);
+
+impl EtfId {
+    pub fn new(previous: &Self) -> Self {
+        Self(previous.0 + 1)
+    }
+
+    pub fn init() -> Self {
+        Self(0)
+    }
+}
+
+/// What the basket of the ETF replicates
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EtfTracking {
+    Index,
+    Sector {
+        sector: Sector,
+    },
+    /** Fixed shares of each symbol per creation unit */
+    Symbols {
+        basket: BTreeMap<CompanySymbol, u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Etf {
+    /** Shares of each symbol that have to be delivered to create a unit */
+    pub basket: BTreeMap<CompanySymbol, u64>,
+    /** Received from the holdings of the custodian, e.g. privatization offers */
+    pub cash: Money,
+    /** ETF shares created or redeemed at once */
+    pub creation_unit: u64,
+    pub id: EtfId,
+    /** Indicative net asset value per ETF share, updated every tick */
+    pub inav: f64,
+    pub name: String,
+    pub symbol: CompanySymbol,
+    pub tracking: EtfTracking,
+    /** ETF shares outstanding */
+    pub units: u64,
+}
+
+impl Etf {
+    /// Premium (positive) or discount (negative) of the price to the iNAV
+    pub fn get_premium(&self, price: f64) -> Option<f64> {
+        (self.inav > 0.0).then(|| price / self.inav - 1.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Etfs {
+    pub last_id: EtfId,
+    pub mapping: BTreeMap<EtfId, Etf>,
+}
+
+impl Etfs {
+    pub fn get_by_symbol(&self, symbol: &CompanySymbol) -> Option<&Etf> {
+        self.mapping.values().find(|etf| &etf.symbol == symbol)
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+
+use super::{
+    company::Sector,
+    investor::InvestorId,
+    money::{Money, MoneyError},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord,
+)]
+#[serde(transparent)]
+pub struct FundId(u64);
+
+impl FundId {
+    pub fn new(previous: &Self) -> Self {
+        Self(previous.0 + 1)
+    }
+
+    pub fn init() -> Self {
+        Self(0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum FundKind {
+    Insurer,
+    MutualFund,
+    PensionFund,
+}
+
+/// Restrictions on what the fund can hold
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FundMandate {
+    /** Sectors that the fund can invest in, all of them when empty */
+    pub allowed_sectors: Vec<Sector>,
+    /** Ratio of the net asset value that is kept in cash */
+    pub cash_ratio: f64,
+    /** Weights the companies like the market index, otherwise equally */
+    pub follow_benchmark: bool,
+    /** Maximum ratio of the net asset value in a single company */
+    pub max_position_ratio: f64,
+    /** Minimum free float market cap of the companies, with the price currency */
+    pub min_market_cap: f64,
+}
+
+impl FundMandate {
+    pub fn verify(&self) -> Result<(), String> {
+        if !(0.0..1.0).contains(&self.cash_ratio) {
+            return Err("The cash ratio of a fund has to be between 0 and 1".to_string());
+        }
+
+        if self.max_position_ratio <= 0.0 || self.max_position_ratio > 1.0 {
+            return Err("The max position ratio of a fund has to be between 0 and 1".to_string());
+        }
+
+        if self.min_market_cap < 0.0 {
+            return Err("The min market cap of a fund can't be negative".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn allows_sector(&self, sector: &Sector) -> bool {
+        self.allowed_sectors.is_empty() || self.allowed_sectors.contains(sector)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Fund {
+    pub cash: Money,
+    pub id: FundId,
+    pub kind: FundKind,
+    pub last_valuation: u64,
+    pub mandate: FundMandate,
+    pub name: String,
+    /** Net asset value per unit from the last valuation */
+    pub nav_per_unit: f64,
+    /** Units owned by retail investors, the rest belong to the sponsor */
+    pub unitholders: BTreeMap<InvestorId, f64>,
+    pub units: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FundSummary {
+    pub cash: Money,
+    pub id: FundId,
+    pub kind: FundKind,
+    pub last_valuation: u64,
+    pub mandate: FundMandate,
+    pub name: String,
+    pub nav: f64,
+    pub nav_per_unit: f64,
+    pub unitholders: usize,
+    pub units: f64,
+}
+
+impl Fund {
+    pub fn get_summary(&self) -> FundSummary {
+        FundSummary {
+            cash: self.cash,
+            id: self.id,
+            kind: self.kind,
+            last_valuation: self.last_valuation,
+            mandate: self.mandate.clone(),
+            name: self.name.clone(),
+            nav: self.get_nav(),
+            nav_per_unit: self.nav_per_unit,
+            unitholders: self.unitholders.len(),
+            units: self.units,
+        }
+    }
+
+    pub fn get_nav(&self) -> f64 {
+        self.nav_per_unit * self.units
+    }
+
+    pub fn get_investor_units(&self, investor_id: &InvestorId) -> f64 {
+        self.unitholders.get(investor_id).cloned().unwrap_or(0.0)
+    }
+
+    /// Issues units at the last NAV in exchange of the cash, and returns them
+    pub fn issue_units(
+        &mut self,
+        investor_id: &InvestorId,
+        amount: &Money,
+    ) -> Result<f64, MoneyError> {
+        if self.nav_per_unit <= 0.0 {
+            return Ok(0.0);
+        }
+
+        let units = amount.to_f64() / self.nav_per_unit;
+
+        self.cash = self.cash.checked_add(amount)?;
+        self.units += units;
+        *self.unitholders.entry(*investor_id).or_default() += units;
+
+        Ok(units)
+    }
+
+    /// Cancels the units at the last NAV, as many as the cash of the fund
+    /// allows, and returns what has to be paid
+    pub fn cancel_units(
+        &mut self,
+        investor_id: &InvestorId,
+        units: f64,
+    ) -> Result<Money, MoneyError> {
+        let payable_units = (self.cash.to_f64() / self.nav_per_unit.max(f64::EPSILON))
+            .min(units)
+            .min(self.get_investor_units(investor_id))
+            .max(0.0);
+        let value = Money {
+            currency: self.cash.currency,
+            value: Money::from_f64(payable_units * self.nav_per_unit).min(self.cash.value),
+        };
+
+        self.cash = self.cash.checked_sub(&value)?;
+        self.units -= payable_units;
+
+        if let Some(holding) = self.unitholders.get_mut(investor_id) {
+            *holding -= payable_units;
+
+            if *holding <= f64::EPSILON {
+                self.unitholders.remove(investor_id);
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Cancels the units without paying them, which raises the NAV per unit of
+    /// the rest
+    pub fn write_off_units(&mut self, investor_id: &InvestorId) {
+        if let Some(units) = self.unitholders.remove(investor_id) {
+            self.units -= units;
+        }
+    }
+
+    pub fn transfer_units(&mut self, from: &InvestorId, to: &InvestorId) {
+        if let Some(units) = self.unitholders.remove(from) {
+            *self.unitholders.entry(*to).or_default() += units;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Funds {
+    pub last_id: FundId,
+    pub mapping: BTreeMap<FundId, Fund>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_fixtures::build_money;
+
+    #[test]
+    fn test_fund_units() {
+        let investor_id = InvestorId::init();
+        let mut fund = Fund {
+            cash: build_money(100.0),
+            id: FundId::init(),
+            kind: FundKind::MutualFund,
+            last_valuation: 0,
+            mandate: FundMandate {
+                allowed_sectors: vec![],
+                cash_ratio: 0.05,
+                follow_benchmark: true,
+                max_position_ratio: 0.1,
+                min_market_cap: 0.0,
+            },
+            name: "Foo".to_string(),
+            nav_per_unit: 2.0,
+            unitholders: BTreeMap::new(),
+            units: 50.0,
+        };
+        let units = fund.issue_units(&investor_id, &build_money(20.0)).unwrap();
+
+        assert_eq!(units, 10.0);
+        assert_eq!(fund.units, 60.0);
+        assert_eq!(fund.cash.to_f64(), 120.0);
+
+        fund.cash.value = Money::from_f64(8.0);
+
+        // Only the units covered by the cash of the fund are redeemed
+        let paid = fund.cancel_units(&investor_id, 10.0).unwrap();
+
+        assert_eq!(paid.to_f64(), 8.0);
+        assert_eq!(fund.get_investor_units(&investor_id), 6.0);
+        assert_eq!(fund.units, 56.0);
+    }
+}
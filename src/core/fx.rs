@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use super::money::{Currency, Money};
+use serde::{Deserialize, Serialize};
+
+/// Exchange rates of the currencies against a base currency
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FxRates {
+    pub base: Currency,
+    pub last_update: u64,
+    /** Value of one unit of each currency, in the base currency */
+    pub rates: BTreeMap<Currency, f64>,
+}
+
+impl FxRates {
+    fn get_base_value(&self, currency: &Currency) -> Option<f64> {
+        if currency == &self.base {
+            return Some(1.0);
+        }
+
+        self.rates.get(currency).cloned().filter(|rate| *rate > 0.0)
+    }
+
+    /// Units of `to` that one unit of `from` buys
+    pub fn get_rate(&self, from: &Currency, to: &Currency) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+
+        Some(self.get_base_value(from)? / self.get_base_value(to)?)
+    }
+
+    pub fn convert(&self, amount: &Money, to: &Currency) -> Option<Money> {
+        let rate = self.get_rate(&amount.currency, to)?;
+
+        Some(Money {
+            currency: *to,
+            value: Money::from_f64(amount.to_f64() * rate),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_fixtures::build_money_in;
+
+    #[test]
+    fn test_convert() {
+        let fx_rates = FxRates {
+            base: Currency::Usd,
+            last_update: 0,
+            rates: [(Currency::Hkd, 0.128), (Currency::Eur, 1.08)]
+                .into_iter()
+                .collect(),
+        };
+        let amount = build_money_in(Currency::Eur, 100.0);
+
+        assert_eq!(
+            fx_rates.convert(&amount, &Currency::Usd).unwrap().to_f64(),
+            108.0
+        );
+        assert_eq!(
+            fx_rates.convert(&amount, &Currency::Hkd).unwrap().to_f64(),
+            843.75
+        );
+        assert_eq!(fx_rates.get_rate(&Currency::Hkd, &Currency::Hkd), Some(1.0));
+        assert!(fx_rates.convert(&amount, &Currency::Jpy).is_none());
+    }
+}
@@ -1,11 +1,13 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use super::{
-    money::{Money, MoneyVerifyError},
+    money::{Currency, Money, MoneyError, MoneyVerifyError, RoundingMode},
+    order::OrderSide,
+    stock_exchange::ExchangeId,
     time::TimeHandler,
 };
 use rand::{rngs::StdRng, seq::SliceRandom};
-use rust_decimal::Decimal;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Copy, PartialOrd, Ord)]
@@ -28,13 +30,104 @@ impl Default for InvestorId {
     }
 }
 
+/// How the investor decides which orders to place
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum InvestorStrategyKind {
+    Fundamental,
+    Index,
+    MeanReversion,
+    Momentum,
+    #[default]
+    Noise,
+}
+
+impl InvestorStrategyKind {
+    pub fn all() -> [InvestorStrategyKind; 5] {
+        [
+            InvestorStrategyKind::Fundamental,
+            InvestorStrategyKind::Index,
+            InvestorStrategyKind::MeanReversion,
+            InvestorStrategyKind::Momentum,
+            InvestorStrategyKind::Noise,
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStyle {
+    Limit,
+    #[default]
+    Market,
+}
+
+/// Furthest that a limit order can be from the market price, as a fraction
+const MAX_LIMIT_OFFSET: f64 = 0.02;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InvestorProfile {
+    pub investment_horizon_days: u64,
+    /** From 0 (limit price far from the market) to 1 (at the market price) */
+    pub limit_aggressiveness: f64,
+    /** How much more a loss weighs than a gain of the same size, at least 1 */
+    pub loss_aversion: f64,
+    pub order_style: OrderStyle,
+    /** From 0 to 1, scales the size of the positions */
+    pub risk_tolerance: f64,
+    /** Probability of acting each time the investor is considered for trading */
+    pub trading_frequency: f64,
+}
+
+impl Default for InvestorProfile {
+    fn default() -> Self {
+        Self {
+            investment_horizon_days: 365,
+            limit_aggressiveness: 1.0,
+            loss_aversion: 1.0,
+            order_style: OrderStyle::Market,
+            risk_tolerance: 1.0,
+            trading_frequency: 1.0,
+        }
+    }
+}
+
+impl InvestorProfile {
+    /// Limit price for an order, below the market price when buying and above
+    /// it when selling. The rounding keeps it on the same side.
+    pub fn get_limit_price(
+        &self,
+        side: &OrderSide,
+        market_price: &Money,
+    ) -> Result<Money, MoneyError> {
+        let offset = Decimal::from_f64((1.0 - self.limit_aggressiveness) * MAX_LIMIT_OFFSET)
+            .unwrap_or_default();
+        let (factor, rounding) = match side {
+            OrderSide::Buy => (Decimal::ONE - offset, RoundingMode::Down),
+            OrderSide::Sell => (Decimal::ONE + offset, RoundingMode::Up),
+        };
+
+        market_price.checked_scale(factor, rounding)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Investor {
+    /** Exchanges where the investor can trade besides the home one */
+    pub accounts: BTreeSet<ExchangeId>,
     pub debt: Money,
     pub dob: u64, // UNIX timestamp
+    /** Cash balances in currencies other than the one of the exchange */
+    pub foreign_cash: BTreeMap<Currency, Money>,
     pub id: InvestorId,
+    /** Since when the investor has debt, which starts the insolvency process */
+    pub in_debt_since: Option<u64>,
     pub liquid_cash: Money,
+    pub monthly_expenses: Money,
+    pub monthly_income: Money,
     pub name: String,
+    pub profile: InvestorProfile,
+    pub strategy: InvestorStrategyKind,
 }
 
 #[derive(Debug)]
@@ -69,30 +162,73 @@ impl Investor {
 }
 
 impl Investor {
-    pub fn subtract_cash(&mut self, amount: &Money) {
-        let diff = self.liquid_cash.value - amount.value;
-
-        if diff < Decimal::ZERO {
-            self.liquid_cash.value = Decimal::ZERO;
-            self.debt.value += diff.abs();
-        } else {
-            self.liquid_cash.value = diff;
+    /// Cash available in the currency, without the debt
+    pub fn get_cash(&self, currency: &Currency) -> Money {
+        if currency == &self.liquid_cash.currency {
+            return self.liquid_cash;
         }
+
+        self.foreign_cash
+            .get(currency)
+            .cloned()
+            .unwrap_or(Money::zero(*currency))
     }
 
-    pub fn add_cash(&mut self, amount: &Money) {
-        if self.debt.value > Decimal::ZERO {
-            let diff = self.debt.value - amount.value;
+    /// What the cash doesn't cover becomes debt. The other currencies are
+    /// taken from their own balance, which can't go below zero.
+    pub fn subtract_cash(&mut self, amount: &Money) -> Result<(), MoneyError> {
+        if amount.currency != self.liquid_cash.currency {
+            let rest = self.get_cash(&amount.currency).checked_sub(amount)?;
 
-            if diff < Decimal::ZERO {
-                self.debt.value = Decimal::ZERO;
-                self.liquid_cash.value += diff.abs();
+            if rest.is_negative() {
+                return Err(MoneyError::InsufficientFunds(amount.currency));
+            }
+
+            if rest.value.is_zero() {
+                self.foreign_cash.remove(&amount.currency);
             } else {
-                self.debt.value = diff;
+                self.foreign_cash.insert(amount.currency, rest);
             }
+
+            return Ok(());
+        }
+
+        let rest = self.liquid_cash.checked_sub(amount)?;
+
+        if rest.is_negative() {
+            self.debt = self.debt.checked_sub(&rest)?;
+            self.liquid_cash = Money::zero(self.liquid_cash.currency);
+        } else {
+            self.liquid_cash = rest;
+        }
+
+        Ok(())
+    }
+
+    /// The cash in other currencies goes to its own balance, without
+    /// repaying the debt
+    pub fn add_cash(&mut self, amount: &Money) -> Result<(), MoneyError> {
+        if amount.currency != self.liquid_cash.currency {
+            let balance = self
+                .foreign_cash
+                .entry(amount.currency)
+                .or_insert(Money::zero(amount.currency));
+
+            *balance = balance.checked_add(amount)?;
+
+            return Ok(());
+        }
+
+        let rest = self.debt.checked_sub(amount)?;
+
+        if rest.is_negative() {
+            self.liquid_cash = self.liquid_cash.checked_sub(&rest)?;
+            self.debt = Money::zero(self.debt.currency);
         } else {
-            self.liquid_cash.value += amount.value;
+            self.debt = rest;
         }
+
+        Ok(())
     }
 }
 
@@ -108,6 +244,19 @@ impl Investors {
 
         investors_list.choose(r).unwrap()
     }
+
+    /// Allocates the id for a new investor, continuing after the last one
+    pub fn next_id(&mut self) -> InvestorId {
+        self.last_id = InvestorId::new(&self.last_id);
+        self.last_id
+    }
+
+    pub fn count_with_strategy(&self, strategy: InvestorStrategyKind) -> usize {
+        self.mapping
+            .values()
+            .filter(|investor| investor.strategy == strategy)
+            .count()
+    }
 }
 
 #[cfg(test)]
@@ -132,4 +281,83 @@ mod test {
         let id = InvestorId::new(&id);
         assert_eq!(id.0, 1);
     }
+
+    #[test]
+    fn test_subtract_cash() {
+        use super::*;
+        use crate::core::test_fixtures::{build_investor, build_money_in};
+
+        let mut investor = Investor {
+            foreign_cash: BTreeMap::from([(Currency::Usd, build_money_in(Currency::Usd, 10.0))]),
+            ..build_investor(InvestorId::init(), build_money_in(Currency::Hkd, 10.0))
+        };
+
+        // The shortfall of the home currency becomes debt
+        investor
+            .subtract_cash(&build_money_in(Currency::Hkd, 15.0))
+            .unwrap();
+
+        assert!(investor.liquid_cash.value.is_zero());
+        assert_eq!(investor.debt.value, Money::from_f64(5.0));
+
+        // The foreign balances don't go negative
+        assert_eq!(
+            investor.subtract_cash(&build_money_in(Currency::Usd, 15.0)),
+            Err(MoneyError::InsufficientFunds(Currency::Usd))
+        );
+        assert_eq!(
+            investor.get_cash(&Currency::Usd).value,
+            Money::from_f64(10.0)
+        );
+
+        investor
+            .subtract_cash(&build_money_in(Currency::Usd, 10.0))
+            .unwrap();
+
+        assert!(!investor.foreign_cash.contains_key(&Currency::Usd));
+    }
+
+    #[test]
+    fn test_limit_price() {
+        use super::*;
+        use crate::core::test_fixtures::build_money;
+
+        let profile = InvestorProfile {
+            limit_aggressiveness: 0.0,
+            ..Default::default()
+        };
+        let limit_prices = |profile: &InvestorProfile, market_price: f64| {
+            [OrderSide::Buy, OrderSide::Sell].map(|side| {
+                profile
+                    .get_limit_price(&side, &build_money(market_price))
+                    .unwrap()
+                    .value
+            })
+        };
+
+        // The least aggressive investors are 2% away from the market
+        assert_eq!(
+            limit_prices(&profile, 10.0),
+            [Money::from_f64(9.8), Money::from_f64(10.2)]
+        );
+        // Rounded away from the market, to the cent
+        assert_eq!(
+            limit_prices(&profile, 10.01),
+            [Money::from_f64(9.8), Money::from_f64(10.22)]
+        );
+
+        let profile = InvestorProfile {
+            limit_aggressiveness: 0.5,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            limit_prices(&profile, 10.0),
+            [Money::from_f64(9.9), Money::from_f64(10.1)]
+        );
+        assert_eq!(
+            limit_prices(&InvestorProfile::default(), 10.0),
+            [Money::from_f64(10.0), Money::from_f64(10.0)]
+        );
+    }
 }
@@ -0,0 +1,103 @@
+use super::{
+    company::CompanySymbol, investor::InvestorId, money::Money, order::Order, price::Prices,
+    stock::StockOwner, stock_exchange::ExchangeId, time::TimeHandler,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Change of the state of the simulation. A replay runs the same ticks from
+/// the seed and compares its events with the recorded ones, which checks that
+/// the simulation is deterministic.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum JournalEvent {
+    /** Cash of the owner in the currency at the end of the tick */
+    CashChanged {
+        cash: Money,
+        owner: StockOwner,
+    },
+    /** State after the events since the previous checkpoint */
+    Checkpoint {
+        state_hash: u64,
+    },
+    /** Shares of the symbol that the owner has at the end of the tick */
+    HoldingChanged {
+        owner: StockOwner,
+        shares: u64,
+        symbol: CompanySymbol,
+    },
+    HolidaysSet {
+        exchange: ExchangeId,
+        holidays: BTreeSet<String>,
+        year: String,
+    },
+    InvestorAdded {
+        investor_id: InvestorId,
+    },
+    InvestorRemoved {
+        investor_id: InvestorId,
+    },
+    OrderFilled {
+        buyer: StockOwner,
+        seller: StockOwner,
+        shares: u64,
+        symbol: CompanySymbol,
+        total: Money,
+    },
+    OrderPlaced {
+        order: Order,
+    },
+    PricesUpdated {
+        prices: Prices,
+    },
+    /** The saved state was loaded again, with a new seed from then on */
+    Resumed {
+        seed: [u8; 32],
+    },
+    TickStarted {
+        time: TimeHandler,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JournalEntry {
+    #[serde(flatten)]
+    pub event: JournalEvent,
+    pub sequence: u64,
+}
+
+/// FNV-1a, which is stable across builds unlike the `DefaultHasher`
+pub fn get_state_hash(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_journal_entry() {
+        assert_eq!(get_state_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(get_state_hash(b"a"), 0xaf63dc4c8601ec8c);
+
+        let entry = JournalEntry {
+            event: JournalEvent::Checkpoint { state_hash: 1 },
+            sequence: 2,
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+
+        assert_eq!(json, r#"{"type":"checkpoint","state_hash":1,"sequence":2}"#);
+        assert!(matches!(
+            serde_json::from_str::<JournalEntry>(&json).unwrap(),
+            JournalEntry {
+                event: JournalEvent::Checkpoint { state_hash: 1 },
+                sequence: 2
+            }
+        ));
+    }
+}
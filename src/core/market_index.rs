@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use super::{company::CompanySymbol, price::Prices};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MarketIndexSettings {
+    pub base_level: f64,
+    pub max_constituents: usize,
+    pub min_free_float: f64,
+    pub name: String,
+    pub rebalance_days: u64,
+}
+
+impl Default for MarketIndexSettings {
+    fn default() -> Self {
+        Self {
+            base_level: 1000.0,
+            max_constituents: 50,
+            min_free_float: 0.25,
+            name: "MSI".to_string(),
+            rebalance_days: 90,
+        }
+    }
+}
+
+/// Free-float market capitalization weighted index. The divisor is adjusted
+/// on every change of the constituents or their shares, so the level only
+/// moves with the prices.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MarketIndex {
+    /** Free-float shares of each constituent */
+    pub constituents: BTreeMap<CompanySymbol, u64>,
+    pub divisor: f64,
+    pub last_rebalance: u64,
+    pub level: f64,
+    pub settings: MarketIndexSettings,
+}
+
+impl MarketIndex {
+    pub fn new(settings: MarketIndexSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_market_cap(&self, prices: &Prices) -> f64 {
+        Self::get_constituents_market_cap(&self.constituents, prices)
+    }
+
+    fn get_constituents_market_cap(
+        constituents: &BTreeMap<CompanySymbol, u64>,
+        prices: &Prices,
+    ) -> f64 {
+        constituents
+            .iter()
+            .filter_map(|(symbol, shares)| {
+                prices
+                    .get_average_price(symbol)
+                    .map(|price| price.to_f64() * *shares as f64)
+            })
+            .sum()
+    }
+
+    /// Replaces the constituents, adjusting the divisor with the prices at
+    /// the moment of the change
+    pub fn set_constituents(
+        &mut self,
+        constituents: BTreeMap<CompanySymbol, u64>,
+        prices: &Prices,
+    ) {
+        let cap_before = self.get_market_cap(prices);
+        let cap_after = Self::get_constituents_market_cap(&constituents, prices);
+
+        self.constituents = constituents;
+
+        if cap_after <= 0.0 {
+            return;
+        }
+
+        if self.divisor <= 0.0 || cap_before <= 0.0 {
+            self.divisor = cap_after / self.settings.base_level;
+        } else {
+            self.divisor *= cap_after / cap_before;
+        }
+
+        self.update_level(prices);
+    }
+
+    pub fn update_level(&mut self, prices: &Prices) {
+        if self.divisor <= 0.0 {
+            return;
+        }
+
+        self.level = self.get_market_cap(prices) / self.divisor;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_fixtures::{build_money, build_price, build_symbol};
+
+    fn build_prices(values: &[(&str, f64)]) -> Prices {
+        Prices(
+            values
+                .iter()
+                .map(|(symbol, value)| (build_symbol(symbol), build_price(build_money(*value))))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_level_does_not_jump_on_constituent_changes() {
+        let mut index = MarketIndex::new(Default::default());
+        let prices = build_prices(&[("A", 10.0), ("B", 20.0), ("C", 5.0)]);
+        let constituents = |items: &[(&str, u64)]| {
+            items
+                .iter()
+                .map(|(symbol, shares)| (CompanySymbol::new(symbol.to_string()), *shares))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        index.set_constituents(constituents(&[("A", 100), ("B", 100)]), &prices);
+        assert_eq!(index.level, 1000.0);
+
+        let prices = build_prices(&[("A", 11.0), ("B", 22.0), ("C", 5.0)]);
+        index.update_level(&prices);
+        assert!((index.level - 1100.0).abs() < 1e-9);
+
+        index.set_constituents(constituents(&[("A", 100), ("B", 100), ("C", 500)]), &prices);
+        assert!((index.level - 1100.0).abs() < 1e-9);
+
+        index.set_constituents(constituents(&[("B", 100), ("C", 500)]), &prices);
+        assert!((index.level - 1100.0).abs() < 1e-9);
+    }
+}
@@ -1,10 +1,19 @@
 pub mod broker;
+pub mod candle;
 pub mod company;
+pub mod etf;
+pub mod fund;
+pub mod fx;
 pub mod investor;
+pub mod journal;
+pub mod market_index;
 pub mod market_maker;
 pub mod money;
 pub mod order;
 pub mod price;
+pub mod session;
 pub mod stock;
 pub mod stock_exchange;
+#[cfg(test)]
+pub mod test_fixtures;
 pub mod time;
@@ -1,15 +1,74 @@
 use rand::{rngs::StdRng, Rng};
-use rust_decimal::{prelude::FromPrimitive, prelude::ToPrimitive, Decimal};
+use rust_decimal::{prelude::FromPrimitive, prelude::ToPrimitive, Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
-use std::ops::Add;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone, Default)]
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Copy, Clone, Default, PartialOrd, Ord,
+)]
 pub enum Currency {
+    Cny,
+    Eur,
     #[default]
     Hkd,
+    Jpy,
+    Usd,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Copy)]
+impl Currency {
+    pub fn all() -> [Currency; 5] {
+        [
+            Currency::Cny,
+            Currency::Eur,
+            Currency::Hkd,
+            Currency::Jpy,
+            Currency::Usd,
+        ]
+    }
+
+    /// Decimals of the minor unit, e.g. the cents
+    pub fn get_minor_units(&self) -> u32 {
+        match self {
+            Currency::Jpy => 0,
+            _ => 2,
+        }
+    }
+
+    /// ISO 4217 code
+    pub fn get_code(&self) -> &'static str {
+        match self {
+            Currency::Cny => "CNY",
+            Currency::Eur => "EUR",
+            Currency::Hkd => "HKD",
+            Currency::Jpy => "JPY",
+            Currency::Usd => "USD",
+        }
+    }
+}
+
+/// How the amounts are rounded to the minor unit of their currency
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /** Half to even, which doesn't bias the totals */
+    #[default]
+    Bankers,
+    /** Towards zero */
+    Down,
+    /** Away from zero */
+    Up,
+}
+
+impl RoundingMode {
+    fn get_strategy(&self) -> RoundingStrategy {
+        match self {
+            RoundingMode::Bankers => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::Down => RoundingStrategy::ToZero,
+            RoundingMode::Up => RoundingStrategy::AwayFromZero,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Copy, PartialEq)]
 pub struct Money {
     pub currency: Currency,
     pub value: Decimal,
@@ -21,32 +80,149 @@ pub enum MoneyVerifyError {
     TooManyDecimals,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoneyError {
+    CurrencyMismatch(Currency, Currency),
+    DivisionByZero,
+    Empty,
+    InsufficientFunds(Currency),
+    Overflow,
+}
+
+impl std::fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoneyError::CurrencyMismatch(left, right) => write!(
+                f,
+                "Can't operate {} with {}",
+                left.get_code(),
+                right.get_code()
+            ),
+            MoneyError::DivisionByZero => write!(f, "Division of money by zero"),
+            MoneyError::Empty => write!(f, "There is no money to operate"),
+            MoneyError::InsufficientFunds(currency) => {
+                write!(f, "Not enough {} to subtract", currency.get_code())
+            }
+            MoneyError::Overflow => write!(f, "Money overflow"),
+        }
+    }
+}
+
 impl Money {
     pub fn verify(&self) -> Result<(), MoneyVerifyError> {
-        let cash_decimals_digits = self.value.to_string().split('.').nth(1).unwrap_or("").len();
-
         if self.value.lt(&Decimal::new(0, 0)) {
             return Err(MoneyVerifyError::NegativeValue);
         }
 
-        if cash_decimals_digits > 2 {
+        if self.value.normalize().scale() > self.currency.get_minor_units() {
             Err(MoneyVerifyError::TooManyDecimals)
         } else {
             Ok(())
         }
     }
 
-    pub fn calculate_average(prices: &[Money]) -> Money {
-        let total: Decimal = prices.iter().map(|price| price.value).sum();
-        let average = total
-            .checked_div(Decimal::new(prices.len() as i64, 0))
-            .unwrap();
-        let value = average.round_dp(2);
+    pub fn zero(currency: Currency) -> Money {
+        Money {
+            currency,
+            value: Decimal::ZERO,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.value.is_sign_negative() && !self.value.is_zero()
+    }
 
+    /// Rounds to the minor unit of the currency
+    pub fn round(&self, mode: RoundingMode) -> Money {
         Money {
-            currency: prices[0].currency,
+            currency: self.currency,
+            value: self
+                .value
+                .round_dp_with_strategy(self.currency.get_minor_units(), mode.get_strategy()),
+        }
+    }
+
+    fn check_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch(self.currency, other.currency));
+        }
+
+        Ok(())
+    }
+
+    pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.check_currency(other)?;
+
+        Ok(Money {
+            currency: self.currency,
+            value: self
+                .value
+                .checked_add(other.value)
+                .ok_or(MoneyError::Overflow)?,
+        })
+    }
+
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.check_currency(other)?;
+
+        Ok(Money {
+            currency: self.currency,
+            value: self
+                .value
+                .checked_sub(other.value)
+                .ok_or(MoneyError::Overflow)?,
+        })
+    }
+
+    /// Total of a quantity at this price, which needs no rounding
+    pub fn checked_mul(&self, quantity: u64) -> Result<Money, MoneyError> {
+        Ok(Money {
+            currency: self.currency,
+            value: self
+                .value
+                .checked_mul(Decimal::from(quantity))
+                .ok_or(MoneyError::Overflow)?,
+        })
+    }
+
+    /// Multiplies by a factor, e.g. a ratio, rounding to the minor unit
+    pub fn checked_scale(&self, factor: Decimal, mode: RoundingMode) -> Result<Money, MoneyError> {
+        let value = self.value.checked_mul(factor).ok_or(MoneyError::Overflow)?;
+
+        Ok(Money {
+            currency: self.currency,
             value,
         }
+        .round(mode))
+    }
+
+    /// Divides, e.g. a total by a quantity, rounding to the minor unit
+    pub fn checked_div(&self, divisor: Decimal, mode: RoundingMode) -> Result<Money, MoneyError> {
+        if divisor.is_zero() {
+            return Err(MoneyError::DivisionByZero);
+        }
+
+        let value = self
+            .value
+            .checked_div(divisor)
+            .ok_or(MoneyError::Overflow)?;
+
+        Ok(Money {
+            currency: self.currency,
+            value,
+        }
+        .round(mode))
+    }
+
+    pub fn calculate_average(prices: &[Money]) -> Result<Money, MoneyError> {
+        let Some(first) = prices.first() else {
+            return Err(MoneyError::Empty);
+        };
+        let total = prices[1..]
+            .iter()
+            .try_fold(*first, |total, price| total.checked_add(price))?;
+
+        total.checked_div(Decimal::from(prices.len()), RoundingMode::Bankers)
     }
 
     pub fn gen_from_range(r: &mut StdRng, range: (f64, f64)) -> Decimal {
@@ -63,22 +239,50 @@ impl Money {
         Decimal::from_f64(value).unwrap().round_dp(2)
     }
 
-    pub fn to_f64(&self) -> f64 {
+    pub fn to_f64(self) -> f64 {
         self.value.to_f64().unwrap()
     }
 }
 
-impl Add for Money {
-    type Output = Money;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_fixtures::build_money_in;
 
-    fn add(self, other: Money) -> Money {
-        if self.currency != other.currency {
-            panic!("Cannot add money of different currencies");
-        }
+    #[test]
+    fn test_checked_arithmetic() {
+        let hkd = build_money_in(Currency::Hkd, 10.25);
+        let usd = build_money_in(Currency::Usd, 1.0);
 
-        Money {
-            currency: self.currency,
-            value: self.value + other.value,
-        }
+        assert_eq!(
+            hkd.checked_add(&usd).unwrap_err(),
+            MoneyError::CurrencyMismatch(Currency::Hkd, Currency::Usd)
+        );
+        assert_eq!(hkd.checked_sub(&hkd).unwrap().value, Decimal::ZERO);
+        assert_eq!(hkd.checked_mul(3).unwrap().value, "30.75".parse().unwrap());
+        assert_eq!(
+            hkd.checked_div(Decimal::ZERO, RoundingMode::Bankers)
+                .unwrap_err(),
+            MoneyError::DivisionByZero
+        );
+        assert_eq!(
+            Money::calculate_average(&[]).unwrap_err(),
+            MoneyError::Empty
+        );
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let value = build_money_in(Currency::Hkd, 2.125);
+        let rounded = [RoundingMode::Bankers, RoundingMode::Down, RoundingMode::Up]
+            .map(|mode| value.round(mode).value.to_string());
+
+        assert_eq!(rounded, ["2.12", "2.12", "2.13"]);
+
+        // The yen has no minor unit
+        let value = build_money_in(Currency::Jpy, 1234.5);
+
+        assert_eq!(value.round(RoundingMode::Bankers).value.to_string(), "1234");
+        assert!(build_money_in(Currency::Jpy, 10.5).verify().is_err());
     }
 }
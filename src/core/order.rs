@@ -1,6 +1,7 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
-use super::{company::CompanySymbol, stock::StockOwner};
+use super::{company::CompanySymbol, money::Money, stock::StockOwner};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash, Eq)]
@@ -38,6 +39,21 @@ pub enum OrderVerifyError {
 }
 
 impl Order {
+    /// Whether the order can be executed at the price per share
+    pub fn accepts_price(&self, price: &Money) -> bool {
+        let OrderType::Limit { price: limit_price } = &self.order_type else {
+            return true;
+        };
+        let Ok(limit_price) = limit_price.parse::<Decimal>() else {
+            return false;
+        };
+
+        match self.order_side {
+            OrderSide::Buy => price.value <= limit_price,
+            OrderSide::Sell => price.value >= limit_price,
+        }
+    }
+
     pub fn verify(&self) -> Result<(), OrderVerifyError> {
         if self.shares == 0 {
             return Err(OrderVerifyError::NoShares);
@@ -55,6 +71,42 @@ impl CentralOrderBook {
         self.0.iter().any(|order| order.owner_id == *owner_id)
     }
 
+    /// Shares waiting to be bought minus the ones waiting to be sold, of each
+    /// symbol with orders
+    pub fn get_imbalances(&self) -> BTreeMap<CompanySymbol, i128> {
+        let mut imbalances = BTreeMap::new();
+
+        for order in &self.0 {
+            let shares = match order.order_side {
+                OrderSide::Buy => order.shares as i128,
+                OrderSide::Sell => -(order.shares as i128),
+            };
+
+            *imbalances.entry(order.symbol.clone()).or_default() += shares;
+        }
+
+        imbalances
+    }
+
+    pub fn cancel_symbol_orders(&mut self, symbol: &CompanySymbol) {
+        self.0.retain(|order| &order.symbol != symbol);
+    }
+
+    /// Orders of each symbol, in the same order as in the book
+    pub fn split_by_symbol(&self) -> BTreeMap<CompanySymbol, CentralOrderBook> {
+        let mut books: BTreeMap<CompanySymbol, CentralOrderBook> = BTreeMap::new();
+
+        for order in &self.0 {
+            books
+                .entry(order.symbol.clone())
+                .or_default()
+                .0
+                .push(order.clone());
+        }
+
+        books
+    }
+
     pub fn get_matching_orders(
         &self,
         order: &Order,
@@ -66,7 +118,7 @@ impl CentralOrderBook {
                 o.symbol == order.symbol
                     && o.order_side != order.order_side
                     && o.owner_id != order.owner_id
-                    && skipped.map_or(true, |skipped| !skipped.contains(o))
+                    && skipped.is_none_or(|skipped| !skipped.contains(o))
             })
             .cloned()
             .collect()
@@ -76,6 +128,30 @@ impl CentralOrderBook {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::test_fixtures::build_money;
+
+    #[test]
+    fn test_accepts_price() {
+        let price = build_money(10.0);
+        let mut order = Order {
+            owner_id: StockOwner::default(),
+            order_side: OrderSide::Buy,
+            order_type: OrderType::Limit {
+                price: "9.5".to_string(),
+            },
+            shares: 100,
+            status: OrderStatus::Init,
+            symbol: CompanySymbol::new("AAPL".to_string()),
+        };
+
+        assert!(!order.accepts_price(&price));
+
+        order.order_side = OrderSide::Sell;
+        assert!(order.accepts_price(&price));
+
+        order.order_type = OrderType::Market;
+        assert!(order.accepts_price(&price));
+    }
 
     #[test]
     fn compare_orders() {
@@ -1,7 +1,14 @@
-use super::{company::CompanySymbol, money::Money};
+use super::{
+    company::CompanySymbol,
+    fx::FxRates,
+    money::{Currency, Money, MoneyError, RoundingMode},
+};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Number of returns kept per symbol to calculate the realized correlations
+const RETURNS_WINDOW: usize = 250;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Copy)]
 pub struct Price {
@@ -10,12 +17,11 @@ pub struct Price {
 }
 
 impl Price {
-    pub fn get_average(&self) -> Money {
-        let value = (self.ask.value + self.bid.value) / Money::from_f64(2.0);
-        Money {
-            currency: self.ask.currency,
-            value,
-        }
+    /// Midpoint of the quote, rounded to the minor unit
+    pub fn get_average(&self) -> Result<Money, MoneyError> {
+        self.ask
+            .checked_add(&self.bid)?
+            .checked_div(Decimal::TWO, RoundingMode::Bankers)
     }
 
     pub fn get_with_spread(&self, value: Decimal, spread: Decimal) -> Price {
@@ -36,18 +42,252 @@ impl Price {
 pub struct Prices(pub BTreeMap<CompanySymbol, Price>);
 
 impl Prices {
-    pub fn get_lowest_bid_price(&self) -> Option<&Money> {
-        self.0
+    /// Lowest bid of all the listings, converted to the currency. The bids
+    /// without a rate to it are left out.
+    pub fn get_lowest_bid_price(&self, fx_rates: &FxRates, currency: &Currency) -> Option<Money> {
+        // The conversion keeps the order, so only the lowest bid of each
+        // currency is converted
+        let mut lowest_bids: BTreeMap<Currency, Money> = BTreeMap::new();
+
+        for price in self.0.values() {
+            lowest_bids
+                .entry(price.bid.currency)
+                .and_modify(|lowest_bid| {
+                    if price.bid.value < lowest_bid.value {
+                        *lowest_bid = price.bid;
+                    }
+                })
+                .or_insert(price.bid);
+        }
+
+        lowest_bids
             .values()
-            .map(|price| &price.bid)
-            .min_by(|a, b| a.value.partial_cmp(&b.value).unwrap())
+            .filter_map(|bid| {
+                if &bid.currency == currency {
+                    Some(*bid)
+                } else {
+                    fx_rates.convert(bid, currency)
+                }
+            })
+            .min_by(|a, b| a.value.cmp(&b.value))
     }
 
     pub fn get_average_price(&self, symbol: &CompanySymbol) -> Option<Money> {
-        self.0.get(symbol).map(|price| price.get_average())
+        self.0
+            .get(symbol)
+            .and_then(|price| price.get_average().ok())
     }
 
     pub fn get_ask_price(&self, symbol: &CompanySymbol) -> Option<&Money> {
         self.0.get(symbol).map(|price| &price.ask)
     }
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PriceReturns(pub BTreeMap<CompanySymbol, VecDeque<f64>>);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CorrelationMatrix {
+    pub symbols: Vec<CompanySymbol>,
+    pub values: Vec<Vec<Option<f64>>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HistoricPrice {
+    pub price: f64,
+    pub time: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PriceHistoryPage {
+    /** Value of `from` to request the following page */
+    pub next_from: Option<u64>,
+    pub prices: Vec<HistoricPrice>,
+}
+
+impl PriceHistoryPage {
+    /// Keeps the last price of every `step` seconds bucket, and splits the
+    /// result in pages of `limit` prices. The samples have to be sorted by
+    /// time.
+    pub fn new(samples: Vec<HistoricPrice>, step: Option<u64>, limit: usize) -> Self {
+        let mut prices = match step.filter(|step| *step > 1) {
+            None => samples,
+            Some(step) => {
+                let mut downsampled: Vec<HistoricPrice> = Vec::new();
+
+                for sample in samples {
+                    match downsampled.last_mut() {
+                        Some(last) if last.time / step == sample.time / step => {
+                            *last = sample;
+                        }
+                        _ => downsampled.push(sample),
+                    }
+                }
+
+                downsampled
+            }
+        };
+
+        let next_from = prices.get(limit).map(|price| price.time);
+
+        prices.truncate(limit);
+
+        Self { next_from, prices }
+    }
+}
+
+impl PriceReturns {
+    /// Records the log returns between two consecutive prices
+    pub fn record(&mut self, previous: &Prices, current: &Prices) {
+        self.0.retain(|symbol, _| current.0.contains_key(symbol));
+
+        for (symbol, price) in current.0.iter() {
+            let Some(previous_price) = previous.get_average_price(symbol) else {
+                continue;
+            };
+            let before = previous_price.to_f64();
+            let Ok(after) = price.get_average().map(|price| price.to_f64()) else {
+                continue;
+            };
+
+            if before <= 0.0 || after <= 0.0 {
+                continue;
+            }
+
+            let returns = self.0.entry(symbol.clone()).or_default();
+
+            returns.push_back((after / before).ln());
+
+            if returns.len() > RETURNS_WINDOW {
+                returns.pop_front();
+            }
+        }
+    }
+
+    pub fn get_correlation(&self, a: &CompanySymbol, b: &CompanySymbol) -> Option<f64> {
+        let a_returns = self.0.get(a)?;
+        let b_returns = self.0.get(b)?;
+        let n = a_returns.len().min(b_returns.len());
+
+        if n < 2 {
+            return None;
+        }
+
+        let xs = a_returns.iter().skip(a_returns.len() - n);
+        let ys = b_returns.iter().skip(b_returns.len() - n);
+        let x_mean = xs.clone().sum::<f64>() / n as f64;
+        let y_mean = ys.clone().sum::<f64>() / n as f64;
+
+        let (mut covariance, mut x_variance, mut y_variance) = (0.0, 0.0, 0.0);
+
+        for (x, y) in xs.zip(ys) {
+            covariance += (x - x_mean) * (y - y_mean);
+            x_variance += (x - x_mean).powi(2);
+            y_variance += (y - y_mean).powi(2);
+        }
+
+        if x_variance == 0.0 || y_variance == 0.0 {
+            return None;
+        }
+
+        Some(covariance / (x_variance * y_variance).sqrt())
+    }
+
+    pub fn get_correlation_matrix(&self) -> CorrelationMatrix {
+        let symbols = self.0.keys().cloned().collect::<Vec<_>>();
+        let values = symbols
+            .iter()
+            .map(|a| symbols.iter().map(|b| self.get_correlation(a, b)).collect())
+            .collect();
+
+        CorrelationMatrix { symbols, values }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_fixtures::{build_money_in, build_price, build_symbol};
+
+    #[test]
+    fn test_price_history_page() {
+        let samples = (0..10)
+            .map(|i| HistoricPrice {
+                price: i as f64,
+                time: i * 30,
+            })
+            .collect::<Vec<_>>();
+
+        let page = PriceHistoryPage::new(samples.clone(), Some(60), 3);
+        let prices = page.prices.iter().map(|p| p.price).collect::<Vec<_>>();
+
+        assert_eq!(prices, vec![1.0, 3.0, 5.0]);
+        assert_eq!(page.next_from, Some(210));
+
+        let page = PriceHistoryPage::new(samples, None, 20);
+
+        assert_eq!(page.prices.len(), 10);
+        assert_eq!(page.next_from, None);
+    }
+
+    #[test]
+    fn test_correlation() {
+        let returns = PriceReturns(
+            vec![
+                (CompanySymbol::new("A".to_string()), vec![0.1, -0.2, 0.3]),
+                (CompanySymbol::new("B".to_string()), vec![0.2, -0.4, 0.6]),
+                (CompanySymbol::new("C".to_string()), vec![-0.1, 0.2, -0.3]),
+                (CompanySymbol::new("D".to_string()), vec![0.1]),
+            ]
+            .into_iter()
+            .map(|(symbol, values)| (symbol, values.into_iter().collect()))
+            .collect(),
+        );
+        let matrix = returns.get_correlation_matrix();
+
+        assert!((matrix.values[0][1].unwrap() - 1.0).abs() < 1e-9);
+        assert!((matrix.values[0][2].unwrap() + 1.0).abs() < 1e-9);
+        assert_eq!(matrix.values[0][3], None);
+    }
+
+    #[test]
+    fn test_lowest_bid_in_the_currency() {
+        let prices = Prices(BTreeMap::from([
+            (
+                build_symbol("FOO"),
+                build_price(build_money_in(Currency::Hkd, 6.0)),
+            ),
+            (
+                build_symbol("FOO.SH"),
+                build_price(build_money_in(Currency::Cny, 4.0)),
+            ),
+        ]));
+        let fx_rates = FxRates {
+            base: Currency::Hkd,
+            last_update: 0,
+            rates: BTreeMap::from([(Currency::Cny, 2.0)]),
+        };
+
+        // 4 yuan are 8 Hong Kong dollars
+        assert_eq!(
+            prices
+                .get_lowest_bid_price(&fx_rates, &Currency::Hkd)
+                .unwrap()
+                .value,
+            Money::from_f64(6.0)
+        );
+        assert_eq!(
+            prices
+                .get_lowest_bid_price(&fx_rates, &Currency::Cny)
+                .unwrap()
+                .value,
+            Money::from_f64(3.0)
+        );
+        assert_eq!(
+            prices
+                .get_lowest_bid_price(&FxRates::default(), &Currency::Cny)
+                .map(|bid| bid.value),
+            Some(Money::from_f64(4.0))
+        );
+    }
+}
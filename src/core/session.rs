@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Minute of the day in the local time of an exchange, serialized as "HH:MM"
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct SessionTime(pub u16);
+
+impl SessionTime {
+    pub fn new(hour: u16, minute: u16) -> Self {
+        SessionTime(hour * 60 + minute)
+    }
+}
+
+impl TryFrom<String> for SessionTime {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("Invalid session time {:?}, expected HH:MM", value);
+        let (hour, minute) = value.split_once(':').ok_or_else(invalid)?;
+        let hour = hour.parse::<u16>().map_err(|_| invalid())?;
+        let minute = minute.parse::<u16>().map_err(|_| invalid())?;
+
+        // "24:00" is the end of the day
+        if minute >= 60 || hour * 60 + minute > MINUTES_PER_DAY {
+            return Err(invalid());
+        }
+
+        Ok(SessionTime::new(hour, minute))
+    }
+}
+
+impl From<SessionTime> for String {
+    fn from(time: SessionTime) -> Self {
+        format!("{:02}:{:02}", time.0 / 60, time.0 % 60)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionKind {
+    PreOpen,
+    MorningContinuous,
+    Lunch,
+    AfternoonContinuous,
+    ClosingAuction,
+}
+
+impl SessionKind {
+    pub fn all() -> [SessionKind; 5] {
+        [
+            SessionKind::PreOpen,
+            SessionKind::MorningContinuous,
+            SessionKind::Lunch,
+            SessionKind::AfternoonContinuous,
+            SessionKind::ClosingAuction,
+        ]
+    }
+
+    /// New orders are accepted in every session but the lunch break
+    pub fn accepts_orders(&self) -> bool {
+        *self != SessionKind::Lunch
+    }
+
+    /// The orders collected in the pre-open are matched once the continuous
+    /// trading starts, and the ones of the lunch break after it
+    pub fn matches_orders(&self) -> bool {
+        !matches!(self, SessionKind::PreOpen | SessionKind::Lunch)
+    }
+}
+
+/// Interval of a trading day, which includes its start but not its end
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Session {
+    pub end: SessionTime,
+    pub kind: SessionKind,
+    pub start: SessionTime,
+}
+
+impl Session {
+    pub fn new(kind: SessionKind, start: (u16, u16), end: (u16, u16)) -> Self {
+        Session {
+            end: SessionTime::new(end.0, end.1),
+            kind,
+            start: SessionTime::new(start.0, start.1),
+        }
+    }
+}
+
+pub fn get_session(sessions: &[Session], time: SessionTime) -> Option<SessionKind> {
+    sessions
+        .iter()
+        .find(|session| session.start <= time && time < session.end)
+        .map(|session| session.kind)
+}
+
+/// The sessions of a day must be in order and not overlap
+pub fn verify_sessions(sessions: &[Session]) -> Result<(), String> {
+    let mut previous_end = SessionTime(0);
+
+    for session in sessions {
+        if session.start >= session.end || session.start < previous_end {
+            return Err(format!(
+                "The session {:?} from {} to {} is empty or overlaps the previous one",
+                session.kind,
+                String::from(session.start),
+                String::from(session.end)
+            ));
+        }
+
+        previous_end = session.end;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_session() {
+        let sessions = vec![
+            Session::new(SessionKind::PreOpen, (9, 0), (9, 30)),
+            Session::new(SessionKind::MorningContinuous, (9, 30), (12, 0)),
+            Session::new(SessionKind::Lunch, (12, 0), (13, 0)),
+        ];
+
+        assert!(verify_sessions(&sessions).is_ok());
+        assert_eq!(get_session(&sessions, SessionTime::new(8, 59)), None);
+        assert_eq!(
+            get_session(&sessions, SessionTime::new(9, 30)),
+            Some(SessionKind::MorningContinuous)
+        );
+        assert_eq!(
+            get_session(&sessions, SessionTime::new(12, 59)),
+            Some(SessionKind::Lunch)
+        );
+        assert_eq!(get_session(&sessions, SessionTime::new(13, 0)), None);
+
+        assert_eq!(
+            SessionTime::try_from("09:30".to_string()),
+            Ok(SessionTime::new(9, 30))
+        );
+        assert!(SessionTime::try_from("9h30".to_string()).is_err());
+        assert!(verify_sessions(&[sessions[1].clone(), sessions[0].clone()]).is_err());
+    }
+}
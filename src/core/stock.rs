@@ -1,12 +1,21 @@
 use std::collections::BTreeMap;
 
 use super::{
-    company::CompanySymbol, investor::InvestorId, market_maker::MarketMakerId, money::Money,
+    company::CompanySymbol,
+    etf::EtfId,
+    fund::FundId,
+    investor::InvestorId,
+    market_maker::MarketMakerId,
+    money::{Money, RoundingMode},
 };
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Copy, PartialOrd, Ord)]
 pub enum StockOwner {
+    /** Custodian of the basket of an ETF */
+    Etf(EtfId),
+    Fund(FundId),
     Investor(InvestorId),
     MarketMaker(MarketMakerId),
 }
@@ -14,6 +23,12 @@ pub enum StockOwner {
 impl Serialize for StockOwner {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         match self {
+            StockOwner::Etf(id) => {
+                format!("E{}", serde_json::to_string(id).unwrap()).serialize(serializer)
+            }
+            StockOwner::Fund(id) => {
+                format!("F{}", serde_json::to_string(id).unwrap()).serialize(serializer)
+            }
             StockOwner::Investor(id) => {
                 format!("I{}", serde_json::to_string(id).unwrap()).serialize(serializer)
             }
@@ -27,7 +42,11 @@ impl Serialize for StockOwner {
 impl<'a> Deserialize<'a> for StockOwner {
     fn deserialize<D: serde::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
         let s = String::deserialize(deserializer)?;
-        if let Some(stripped) = s.strip_prefix('I') {
+        if let Some(stripped) = s.strip_prefix('E') {
+            Ok(StockOwner::Etf(serde_json::from_str(stripped).unwrap()))
+        } else if let Some(stripped) = s.strip_prefix('F') {
+            Ok(StockOwner::Fund(serde_json::from_str(stripped).unwrap()))
+        } else if let Some(stripped) = s.strip_prefix('I') {
             Ok(StockOwner::Investor(
                 serde_json::from_str(stripped).unwrap(),
             ))
@@ -63,6 +82,69 @@ impl OwnedStocks {
         self.0.get(owner).is_some_and(|stocks| !stocks.is_empty())
     }
 
+    pub fn get_quantity(&self, owner: &StockOwner, symbol: &CompanySymbol) -> u64 {
+        self.0.get(owner).map_or(0, |stocks| {
+            stocks
+                .iter()
+                .filter(|stock| &stock.symbol == symbol)
+                .map(|stock| stock.quantity)
+                .sum()
+        })
+    }
+
+    /// Average purchase price of the shares of the symbol, weighted by quantity
+    pub fn get_average_cost(&self, owner: &StockOwner, symbol: &CompanySymbol) -> Option<Money> {
+        let stocks = self
+            .0
+            .get(owner)?
+            .iter()
+            .filter(|stock| &stock.symbol == symbol && stock.quantity > 0)
+            .collect::<Vec<_>>();
+        let quantity: u64 = stocks.iter().map(|stock| stock.quantity).sum();
+
+        if quantity == 0 {
+            return None;
+        }
+
+        let total = stocks[1..].iter().try_fold(
+            stocks[0].price.checked_mul(stocks[0].quantity).ok()?,
+            |total, stock| total.checked_add(&stock.price.checked_mul(stock.quantity)?),
+        );
+
+        total
+            .and_then(|total| total.checked_div(Decimal::from(quantity), RoundingMode::Bankers))
+            .ok()
+    }
+
+    /// Removes up to `quantity` shares of the symbol from the owner, and
+    /// returns how many were removed
+    pub fn remove_quantity(
+        &mut self,
+        owner: &StockOwner,
+        symbol: &CompanySymbol,
+        quantity: u64,
+    ) -> u64 {
+        let Some(stocks) = self.0.get_mut(owner) else {
+            return 0;
+        };
+        let mut removed = 0;
+
+        for stock in stocks.iter_mut().filter(|stock| &stock.symbol == symbol) {
+            let taken = stock.quantity.min(quantity - removed);
+
+            stock.quantity -= taken;
+            removed += taken;
+
+            if removed == quantity {
+                break;
+            }
+        }
+
+        stocks.retain(|stock| stock.quantity > 0);
+
+        removed
+    }
+
     pub fn get_prices(&self, symbol: &CompanySymbol) -> Vec<Money> {
         self.0
             .values()
@@ -0,0 +1,80 @@
+use super::StockExchange;
+use crate::core::{
+    company::{Company, CompanyStatus, CompanySymbol, CompanyTransitionError, InstrumentKind},
+    time::TimeHandler,
+};
+
+impl StockExchange {
+    /// All the changes of a company status should go through this method, so
+    /// it is validated against the current status.
+    pub fn transition_company(
+        &mut self,
+        symbol: &CompanySymbol,
+        status: CompanyStatus,
+        time: &TimeHandler,
+    ) -> Result<(), String> {
+        let company = self
+            .companies
+            .mapping
+            .get_mut(symbol)
+            .ok_or(format!("Unknown company {}", symbol))?;
+
+        company
+            .transition(status, time)
+            .map_err(|e| format!("Invalid transition for company {}: {:?}", symbol, e))
+    }
+
+    /// Fails like `transition_company` without changing the status, for the
+    /// flows that have to check it before their other changes
+    pub fn verify_company_transition(
+        &self,
+        symbol: &CompanySymbol,
+        status: CompanyStatus,
+    ) -> Result<(), String> {
+        let company = self
+            .companies
+            .mapping
+            .get(symbol)
+            .ok_or(format!("Unknown company {}", symbol))?;
+
+        if !company.status.can_transition_to(&status) {
+            let e = CompanyTransitionError::Invalid {
+                from: company.status,
+                to: status,
+            };
+
+            return Err(format!(
+                "Invalid transition for company {}: {:?}",
+                symbol, e
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The secondary listings share the company of the primary one
+    pub fn get_company(&self, symbol: &CompanySymbol) -> Option<&Company> {
+        self.companies.mapping.get(self.get_primary_symbol(symbol))
+    }
+
+    pub fn get_company_status(&self, symbol: &CompanySymbol) -> Option<CompanyStatus> {
+        self.get_company(symbol).map(|company| company.status)
+    }
+
+    pub fn is_tradeable(&self, symbol: &CompanySymbol) -> bool {
+        match self.listed_companies.mapping.get(symbol) {
+            None => false,
+            Some(listed) if listed.instrument == InstrumentKind::Etf => true,
+            Some(_) => self
+                .get_company_status(symbol)
+                .is_some_and(|status| status.is_trading()),
+        }
+    }
+
+    pub fn is_etf(&self, symbol: &CompanySymbol) -> bool {
+        self.listed_companies
+            .mapping
+            .get(symbol)
+            .is_some_and(|listed| listed.instrument == InstrumentKind::Etf)
+    }
+}
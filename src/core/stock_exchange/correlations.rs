@@ -0,0 +1,39 @@
+use super::StockExchange;
+
+impl StockExchange {
+    /// Average realized correlation of the pairs of symbols in the same
+    /// sector, and of the pairs in different sectors
+    pub fn get_average_sector_correlations(&self) -> (Option<f64>, Option<f64>) {
+        let symbols = self.returns.0.keys().collect::<Vec<_>>();
+        let (mut intra_sum, mut intra_count) = (0.0, 0);
+        let (mut inter_sum, mut inter_count) = (0.0, 0);
+
+        for (idx, a) in symbols.iter().enumerate() {
+            for b in symbols.iter().skip(idx + 1) {
+                let (Some(a_company), Some(b_company)) =
+                    (self.companies.mapping.get(a), self.companies.mapping.get(b))
+                else {
+                    continue;
+                };
+                let Some(correlation) = self.returns.get_correlation(a, b) else {
+                    continue;
+                };
+
+                if a_company.sector == b_company.sector {
+                    intra_sum += correlation;
+                    intra_count += 1;
+                } else {
+                    inter_sum += correlation;
+                    inter_count += 1;
+                }
+            }
+        }
+
+        let average = |sum: f64, count: u64| (count > 0).then(|| sum / count as f64);
+
+        (
+            average(intra_sum, intra_count),
+            average(inter_sum, inter_count),
+        )
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+
+use super::StockExchange;
+use crate::core::{
+    company::{CompanyStatus, CompanySymbol, DelistedCompany, DelistingReason},
+    money::{Money, MoneyError},
+    stock::StockOwner,
+    time::TimeHandler,
+};
+
+impl StockExchange {
+    pub fn suspend_trading(
+        &mut self,
+        symbol: &CompanySymbol,
+        time: &TimeHandler,
+    ) -> Result<(), String> {
+        self.transition_company(symbol, CompanyStatus::Suspended, time)?;
+        self.orders_book.cancel_symbol_orders(symbol);
+
+        for secondary_symbol in self.get_secondary_listings(symbol) {
+            self.orders_book.cancel_symbol_orders(&secondary_symbol);
+        }
+
+        Ok(())
+    }
+
+    pub fn resume_trading(
+        &mut self,
+        symbol: &CompanySymbol,
+        time: &TimeHandler,
+    ) -> Result<(), String> {
+        self.transition_company(symbol, CompanyStatus::Listed, time)
+    }
+
+    /// Removes the company from the listed ones, with its secondary listings.
+    /// The shares of a plain delisting remain with their holders, but they
+    /// can't be traded anymore. Everything that can fail is checked before
+    /// changing the state, so a failed delisting leaves the company listed.
+    pub fn delist(
+        &mut self,
+        symbol: &CompanySymbol,
+        reason: DelistingReason,
+        time: &TimeHandler,
+    ) -> Result<(), String> {
+        if !self.listed_companies.mapping.contains_key(symbol) {
+            return Err(format!("Company {} is not listed", symbol));
+        }
+
+        let mut listings = Vec::new();
+
+        for secondary_symbol in self.get_secondary_listings(symbol) {
+            let reason = match &reason {
+                // The offer is paid in the currency of each listing
+                DelistingReason::Privatization { offer_price } => {
+                    let currency = self
+                        .get_listing_exchange(&secondary_symbol)
+                        .map(|exchange| exchange.settings.currency)
+                        .unwrap_or(offer_price.currency);
+
+                    DelistingReason::Privatization {
+                        offer_price: self.convert_money(offer_price, &currency)?,
+                    }
+                }
+                reason => reason.clone(),
+            };
+
+            listings.push((secondary_symbol, reason));
+        }
+
+        listings.push((symbol.clone(), reason));
+
+        let mut payments = Vec::new();
+
+        for (listing_symbol, reason) in &listings {
+            if let DelistingReason::Privatization { offer_price } = reason {
+                let listing_payments = self
+                    .get_privatization_payments(listing_symbol, offer_price)
+                    .map_err(|e| format!("Error paying the offer for {}: {}", listing_symbol, e))?;
+
+                payments.extend(listing_payments);
+            }
+        }
+
+        self.verify_company_transition(symbol, CompanyStatus::Delisted)?;
+        self.pay_holders(&payments)
+            .map_err(|e| format!("Error paying the offer for {}: {}", symbol, e))?;
+
+        for (listing_symbol, reason) in listings {
+            self.remove_listing(&listing_symbol, reason, time);
+        }
+
+        self.transition_company(symbol, CompanyStatus::Delisted, time)
+    }
+
+    /// The holders of a privatized listing were paid for their shares
+    /// before, so they are removed
+    fn remove_listing(
+        &mut self,
+        symbol: &CompanySymbol,
+        reason: DelistingReason,
+        time: &TimeHandler,
+    ) {
+        let listed_company = self.listed_companies.mapping.remove(symbol).unwrap();
+
+        self.orders_book.cancel_symbol_orders(symbol);
+
+        if let DelistingReason::Privatization { .. } = &reason {
+            for stocks in self.owned_stocks.0.values_mut() {
+                stocks.retain(|stock| &stock.symbol != symbol);
+            }
+        }
+
+        self.remove_index_constituent(symbol);
+
+        let last_price = self.prices.0.remove(symbol);
+
+        self.delisted_companies.mapping.insert(
+            symbol.clone(),
+            DelistedCompany {
+                delisted_at: time.get_now_unix_timestamp(),
+                last_price,
+                listed_company,
+                reason,
+            },
+        );
+    }
+
+    /// What each holder of the listing gets for their shares
+    fn get_privatization_payments(
+        &self,
+        symbol: &CompanySymbol,
+        offer_price: &Money,
+    ) -> Result<Vec<(StockOwner, Money)>, MoneyError> {
+        let mut payments = Vec::new();
+
+        for (owner, stocks) in self.owned_stocks.0.iter() {
+            let quantity: u64 = stocks
+                .iter()
+                .filter(|stock| &stock.symbol == symbol)
+                .map(|stock| stock.quantity)
+                .sum();
+
+            if quantity > 0 {
+                payments.push((*owner, offer_price.checked_mul(quantity)?));
+            }
+        }
+
+        Ok(payments)
+    }
+
+    /// The new balances are computed before changing any of them, so either
+    /// all the holders are paid or none
+    fn pay_holders(&mut self, payments: &[(StockOwner, Money)]) -> Result<(), MoneyError> {
+        let mut etfs_cash = BTreeMap::new();
+        let mut funds_cash = BTreeMap::new();
+        let mut investors = BTreeMap::new();
+
+        for (owner, payment) in payments {
+            match owner {
+                StockOwner::MarketMaker(_) => {}
+                StockOwner::Etf(etf_id) => {
+                    if let Some(etf) = self.etfs.mapping.get(etf_id) {
+                        let cash = etfs_cash.entry(*etf_id).or_insert(etf.cash);
+
+                        *cash = cash.checked_add(payment)?;
+                    }
+                }
+                StockOwner::Fund(fund_id) => {
+                    if let Some(fund) = self.funds.mapping.get(fund_id) {
+                        let cash = funds_cash.entry(*fund_id).or_insert(fund.cash);
+
+                        *cash = cash.checked_add(payment)?;
+                    }
+                }
+                StockOwner::Investor(investor_id) => {
+                    if let Some(investor) = self.investors.mapping.get(investor_id) {
+                        investors
+                            .entry(*investor_id)
+                            .or_insert_with(|| investor.clone())
+                            .add_cash(payment)?;
+                    }
+                }
+            }
+        }
+
+        for (etf_id, cash) in etfs_cash {
+            self.etfs.mapping.get_mut(&etf_id).unwrap().cash = cash;
+        }
+
+        for (fund_id, cash) in funds_cash {
+            self.funds.mapping.get_mut(&fund_id).unwrap().cash = cash;
+        }
+
+        self.investors.mapping.extend(investors);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{
+        company::ListedCompany,
+        investor::InvestorId,
+        money::Currency,
+        order::{Order, OrderSide, OrderStatus, OrderType},
+        stock_exchange::{ExchangeId, StockExchangeSettings},
+        test_fixtures::{add_holding, add_listing, build_investor, build_listing, build_money_in},
+    };
+
+    /// FOO is listed in HK and has a secondary listing in SH. The first
+    /// investor holds 100 shares of the primary and the second 50 of the
+    /// secondary.
+    fn build_exchange() -> (StockExchange, InvestorId, InvestorId) {
+        let mut se = StockExchange::new(
+            [("HK", Currency::Hkd), ("SH", Currency::Cny)]
+                .into_iter()
+                .map(|(id, currency)| StockExchangeSettings {
+                    currency,
+                    id: ExchangeId(id.to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let secondary_symbol = symbol.get_secondary(&ExchangeId("SH".to_string()));
+        let primary_holder = InvestorId::new(&InvestorId::init());
+        let secondary_holder = InvestorId::new(&primary_holder);
+
+        se.fx_rates.base = Currency::Hkd;
+        se.fx_rates.rates.insert(Currency::Cny, 2.0);
+
+        for (listing_symbol, exchange, currency, primary_symbol, holder, quantity) in [
+            (&symbol, "HK", Currency::Hkd, None, primary_holder, 100),
+            (
+                &secondary_symbol,
+                "SH",
+                Currency::Cny,
+                Some(symbol.clone()),
+                secondary_holder,
+                50,
+            ),
+        ] {
+            let price = build_money_in(currency, 4.0);
+            let listing = ListedCompany {
+                primary_symbol,
+                ..build_listing(listing_symbol, &ExchangeId(exchange.to_string()))
+            };
+
+            add_listing(&mut se, listing, price);
+            se.investors.mapping.insert(
+                holder,
+                build_investor(holder, build_money_in(Currency::Hkd, 0.0)),
+            );
+            add_holding(
+                &mut se,
+                StockOwner::Investor(holder),
+                listing_symbol,
+                price,
+                quantity,
+            );
+            se.orders_book.0.push(Order {
+                order_side: OrderSide::Sell,
+                order_type: OrderType::Market,
+                owner_id: StockOwner::Investor(holder),
+                shares: 10,
+                status: OrderStatus::Init,
+                symbol: listing_symbol.clone(),
+            });
+        }
+
+        (se, primary_holder, secondary_holder)
+    }
+
+    #[test]
+    fn test_suspend_and_resume_trading() {
+        let time = TimeHandler::new(0, None, 1000);
+        let (mut se, _, _) = build_exchange();
+        let symbol = CompanySymbol::new("FOO".to_string());
+
+        se.suspend_trading(&symbol, &time).unwrap();
+
+        assert_eq!(
+            se.get_company_status(&symbol),
+            Some(CompanyStatus::Suspended)
+        );
+        assert!(!se.is_tradeable(&symbol));
+        // Including the orders of the secondary listing
+        assert!(se.orders_book.0.is_empty());
+
+        se.resume_trading(&symbol, &time).unwrap();
+
+        assert_eq!(se.get_company_status(&symbol), Some(CompanyStatus::Listed));
+        assert!(se.resume_trading(&symbol, &time).is_err());
+    }
+
+    #[test]
+    fn test_delist_with_privatization_offer() {
+        let time = TimeHandler::new(0, None, 1000);
+        let (mut se, primary_holder, secondary_holder) = build_exchange();
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let secondary_symbol = symbol.get_secondary(&ExchangeId("SH".to_string()));
+        let reason = DelistingReason::Privatization {
+            offer_price: build_money_in(Currency::Hkd, 10.0),
+        };
+
+        se.delist(&symbol, reason, &time).unwrap();
+
+        assert_eq!(
+            se.get_company_status(&symbol),
+            Some(CompanyStatus::Delisted)
+        );
+        assert!(se.listed_companies.mapping.is_empty());
+        assert!(se
+            .delisted_companies
+            .mapping
+            .contains_key(&secondary_symbol));
+        assert!(se.owned_stocks.0.values().all(|stocks| stocks.is_empty()));
+
+        // The secondary holder is paid the offer in CNY
+        let investors = &se.investors.mapping;
+
+        assert_eq!(investors[&primary_holder].liquid_cash.to_f64(), 1000.0);
+        assert_eq!(
+            investors[&secondary_holder]
+                .get_cash(&Currency::Cny)
+                .to_f64(),
+            250.0
+        );
+    }
+
+    #[test]
+    fn test_delist_without_offer() {
+        let time = TimeHandler::new(0, None, 1000);
+        let (mut se, primary_holder, secondary_holder) = build_exchange();
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let secondary_symbol = symbol.get_secondary(&ExchangeId("SH".to_string()));
+
+        se.delist(&symbol, DelistingReason::LowPrice, &time)
+            .unwrap();
+
+        assert!(se.listed_companies.mapping.is_empty());
+        assert!(se.orders_book.0.is_empty());
+        assert_eq!(
+            se.delisted_companies.mapping[&secondary_symbol]
+                .last_price
+                .map(|price| price.bid.to_f64()),
+            Some(4.0)
+        );
+
+        // The shares remain, but without cash for them
+        let owner = StockOwner::Investor(secondary_holder);
+
+        assert_eq!(se.owned_stocks.get_quantity(&owner, &secondary_symbol), 50);
+        assert_eq!(
+            se.investors.mapping[&primary_holder].liquid_cash.to_f64(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_failed_delisting_changes_nothing() {
+        let time = TimeHandler::new(0, None, 1000);
+        let (mut se, primary_holder, _) = build_exchange();
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let reason = DelistingReason::Privatization {
+            offer_price: build_money_in(Currency::Hkd, 10.0),
+        };
+
+        // Without the rate, the offer can't be paid in the secondary listing
+        se.fx_rates.rates.clear();
+
+        assert!(se.delist(&symbol, reason, &time).is_err());
+        assert_eq!(se.get_company_status(&symbol), Some(CompanyStatus::Listed));
+        assert_eq!(se.listed_companies.mapping.len(), 2);
+        assert_eq!(
+            se.owned_stocks
+                .get_quantity(&StockOwner::Investor(primary_holder), &symbol),
+            100
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use super::StockExchange;
+use crate::core::{
+    company::IpoStatus,
+    investor::InvestorId,
+    journal::JournalEvent,
+    money::Money,
+    stock::{Stock, StockOwner},
+    time::TimeHandler,
+};
+use log::info;
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EstateSettlement {
+    pub cash_inherited: Money,
+    pub debt_settled: Money,
+    pub debt_written_off: Money,
+    pub deceased: InvestorId,
+    /** Without heir the holdings are liquidated and the cash is not inherited */
+    pub heir: Option<InvestorId>,
+    pub shares_inherited: u64,
+    pub timestamp: u64,
+}
+
+impl StockExchange {
+    /// Subscriptions of IPOs still offering are refunded to the estate, and
+    /// the ones allotted pass to the heir
+    fn settle_estate_ipo_subscriptions(
+        &mut self,
+        investor_id: &InvestorId,
+        heir: Option<InvestorId>,
+    ) -> Result<(), String> {
+        for ipo in self.ipos.mapping.values_mut() {
+            let refund = match (&ipo.status, heir) {
+                (IpoStatus::Offering, _) | (IpoStatus::Allotted, None) => {
+                    ipo.cancel_subscription(investor_id)
+                }
+                (IpoStatus::Allotted, Some(heir_id)) => {
+                    ipo.transfer_subscription(investor_id, &heir_id)
+                        .map_err(|e| e.to_string())?;
+                    None
+                }
+                _ => None,
+            };
+
+            if let (Some(refund), Some(investor)) =
+                (refund, self.investors.mapping.get_mut(investor_id))
+            {
+                investor
+                    .add_cash(&refund)
+                    .map_err(|e| format!("Error refunding {:?}: {}", investor_id, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Closes the account of an investor that left the market: cancels the
+    /// open orders, settles the debt (liquidating the holdings if the cash is
+    /// not enough) and transfers what is left, fund units included, to the
+    /// heir.
+    pub fn settle_estate(
+        &mut self,
+        investor_id: &InvestorId,
+        heir: Option<InvestorId>,
+        time: &TimeHandler,
+    ) -> Result<EstateSettlement, String> {
+        let owner = StockOwner::Investor(*investor_id);
+        let heir =
+            heir.filter(|heir| heir != investor_id && self.investors.mapping.contains_key(heir));
+        let Some(investor) = self.investors.mapping.get(investor_id) else {
+            return Err(format!("Investor {:?} not found", investor_id));
+        };
+        let initial_debt = investor.debt;
+
+        self.orders_book.0.retain(|order| order.owner_id != owner);
+        self.settle_estate_ipo_subscriptions(investor_id, heir)?;
+
+        let investor = &self.investors.mapping[investor_id];
+        let needs_liquidation = heir.is_none() || investor.liquid_cash.value < investor.debt.value;
+
+        if needs_liquidation {
+            self.liquidate_investor_holdings(investor_id)?;
+        }
+
+        for fund in self.funds.mapping.values_mut() {
+            match heir {
+                Some(heir_id) => fund.transfer_units(investor_id, &heir_id),
+                // What the funds couldn't redeem with their cash
+                None => fund.write_off_units(investor_id),
+            }
+        }
+
+        let investor = self.investors.mapping.remove(investor_id).unwrap();
+        self.record_event(JournalEvent::InvestorRemoved {
+            investor_id: *investor_id,
+        });
+        let debt_written_off = investor.debt;
+        let debt_settled = initial_debt
+            .checked_sub(&debt_written_off)
+            .map_err(|e| e.to_string())?;
+        let stocks = self.owned_stocks.0.remove(&owner).unwrap_or_default();
+        let mut shares_inherited = 0;
+        let mut cash_inherited = Money {
+            currency: investor.liquid_cash.currency,
+            value: Default::default(),
+        };
+
+        if let Some(heir_id) = heir {
+            let heir_owner = StockOwner::Investor(heir_id);
+
+            for stock in stocks {
+                shares_inherited += stock.quantity;
+
+                self.owned_stocks
+                    .entry_with_default(&heir_owner)
+                    .push(Stock {
+                        owner: heir_owner,
+                        ..stock
+                    });
+            }
+
+            cash_inherited = investor.liquid_cash;
+
+            let heir_investor = self.investors.mapping.get_mut(&heir_id).unwrap();
+
+            heir_investor
+                .add_cash(&cash_inherited)
+                .map_err(|e| e.to_string())?;
+
+            // The foreign cash keeps its currency
+            for cash in investor.foreign_cash.values() {
+                heir_investor.add_cash(cash).map_err(|e| e.to_string())?;
+            }
+
+            // So the inherited secondary listings can be traded
+            heir_investor.accounts.extend(investor.accounts.clone());
+        } else if !stocks.is_empty() {
+            // Holdings without a price anymore can't be liquidated
+            self.owned_stocks.0.insert(owner, stocks);
+        }
+
+        let settlement = EstateSettlement {
+            cash_inherited,
+            debt_settled,
+            debt_written_off,
+            deceased: *investor_id,
+            heir,
+            shares_inherited,
+            timestamp: time.get_now_unix_timestamp(),
+        };
+
+        info!("Settled estate: {:?}", settlement);
+
+        Ok(settlement)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{
+        company::CompanySymbol,
+        investor::Investor,
+        market_maker::{MarketMaker, MarketMakerId},
+        test_fixtures::{add_holding, add_listing, build_investor, build_listing, build_money},
+    };
+
+    fn build_exchange(deceased: InvestorId, heir: InvestorId, debt: f64) -> StockExchange {
+        let mut se = StockExchange::default();
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let market_maker_id = MarketMakerId::init();
+
+        se.investors.mapping.insert(
+            deceased,
+            Investor {
+                debt: build_money(debt),
+                ..build_investor(deceased, build_money(100.0))
+            },
+        );
+        se.investors
+            .mapping
+            .insert(heir, build_investor(heir, build_money(0.0)));
+        se.market_makers.mapping.insert(
+            market_maker_id,
+            MarketMaker {
+                id: market_maker_id,
+                permit_end_time: 1,
+                permit_start_time: 0,
+            },
+        );
+        let listing = build_listing(&symbol, &se.home_exchange);
+
+        add_listing(&mut se, listing, build_money(10.0));
+        add_holding(
+            &mut se,
+            StockOwner::Investor(deceased),
+            &symbol,
+            build_money(5.0),
+            100,
+        );
+
+        se
+    }
+
+    #[test]
+    fn test_settle_estate() {
+        let time = TimeHandler::new(0, None, 1000);
+        let deceased = InvestorId::new(&InvestorId::init());
+        let heir = InvestorId::new(&deceased);
+
+        let mut se = build_exchange(deceased, heir, 0.0);
+        let settlement = se.settle_estate(&deceased, Some(heir), &time).unwrap();
+
+        assert_eq!(settlement.shares_inherited, 100);
+        assert_eq!(settlement.cash_inherited.to_f64(), 100.0);
+        assert!(!se.investors.mapping.contains_key(&deceased));
+        assert_eq!(
+            se.owned_stocks.get_quantity(
+                &StockOwner::Investor(heir),
+                &CompanySymbol::new("FOO".to_string())
+            ),
+            100
+        );
+
+        // The debt is bigger than the cash, so the holdings are sold first
+        let mut se = build_exchange(deceased, heir, 600.0);
+        let settlement = se.settle_estate(&deceased, Some(heir), &time).unwrap();
+
+        assert_eq!(settlement.debt_settled.to_f64(), 600.0);
+        assert_eq!(settlement.shares_inherited, 0);
+        assert_eq!(settlement.cash_inherited.to_f64(), 500.0);
+        assert_eq!(se.investors.mapping[&heir].liquid_cash.to_f64(), 500.0);
+    }
+}
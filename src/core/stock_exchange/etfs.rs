@@ -0,0 +1,474 @@
+use super::StockExchange;
+use crate::core::{
+    company::{CompanySymbol, InstrumentKind, ListedCompany},
+    etf::{Etf, EtfId, EtfTracking},
+    money::Money,
+    price::{Price, Prices},
+    stock::{Stock, StockOwner},
+};
+use std::collections::BTreeMap;
+
+/// Shares of each company in the basket of a creation unit, as a ratio of its
+/// free float
+// @settings
+const BASKET_FREE_FLOAT_RATIO: f64 = 0.001;
+
+impl StockExchange {
+    fn get_etf_basket(&self, tracking: &EtfTracking) -> BTreeMap<CompanySymbol, u64> {
+        let symbols = match tracking {
+            EtfTracking::Symbols { basket } => {
+                return basket
+                    .iter()
+                    .filter(|(symbol, shares)| {
+                        **shares > 0 && self.is_tradeable(symbol) && !self.is_etf(symbol)
+                    })
+                    .map(|(symbol, shares)| (symbol.clone(), *shares))
+                    .collect();
+            }
+            EtfTracking::Index => self.index.constituents.keys().cloned().collect::<Vec<_>>(),
+            EtfTracking::Sector { sector } => self
+                .companies
+                .mapping
+                .values()
+                .filter(|company| &company.sector == sector)
+                .map(|company| company.symbol.clone())
+                .collect(),
+        };
+
+        symbols
+            .into_iter()
+            .filter(|symbol| self.is_tradeable(symbol))
+            .filter_map(|symbol| {
+                let listed = self.listed_companies.mapping.get(&symbol)?;
+                let shares =
+                    (listed.get_free_float_shares() as f64 * BASKET_FREE_FLOAT_RATIO).ceil();
+
+                Some((symbol, (shares as u64).max(1)))
+            })
+            .collect()
+    }
+
+    /// Lists a new ETF without units. The authorized participants create them
+    /// when there is demand.
+    pub fn list_etf(
+        &mut self,
+        name: &str,
+        symbol: &CompanySymbol,
+        tracking: EtfTracking,
+        creation_unit: u64,
+        lot_size: u64,
+    ) -> Result<EtfId, String> {
+        if self.companies.mapping.contains_key(symbol)
+            || self.listed_companies.mapping.contains_key(symbol)
+        {
+            return Err(format!("The symbol {} is already used", symbol));
+        }
+
+        if lot_size == 0 || creation_unit == 0 || !creation_unit.is_multiple_of(lot_size) {
+            return Err(format!(
+                "The creation unit of {} has to be a multiple of the lot size",
+                symbol
+            ));
+        }
+
+        symbol
+            .verify()
+            .map_err(|_| format!("Invalid ETF symbol {}", symbol))?;
+
+        let id = EtfId::new(&self.etfs.last_id);
+        let mut etf = Etf {
+            basket: self.get_etf_basket(&tracking),
+            cash: Money {
+                currency: self.get_currency(),
+                value: Default::default(),
+            },
+            creation_unit,
+            id,
+            inav: 0.0,
+            name: name.to_string(),
+            symbol: symbol.clone(),
+            tracking,
+            units: 0,
+        };
+
+        etf.inav = self.get_etf_inav(&etf, &self.prices);
+
+        if etf.inav <= 0.0 {
+            return Err(format!("The basket of the ETF {} is empty", symbol));
+        }
+
+        let inav = Money {
+            currency: self.get_currency(),
+            value: Money::from_f64(etf.inav),
+        };
+
+        self.prices.0.insert(
+            symbol.clone(),
+            Price {
+                ask: inav,
+                bid: inav,
+            },
+        );
+        self.listed_companies.mapping.insert(
+            symbol.clone(),
+            ListedCompany {
+                exchange: self.home_exchange.clone(),
+                free_float: 1.0,
+                instrument: InstrumentKind::Etf,
+                lot_size,
+                primary_symbol: None,
+                symbol: symbol.clone(),
+                total_stocks: 0,
+            },
+        );
+        self.etfs.last_id = id;
+        self.etfs.mapping.insert(id, etf);
+
+        Ok(id)
+    }
+
+    /// The baskets follow the changes of what the ETFs track, e.g. the
+    /// rebalances of the index
+    pub fn update_etf_baskets(&mut self) {
+        let baskets = self
+            .etfs
+            .mapping
+            .values()
+            .map(|etf| (etf.id, self.get_etf_basket(&etf.tracking)))
+            .collect::<Vec<_>>();
+
+        for (etf_id, basket) in baskets {
+            self.etfs.mapping.get_mut(&etf_id).unwrap().basket = basket;
+        }
+    }
+
+    /// Value per ETF share of what the custodian holds or, when there are no
+    /// units yet, of the basket
+    pub fn get_etf_inav(&self, etf: &Etf, prices: &Prices) -> f64 {
+        if etf.units == 0 {
+            let basket_value = etf
+                .basket
+                .iter()
+                .filter_map(|(symbol, shares)| {
+                    prices
+                        .get_average_price(symbol)
+                        .map(|price| price.to_f64() * *shares as f64)
+                })
+                .sum::<f64>();
+
+            return basket_value / etf.creation_unit as f64;
+        }
+
+        let holdings = self.get_holdings_value(&StockOwner::Etf(etf.id), prices);
+
+        (etf.cash.to_f64() + holdings) / etf.units as f64
+    }
+
+    pub fn update_etf_inavs(&mut self) {
+        let inavs = self
+            .etfs
+            .mapping
+            .values()
+            .map(|etf| (etf.id, self.get_etf_inav(etf, &self.prices)))
+            .collect::<Vec<_>>();
+
+        for (etf_id, inav) in inavs {
+            self.etfs.mapping.get_mut(&etf_id).unwrap().inav = inav;
+        }
+    }
+
+    /// Complete baskets of the ETF that the owner could deliver
+    pub fn count_etf_baskets(&self, etf_id: &EtfId, owner: &StockOwner) -> u64 {
+        let Some(etf) = self.etfs.mapping.get(etf_id) else {
+            return 0;
+        };
+
+        etf.basket
+            .iter()
+            .map(|(symbol, shares)| self.owned_stocks.get_quantity(owner, symbol) / shares)
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Shares of the basket that the owner lacks to create the units
+    fn get_etf_missing_shares(
+        &self,
+        etf: &Etf,
+        owner: &StockOwner,
+        creation_units: u64,
+    ) -> BTreeMap<CompanySymbol, u64> {
+        etf.basket
+            .iter()
+            .map(|(symbol, shares)| {
+                let missing = (shares * creation_units)
+                    .saturating_sub(self.owned_stocks.get_quantity(owner, symbol));
+
+                (symbol.clone(), missing)
+            })
+            .filter(|(_, missing)| *missing > 0)
+            .collect()
+    }
+
+    /// Cash that replaces the basket shares that the owner lacks to create the
+    /// units, at the current prices
+    pub fn get_etf_cash_in_lieu(
+        &self,
+        etf_id: &EtfId,
+        owner: &StockOwner,
+        creation_units: u64,
+    ) -> Money {
+        let value = self.etfs.mapping.get(etf_id).map_or(0.0, |etf| {
+            self.get_etf_missing_shares(etf, owner, creation_units)
+                .iter()
+                .map(|(symbol, missing)| self.get_cost_price(symbol).to_f64() * *missing as f64)
+                .sum()
+        });
+
+        Money {
+            currency: self.get_currency(),
+            value: Money::from_f64(value),
+        }
+    }
+
+    /// Shares that the custodian still has to buy with the cash in lieu, to
+    /// hold the baskets of all the units
+    pub fn get_etf_shares_to_buy(&self, etf_id: &EtfId) -> BTreeMap<CompanySymbol, u64> {
+        let Some(etf) = self.etfs.mapping.get(etf_id) else {
+            return BTreeMap::new();
+        };
+
+        self.get_etf_missing_shares(
+            etf,
+            &StockOwner::Etf(*etf_id),
+            etf.units / etf.creation_unit,
+        )
+    }
+
+    fn get_cost_price(&self, symbol: &CompanySymbol) -> Money {
+        self.prices.get_average_price(symbol).unwrap_or(Money {
+            currency: self.get_currency(),
+            value: Default::default(),
+        })
+    }
+
+    /// The participant delivers the baskets to the custodian, with cash in
+    /// lieu of the missing shares, and receives the new ETF shares
+    pub fn create_etf_units(
+        &mut self,
+        etf_id: &EtfId,
+        participant: &StockOwner,
+        creation_units: u64,
+    ) -> Result<u64, String> {
+        let Some(etf) = self.etfs.mapping.get(etf_id) else {
+            return Err(format!("ETF {:?} not found", etf_id));
+        };
+
+        let cash_in_lieu = self.get_etf_cash_in_lieu(etf_id, participant, creation_units);
+
+        match participant {
+            // The market makers have unlimited liquidity
+            StockOwner::MarketMaker(_) => {}
+            StockOwner::Investor(investor_id) => {
+                let investor = self
+                    .investors
+                    .mapping
+                    .get_mut(investor_id)
+                    .filter(|investor| investor.liquid_cash.value >= cash_in_lieu.value)
+                    .ok_or_else(|| format!("Investor {:?} can't pay the cash", investor_id))?;
+
+                investor
+                    .subtract_cash(&cash_in_lieu)
+                    .map_err(|e| e.to_string())?;
+            }
+            StockOwner::Etf(_) | StockOwner::Fund(_) => {
+                return Err(format!("{:?} can't create units", participant));
+            }
+        }
+
+        let basket = etf.basket.clone();
+        let symbol = etf.symbol.clone();
+        let created = etf.creation_unit * creation_units;
+        let custodian = StockOwner::Etf(*etf_id);
+
+        for (basket_symbol, shares) in basket {
+            let quantity = self.owned_stocks.remove_quantity(
+                participant,
+                &basket_symbol,
+                shares * creation_units,
+            );
+            let price = self.get_cost_price(&basket_symbol);
+
+            self.owned_stocks
+                .entry_with_default(&custodian)
+                .push(Stock {
+                    owner: custodian,
+                    price,
+                    quantity,
+                    symbol: basket_symbol,
+                });
+        }
+
+        let price = self.get_cost_price(&symbol);
+
+        self.owned_stocks
+            .entry_with_default(participant)
+            .push(Stock {
+                owner: *participant,
+                price,
+                quantity: created,
+                symbol: symbol.clone(),
+            });
+        let etf = self.etfs.mapping.get_mut(etf_id).unwrap();
+
+        etf.cash = etf
+            .cash
+            .checked_add(&cash_in_lieu)
+            .map_err(|e| e.to_string())?;
+        etf.units += created;
+        self.listed_companies
+            .mapping
+            .get_mut(&symbol)
+            .unwrap()
+            .total_stocks += created;
+
+        Ok(created)
+    }
+
+    /// The participant returns ETF shares, and receives the same part of what
+    /// the custodian holds
+    pub fn redeem_etf_units(
+        &mut self,
+        etf_id: &EtfId,
+        participant: &StockOwner,
+        creation_units: u64,
+    ) -> Result<(), String> {
+        let Some(etf) = self.etfs.mapping.get(etf_id) else {
+            return Err(format!("ETF {:?} not found", etf_id));
+        };
+        let redeemed = etf.creation_unit * creation_units;
+        let symbol = etf.symbol.clone();
+
+        if etf.units < redeemed || self.owned_stocks.get_quantity(participant, &symbol) < redeemed {
+            return Err(format!("Not enough units to redeem of {}", symbol));
+        }
+
+        let ratio = redeemed as f64 / etf.units as f64;
+        let custodian = StockOwner::Etf(*etf_id);
+        let custodian_symbols = self
+            .owned_stocks
+            .0
+            .get(&custodian)
+            .map(|stocks| {
+                stocks
+                    .iter()
+                    .map(|stock| stock.symbol.clone())
+                    .collect::<std::collections::BTreeSet<_>>()
+            })
+            .unwrap_or_default();
+
+        self.owned_stocks
+            .remove_quantity(participant, &symbol, redeemed);
+
+        for basket_symbol in custodian_symbols {
+            let held = self.owned_stocks.get_quantity(&custodian, &basket_symbol);
+            let delivered = (held as f64 * ratio).round() as u64;
+            let quantity = self
+                .owned_stocks
+                .remove_quantity(&custodian, &basket_symbol, delivered);
+            let price = self.get_cost_price(&basket_symbol);
+
+            if quantity > 0 {
+                self.owned_stocks
+                    .entry_with_default(participant)
+                    .push(Stock {
+                        owner: *participant,
+                        price,
+                        quantity,
+                        symbol: basket_symbol,
+                    });
+            }
+        }
+
+        let etf = self.etfs.mapping.get_mut(etf_id).unwrap();
+        let cash = Money {
+            currency: etf.cash.currency,
+            value: Money::from_f64(etf.cash.to_f64() * ratio).min(etf.cash.value),
+        };
+
+        // The market makers have unlimited liquidity, so only the investors
+        // keep the cash part
+        etf.cash = etf.cash.checked_sub(&cash).map_err(|e| e.to_string())?;
+        etf.units -= redeemed;
+
+        if let StockOwner::Investor(investor_id) = participant {
+            if let Some(investor) = self.investors.mapping.get_mut(investor_id) {
+                investor.add_cash(&cash).map_err(|e| e.to_string())?;
+            }
+        }
+
+        self.listed_companies
+            .mapping
+            .get_mut(&symbol)
+            .unwrap()
+            .total_stocks -= redeemed;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{
+        market_maker::MarketMakerId,
+        test_fixtures::{add_holding, add_listing, build_listing, build_money},
+    };
+
+    #[test]
+    fn test_create_and_redeem_etf_units() {
+        let mut se = StockExchange::default();
+        let participant = StockOwner::MarketMaker(MarketMakerId::init());
+        let basket = [("FOO", 10.0, 20), ("BAR", 5.0, 40)]
+            .into_iter()
+            .map(|(symbol, price, shares)| {
+                let symbol = CompanySymbol::new(symbol.to_string());
+                let price = build_money(price);
+                let listing = build_listing(&symbol, &se.home_exchange);
+
+                add_listing(&mut se, listing, price);
+                add_holding(&mut se, participant, &symbol, price, shares * 3);
+
+                (symbol, shares)
+            })
+            .collect();
+        let symbol = CompanySymbol::new("ETF".to_string());
+        let etf_id = se
+            .list_etf("Foo ETF", &symbol, EtfTracking::Symbols { basket }, 100, 10)
+            .unwrap();
+
+        // (10 * 20 + 5 * 40) / 100
+        assert_eq!(se.etfs.mapping[&etf_id].inav, 4.0);
+        assert_eq!(se.count_etf_baskets(&etf_id, &participant), 3);
+        assert_eq!(
+            se.get_etf_cash_in_lieu(&etf_id, &participant, 4).to_f64(),
+            400.0
+        );
+
+        se.create_etf_units(&etf_id, &participant, 2).unwrap();
+
+        assert_eq!(se.owned_stocks.get_quantity(&participant, &symbol), 200);
+        assert_eq!(se.listed_companies.mapping[&symbol].total_stocks, 200);
+        assert_eq!(se.get_etf_inav(&se.etfs.mapping[&etf_id], &se.prices), 4.0);
+
+        se.redeem_etf_units(&etf_id, &participant, 1).unwrap();
+
+        let foo = CompanySymbol::new("FOO".to_string());
+
+        assert_eq!(se.etfs.mapping[&etf_id].units, 100);
+        assert_eq!(se.owned_stocks.get_quantity(&participant, &foo), 40);
+        assert_eq!(
+            se.owned_stocks.get_quantity(&StockOwner::Etf(etf_id), &foo),
+            20
+        );
+    }
+}
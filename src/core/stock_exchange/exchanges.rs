@@ -0,0 +1,287 @@
+use super::{Exchange, ExchangeId, StockExchange};
+use crate::core::{
+    company::CompanySymbol,
+    investor::Investor,
+    money::Currency,
+    price::Prices,
+    session::{get_session, Session, SessionKind, SessionTime},
+    time::{TimeHandler, Timezone},
+};
+use chrono::{Datelike, NaiveDate, TimeZone, Timelike};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Limit to look for the next session, for the exchanges without any
+// @settings
+const MAX_DAYS_TO_NEXT_OPEN: usize = 30;
+
+#[derive(Serialize, Debug, Default)]
+pub struct ExchangeCalendar {
+    /** Trading days with shorter sessions, which are also in `trading_days` */
+    pub half_days: Vec<String>,
+    pub holidays: Vec<String>,
+    pub trading_days: Vec<String>,
+    /** Years without holidays yet, which are generated when they start unless they are in the calendar file */
+    pub unknown_years: Vec<String>,
+}
+
+impl Exchange {
+    /// Year in the timezone of the exchange, under which its holidays are
+    pub fn get_local_year(&self, time: &TimeHandler) -> String {
+        let local_time = time.get_local_time(&self.settings.timezone);
+
+        format!("{}", local_time.format("%Y"))
+    }
+
+    /// The sessions of a date, which are shorter in the half days
+    pub fn get_day_sessions(&self, date: &NaiveDate) -> &[Session] {
+        let half_day = self
+            .settings
+            .half_days
+            .get(&format!("{}", date.format("%Y-%m-%d")))
+            .or_else(|| {
+                self.settings
+                    .half_days
+                    .get(&format!("{}", date.format("%m-%d")))
+            });
+
+        half_day.unwrap_or(&self.settings.sessions)
+    }
+
+    fn is_holiday(&self, date: &NaiveDate) -> bool {
+        self.holidays
+            .get(&date.year().to_string())
+            .is_some_and(|year_holidays| {
+                year_holidays.contains(&format!("{}", date.format("%Y-%m-%d")))
+            })
+    }
+
+    fn is_trading_day(&self, date: &NaiveDate) -> bool {
+        let num_weekday = date.weekday().num_days_from_monday() as u8;
+
+        self.settings.trading_days.contains(&num_weekday) && !self.is_holiday(date)
+    }
+
+    /// The trading days, holidays and sessions are in the local time of the
+    /// exchange
+    pub fn get_session(&self, time: &TimeHandler) -> Option<SessionKind> {
+        let local_time = time.get_local_time(&self.settings.timezone);
+        let date = local_time.date_naive();
+
+        if !self.is_trading_day(&date) {
+            return None;
+        }
+
+        let sessions = self.get_day_sessions(&date);
+        let minute = SessionTime::new(local_time.hour() as u16, local_time.minute() as u16);
+
+        get_session(sessions, minute)
+    }
+
+    /// Days of the range, both included, by whether there is trading
+    pub fn get_calendar(&self, from: &NaiveDate, to: &NaiveDate) -> ExchangeCalendar {
+        let mut calendar = ExchangeCalendar::default();
+
+        for date in from.iter_days().take_while(|date| date <= to) {
+            let year = date.year().to_string();
+            let formatted = format!("{}", date.format("%Y-%m-%d"));
+
+            if !self.holidays.contains_key(&year) && !calendar.unknown_years.contains(&year) {
+                calendar.unknown_years.push(year);
+            }
+
+            if self.is_holiday(&date) {
+                calendar.holidays.push(formatted);
+            } else if self.is_trading_day(&date) {
+                if self.get_day_sessions(&date) != self.settings.sessions {
+                    calendar.half_days.push(formatted.clone());
+                }
+
+                calendar.trading_days.push(formatted);
+            }
+        }
+
+        calendar
+    }
+
+    /// Start of the next session after the current time, looking up to the
+    /// days with known holidays
+    pub fn get_next_open_timestamp(&self, time: &TimeHandler) -> Option<u64> {
+        let now = time.get_now_unix_timestamp() as i64;
+        let tz = self.settings.timezone.get_tz();
+        let today = time.get_local_time(&self.settings.timezone).date_naive();
+
+        today
+            .iter_days()
+            .take(MAX_DAYS_TO_NEXT_OPEN)
+            .filter(|date| self.is_trading_day(date))
+            .flat_map(|date| {
+                self.get_day_sessions(&date)
+                    .iter()
+                    .filter_map(move |session| {
+                        let start = date.and_hms_opt(
+                            (session.start.0 / 60) as u32,
+                            (session.start.0 % 60) as u32,
+                            0,
+                        )?;
+
+                        tz.from_local_datetime(&start).earliest()
+                    })
+            })
+            .map(|start| start.timestamp())
+            .find(|start| *start > now)
+            .map(|start| start as u64)
+    }
+
+    /// Whether the exchange accepts new orders
+    pub fn is_open(&self, time: &TimeHandler) -> bool {
+        self.get_session(time)
+            .is_some_and(|session| session.accepts_orders())
+    }
+}
+
+impl StockExchange {
+    pub fn get_home_exchange(&self) -> Option<&Exchange> {
+        self.exchanges.get(&self.home_exchange)
+    }
+
+    /// Currency of the home exchange, in which the investors, funds and ETFs
+    /// keep their cash
+    pub fn get_currency(&self) -> Currency {
+        self.get_home_exchange()
+            .map(|exchange| exchange.settings.currency)
+            .unwrap_or_default()
+    }
+
+    pub fn get_listing_exchange(&self, symbol: &CompanySymbol) -> Option<&Exchange> {
+        let listed = self.listed_companies.mapping.get(symbol)?;
+
+        self.exchanges.get(&listed.exchange)
+    }
+
+    pub fn is_home_listing(&self, symbol: &CompanySymbol) -> bool {
+        self.listed_companies
+            .mapping
+            .get(symbol)
+            .is_some_and(|listed| listed.exchange == self.home_exchange)
+    }
+
+    /// Symbol under which the company is kept, the primary listing of the
+    /// secondary ones
+    pub fn get_primary_symbol<'a>(&'a self, symbol: &'a CompanySymbol) -> &'a CompanySymbol {
+        self.listed_companies
+            .mapping
+            .get(symbol)
+            .and_then(|listed| listed.primary_symbol.as_ref())
+            .unwrap_or(symbol)
+    }
+
+    pub fn get_secondary_listings(&self, primary_symbol: &CompanySymbol) -> Vec<CompanySymbol> {
+        self.listed_companies
+            .mapping
+            .values()
+            .filter(|listed| listed.primary_symbol.as_ref() == Some(primary_symbol))
+            .map(|listed| listed.symbol.clone())
+            .collect()
+    }
+
+    /// Premium of a secondary listing to the primary one, with its price
+    /// converted to the currency of the primary
+    pub fn get_listing_premium(&self, symbol: &CompanySymbol) -> Option<f64> {
+        let listed = self.listed_companies.mapping.get(symbol)?;
+        let primary_price = self
+            .prices
+            .get_average_price(listed.primary_symbol.as_ref()?)?;
+        let price = self.prices.get_average_price(symbol)?;
+        let price = self.convert_money(&price, &primary_price.currency).ok()?;
+
+        (!primary_price.value.is_zero()).then(|| price.to_f64() / primary_price.to_f64() - 1.0)
+    }
+
+    /// Whether the investor has an account in the exchange of the listing
+    pub fn can_investor_trade(&self, investor: &Investor, symbol: &CompanySymbol) -> bool {
+        self.listed_companies
+            .mapping
+            .get(symbol)
+            .is_some_and(|listed| self.can_investor_trade_in(investor, &listed.exchange))
+    }
+
+    /// Whether the exchange is the home one or the investor has an account in
+    /// it
+    pub fn can_investor_trade_in(&self, investor: &Investor, exchange: &ExchangeId) -> bool {
+        exchange == &self.home_exchange || investor.accounts.contains(exchange)
+    }
+
+    fn get_exchanges_in_session(
+        &self,
+        time: &TimeHandler,
+        filter: impl Fn(&SessionKind) -> bool,
+    ) -> BTreeSet<ExchangeId> {
+        self.exchanges
+            .iter()
+            .filter(|(_, exchange)| exchange.get_session(time).is_some_and(|s| filter(&s)))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Earliest start of a session of any exchange after the current time
+    pub fn get_next_open_timestamp(&self, time: &TimeHandler) -> Option<u64> {
+        self.exchanges
+            .values()
+            .filter_map(|exchange| exchange.get_next_open_timestamp(time))
+            .min()
+    }
+
+    /// Exchanges that accept new orders
+    pub fn get_open_exchanges(&self, time: &TimeHandler) -> BTreeSet<ExchangeId> {
+        self.get_exchanges_in_session(time, SessionKind::accepts_orders)
+    }
+
+    /// Exchanges that keep their orders, including the lunch break
+    pub fn get_exchanges_in_any_session(&self, time: &TimeHandler) -> BTreeSet<ExchangeId> {
+        self.get_exchanges_in_session(time, |_| true)
+    }
+
+    /// Exchanges where the orders are matched
+    pub fn get_matching_exchanges(&self, time: &TimeHandler) -> BTreeSet<ExchangeId> {
+        self.get_exchanges_in_session(time, SessionKind::matches_orders)
+    }
+
+    pub fn get_listing_exchange_id(&self, symbol: &CompanySymbol) -> Option<&ExchangeId> {
+        self.listed_companies
+            .mapping
+            .get(symbol)
+            .map(|listed| &listed.exchange)
+    }
+
+    /// Prices of the listings whose exchange is in any session, so the lunch
+    /// break doesn't split the daily bars
+    pub fn get_session_prices(&self, time: &TimeHandler) -> Prices {
+        let exchanges = self.get_exchanges_in_any_session(time);
+
+        Prices(
+            self.prices
+                .0
+                .iter()
+                .filter(|(symbol, _)| {
+                    self.get_listing_exchange_id(symbol)
+                        .is_some_and(|exchange_id| exchanges.contains(exchange_id))
+                })
+                .map(|(symbol, price)| (symbol.clone(), *price))
+                .collect(),
+        )
+    }
+
+    /// Timezone of the exchange of each listing, where its daily bars start
+    pub fn get_listing_timezones(&self) -> BTreeMap<CompanySymbol, Timezone> {
+        self.listed_companies
+            .mapping
+            .iter()
+            .filter_map(|(symbol, listed)| {
+                let exchange = self.exchanges.get(&listed.exchange)?;
+
+                Some((symbol.clone(), exchange.settings.timezone))
+            })
+            .collect()
+    }
+}
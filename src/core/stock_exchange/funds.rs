@@ -0,0 +1,222 @@
+use super::StockExchange;
+use crate::core::{
+    company::CompanySymbol, fund::FundId, investor::InvestorId, money::Money, stock::StockOwner,
+    time::TimeHandler,
+};
+use std::collections::BTreeMap;
+
+/// Limits every weight to the cap, giving the excess to the rest of the
+/// weights proportionally. The total is lower than before only when all the
+/// weights are capped.
+fn cap_weights(
+    mut weights: BTreeMap<CompanySymbol, f64>,
+    cap: f64,
+) -> BTreeMap<CompanySymbol, f64> {
+    loop {
+        let excess = weights
+            .values()
+            .map(|weight| (weight - cap).max(0.0))
+            .sum::<f64>();
+        let uncapped_total = weights
+            .values()
+            .filter(|weight| **weight < cap)
+            .sum::<f64>();
+
+        if excess <= f64::EPSILON {
+            return weights;
+        }
+
+        for weight in weights.values_mut() {
+            if *weight >= cap {
+                *weight = cap;
+            } else if uncapped_total > 0.0 {
+                *weight += excess * *weight / uncapped_total;
+            }
+        }
+
+        if uncapped_total <= 0.0 {
+            return weights;
+        }
+    }
+}
+
+impl StockExchange {
+    /// Current net asset value of the fund, with the prices of now
+    pub fn get_fund_nav(&self, fund_id: &FundId) -> Option<f64> {
+        let fund = self.funds.mapping.get(fund_id)?;
+
+        Some(
+            fund.cash.to_f64() + self.get_holdings_value(&StockOwner::Fund(*fund_id), &self.prices),
+        )
+    }
+
+    /// Daily valuation, which sets the price of the subscriptions and the
+    /// redemptions until the next one
+    pub fn value_funds(&mut self, time: &TimeHandler) {
+        let fund_ids = self.funds.mapping.keys().cloned().collect::<Vec<_>>();
+
+        for fund_id in fund_ids {
+            let nav = self.get_fund_nav(&fund_id).unwrap();
+            let fund = self.funds.mapping.get_mut(&fund_id).unwrap();
+
+            if fund.units > 0.0 {
+                fund.nav_per_unit = nav / fund.units;
+            }
+
+            fund.last_valuation = time.get_now_unix_timestamp();
+        }
+    }
+
+    /// Portfolio that the mandate of the fund asks for, as ratios of the NAV
+    pub fn get_fund_target_weights(&self, fund_id: &FundId) -> BTreeMap<CompanySymbol, f64> {
+        let Some(fund) = self.funds.mapping.get(fund_id) else {
+            return BTreeMap::new();
+        };
+        let mandate = &fund.mandate;
+        let candidates = if mandate.follow_benchmark {
+            self.index.constituents.keys().cloned().collect::<Vec<_>>()
+        } else {
+            self.listed_companies.mapping.keys().cloned().collect()
+        };
+
+        let market_caps = candidates
+            .into_iter()
+            .filter(|symbol| self.is_tradeable(symbol))
+            .filter(|symbol| {
+                self.companies
+                    .mapping
+                    .get(symbol)
+                    .is_some_and(|company| mandate.allows_sector(&company.sector))
+            })
+            .filter_map(|symbol| {
+                let company = self.listed_companies.mapping.get(&symbol)?;
+                let price = self.prices.get_average_price(&symbol)?.to_f64();
+                let market_cap = price * company.get_free_float_shares() as f64;
+
+                (market_cap >= mandate.min_market_cap).then_some((symbol, market_cap))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let total = market_caps.values().sum::<f64>();
+
+        if market_caps.is_empty() || total <= 0.0 {
+            return BTreeMap::new();
+        }
+
+        let invested_ratio = 1.0 - mandate.cash_ratio;
+        let weights = market_caps
+            .iter()
+            .map(|(symbol, market_cap)| {
+                let weight = if mandate.follow_benchmark {
+                    market_cap / total
+                } else {
+                    1.0 / market_caps.len() as f64
+                };
+
+                (symbol.clone(), weight * invested_ratio)
+            })
+            .collect();
+
+        cap_weights(weights, mandate.max_position_ratio)
+    }
+
+    pub fn subscribe_to_fund(
+        &mut self,
+        investor_id: &InvestorId,
+        fund_id: &FundId,
+        amount: &Money,
+    ) -> Result<f64, String> {
+        let Some(investor) = self.investors.mapping.get_mut(investor_id) else {
+            return Err(format!("Investor {:?} not found", investor_id));
+        };
+        let Some(fund) = self.funds.mapping.get_mut(fund_id) else {
+            return Err(format!("Fund {:?} not found", fund_id));
+        };
+
+        if investor.liquid_cash.value < amount.value {
+            return Err(format!("Investor {:?} can't afford it", investor_id));
+        }
+
+        investor.subtract_cash(amount).map_err(|e| e.to_string())?;
+
+        fund.issue_units(investor_id, amount)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn redeem_from_fund(
+        &mut self,
+        investor_id: &InvestorId,
+        fund_id: &FundId,
+        units: f64,
+    ) -> Result<Money, String> {
+        let Some(fund) = self.funds.mapping.get_mut(fund_id) else {
+            return Err(format!("Fund {:?} not found", fund_id));
+        };
+        let Some(investor) = self.investors.mapping.get_mut(investor_id) else {
+            return Err(format!("Investor {:?} not found", investor_id));
+        };
+        let paid = fund
+            .cancel_units(investor_id, units)
+            .map_err(|e| e.to_string())?;
+
+        investor.add_cash(&paid).map_err(|e| e.to_string())?;
+
+        Ok(paid)
+    }
+
+    /// Redeems all the units of the investor, as far as the cash of the funds
+    /// allows
+    pub fn redeem_investor_units(&mut self, investor_id: &InvestorId) -> Result<(), String> {
+        let fund_ids = self.get_investor_funds(investor_id);
+
+        for fund_id in fund_ids {
+            self.redeem_from_fund(investor_id, &fund_id, f64::MAX)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_investor_funds(&self, investor_id: &InvestorId) -> Vec<FundId> {
+        self.funds
+            .mapping
+            .values()
+            .filter(|fund| fund.unitholders.contains_key(investor_id))
+            .map(|fund| fund.id)
+            .collect()
+    }
+
+    /// Value of the units of the investor at the last NAV
+    pub fn get_investor_units_value(&self, investor_id: &InvestorId) -> f64 {
+        self.funds
+            .mapping
+            .values()
+            .map(|fund| fund.get_investor_units(investor_id) * fund.nav_per_unit)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cap_weights() {
+        let weights = [("A", 0.6), ("B", 0.3), ("C", 0.1)]
+            .into_iter()
+            .map(|(symbol, weight)| (CompanySymbol::new(symbol.to_string()), weight))
+            .collect::<BTreeMap<_, _>>();
+
+        let capped = cap_weights(weights.clone(), 0.4)
+            .into_values()
+            .collect::<Vec<_>>();
+
+        assert!((capped[0] - 0.4).abs() < 1e-9);
+        assert!((capped[1] - 0.4).abs() < 1e-9);
+        assert!((capped[2] - 0.2).abs() < 1e-9);
+
+        // The cap doesn't allow investing everything
+        let capped = cap_weights(weights, 0.2).into_values().sum::<f64>();
+
+        assert!((capped - 0.6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,87 @@
+use super::StockExchange;
+use crate::core::{
+    investor::InvestorId,
+    money::{Currency, Money},
+};
+
+impl StockExchange {
+    /// Converts the amount with the current rates of the exchange
+    pub fn convert_money(&self, amount: &Money, to: &Currency) -> Result<Money, String> {
+        self.fx_rates.convert(amount, to).ok_or_else(|| {
+            format!(
+                "There is no rate from {} to {}",
+                amount.currency.get_code(),
+                to.get_code()
+            )
+        })
+    }
+
+    /// Value of the foreign cash balances of the investor, in the currency of
+    /// the exchange. The ones without a rate count as zero.
+    pub fn get_investor_foreign_cash_value(&self, investor_id: &InvestorId) -> f64 {
+        self.investors
+            .mapping
+            .get(investor_id)
+            .map_or(0.0, |investor| {
+                investor
+                    .foreign_cash
+                    .values()
+                    .filter_map(|cash| self.convert_money(cash, &self.get_currency()).ok())
+                    .map(|cash| cash.to_f64())
+                    .sum()
+            })
+    }
+
+    /// Exchanges cash of the investor to another currency at the current rate,
+    /// and returns what was received
+    pub fn exchange_investor_cash(
+        &mut self,
+        investor_id: &InvestorId,
+        amount: &Money,
+        to: &Currency,
+    ) -> Result<Money, String> {
+        let converted = self.convert_money(amount, to)?;
+        let Some(investor) = self.investors.mapping.get_mut(investor_id) else {
+            return Err(format!("Investor {:?} not found", investor_id));
+        };
+
+        if investor.get_cash(&amount.currency).value < amount.value {
+            return Err(format!(
+                "Investor {:?} doesn't have enough {}",
+                investor_id,
+                amount.currency.get_code()
+            ));
+        }
+
+        investor.subtract_cash(amount).map_err(|e| e.to_string())?;
+        investor.add_cash(&converted).map_err(|e| e.to_string())?;
+
+        Ok(converted)
+    }
+
+    /// Exchanges the foreign cash of the investor to the currency of the
+    /// exchange, which repays the debt first. The balances without a rate are
+    /// kept.
+    pub fn repatriate_investor_cash(&mut self, investor_id: &InvestorId) -> Result<(), String> {
+        let currency = self.get_currency();
+        let balances = self
+            .investors
+            .mapping
+            .get(investor_id)
+            .map(|investor| {
+                investor
+                    .foreign_cash
+                    .values()
+                    .filter(|cash| self.fx_rates.get_rate(&cash.currency, &currency).is_some())
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        for balance in balances {
+            self.exchange_investor_cash(investor_id, &balance, &currency)?;
+        }
+
+        Ok(())
+    }
+}
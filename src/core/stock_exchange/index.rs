@@ -0,0 +1,126 @@
+use super::StockExchange;
+use crate::core::{company::CompanySymbol, time::TimeHandler};
+use std::collections::BTreeMap;
+
+struct IndexCandidate {
+    market_cap: f64,
+    shares: u64,
+    symbol: CompanySymbol,
+}
+
+impl StockExchange {
+    fn get_index_candidate(&self, symbol: &CompanySymbol) -> Option<IndexCandidate> {
+        let company = self.listed_companies.mapping.get(symbol)?;
+
+        // The index tracks the home exchange
+        if !self.is_tradeable(symbol)
+            || !self.is_home_listing(symbol)
+            || self.is_etf(symbol)
+            || company.free_float < self.index.settings.min_free_float
+        {
+            return None;
+        }
+
+        let price = self.prices.get_average_price(symbol)?;
+        let shares = company.get_free_float_shares();
+
+        Some(IndexCandidate {
+            market_cap: price.to_f64() * shares as f64,
+            shares,
+            symbol: symbol.clone(),
+        })
+    }
+
+    pub fn rebalance_index(&mut self, time: &TimeHandler) {
+        let mut candidates = self
+            .listed_companies
+            .mapping
+            .keys()
+            .filter_map(|symbol| self.get_index_candidate(symbol))
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|a, b| {
+            b.market_cap
+                .total_cmp(&a.market_cap)
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        candidates.truncate(self.index.settings.max_constituents);
+
+        let constituents = candidates
+            .into_iter()
+            .map(|candidate| (candidate.symbol, candidate.shares))
+            .collect();
+
+        self.index.set_constituents(constituents, &self.prices);
+        self.index.last_rebalance = time.get_now_unix_timestamp();
+    }
+
+    pub fn rebalance_index_if_due(&mut self, time: &TimeHandler) {
+        let next_rebalance = time.get_n_days_from_unix_timestamp(
+            self.index.last_rebalance,
+            self.index.settings.rebalance_days,
+        );
+
+        if time.get_now_unix_timestamp() >= next_rebalance {
+            self.rebalance_index(time);
+        }
+    }
+
+    /// A new listing joins the index right away when it is bigger than the
+    /// smallest constituent, instead of waiting for the next rebalance
+    pub fn add_index_fast_entry(&mut self, symbol: &CompanySymbol) {
+        let Some(candidate) = self.get_index_candidate(symbol) else {
+            return;
+        };
+        let mut constituents = self.index.constituents.clone();
+
+        if constituents.len() >= self.index.settings.max_constituents {
+            let smallest = constituents
+                .keys()
+                .filter_map(|symbol| self.get_index_candidate(symbol))
+                .min_by(|a, b| a.market_cap.total_cmp(&b.market_cap));
+
+            match smallest {
+                Some(smallest) if smallest.market_cap < candidate.market_cap => {
+                    constituents.remove(&smallest.symbol);
+                }
+                _ => return,
+            }
+        }
+
+        constituents.insert(candidate.symbol, candidate.shares);
+        self.index.set_constituents(constituents, &self.prices);
+    }
+
+    /// Has to be called while the price of the symbol is still available
+    pub fn remove_index_constituent(&mut self, symbol: &CompanySymbol) {
+        if !self.index.constituents.contains_key(symbol) {
+            return;
+        }
+
+        let mut constituents = self.index.constituents.clone();
+        constituents.remove(symbol);
+
+        self.index.set_constituents(constituents, &self.prices);
+    }
+
+    /// Picks up the changes in the shares of the constituents, for example
+    /// after a corporate action
+    pub fn sync_index_shares(&mut self) {
+        let constituents = self
+            .index
+            .constituents
+            .keys()
+            .filter_map(|symbol| {
+                self.listed_companies
+                    .mapping
+                    .get(symbol)
+                    .map(|company| (symbol.clone(), company.get_free_float_shares()))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        if constituents != self.index.constituents {
+            self.index.set_constituents(constituents, &self.prices);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use super::StockExchange;
+use crate::core::{
+    company::{CompanyStatus, InstrumentKind, IpoStatus, ListedCompany},
+    price::Price,
+    stock::{Stock, StockOwner},
+    time::TimeHandler,
+};
+use rand::rngs::StdRng;
+
+impl StockExchange {
+    pub fn allot_ipos(&mut self, time: &TimeHandler, rng: &mut StdRng) -> Result<(), String> {
+        let now = time.get_now_unix_timestamp();
+        let closed_symbols = self
+            .ipos
+            .mapping
+            .values()
+            .filter(|ipo| ipo.status == IpoStatus::Offering && now >= ipo.offer_end)
+            .map(|ipo| ipo.symbol.clone())
+            .collect::<Vec<_>>();
+
+        for symbol in closed_symbols {
+            // The allotment and the refunds are applied once all of them,
+            // and the transition of a withdrawn offer, are known to succeed
+            let mut ipo = self.ipos.mapping[&symbol].clone();
+            let refunds = ipo
+                .allot(rng)
+                .map_err(|e| format!("Error allotting {}: {}", symbol, e))?;
+            let mut refunded_investors = BTreeMap::new();
+
+            for (investor_id, refund) in refunds {
+                let investor = self.investors.mapping.get(&investor_id).ok_or_else(|| {
+                    format!("Subscriber {:?} of {} not found", investor_id, symbol)
+                })?;
+
+                refunded_investors
+                    .entry(investor_id)
+                    .or_insert_with(|| investor.clone())
+                    .add_cash(&refund)
+                    .map_err(|e| format!("Error refunding {:?}: {}", investor_id, e))?;
+            }
+
+            let is_withdrawn = ipo.status == IpoStatus::Withdrawn;
+
+            if is_withdrawn {
+                self.verify_company_transition(&symbol, CompanyStatus::Private)?;
+            }
+
+            self.investors.mapping.extend(refunded_investors);
+            self.ipos.mapping.insert(symbol.clone(), ipo);
+
+            if is_withdrawn {
+                self.transition_company(&symbol, CompanyStatus::Private, time)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn list_ipos(&mut self, time: &TimeHandler) -> Result<(), String> {
+        let now = time.get_now_unix_timestamp();
+
+        let listing_symbols = self
+            .ipos
+            .mapping
+            .values()
+            .filter(|ipo| ipo.status == IpoStatus::Allotted && now >= ipo.date)
+            .map(|ipo| ipo.symbol.clone())
+            .collect::<Vec<_>>();
+
+        for symbol in listing_symbols {
+            let ipo = &self.ipos.mapping[&symbol];
+            let listed_company = ListedCompany {
+                exchange: ipo.exchange.clone(),
+                free_float: ipo.free_float,
+                instrument: InstrumentKind::Stock,
+                lot_size: ipo.lot_size,
+                primary_symbol: None,
+                symbol: ipo.symbol.clone(),
+                total_stocks: ipo.shares,
+            };
+
+            listed_company
+                .verify()
+                .map_err(|e| format!("Invalid IPO listing for {}: {:?}", ipo.symbol, e))?;
+            self.verify_company_transition(&symbol, CompanyStatus::Listed)?;
+
+            let ipo = self.ipos.mapping.get_mut(&symbol).unwrap();
+
+            ipo.status = IpoStatus::Listed;
+
+            for subscription in ipo.subscriptions.iter() {
+                if subscription.allotted_lots == 0 {
+                    continue;
+                }
+
+                let stock = Stock {
+                    owner: StockOwner::Investor(subscription.investor_id),
+                    price: ipo.offer_price,
+                    quantity: subscription.allotted_lots * ipo.lot_size,
+                    symbol: ipo.symbol.clone(),
+                };
+
+                self.owned_stocks
+                    .entry_with_default(&stock.owner)
+                    .push(stock);
+            }
+
+            self.prices.0.insert(
+                ipo.symbol.clone(),
+                Price {
+                    ask: ipo.offer_price,
+                    bid: ipo.offer_price,
+                },
+            );
+            self.listed_companies
+                .mapping
+                .insert(ipo.symbol.clone(), listed_company);
+
+            self.transition_company(&symbol, CompanyStatus::Listed, time)?;
+            self.add_index_fast_entry(&symbol);
+        }
+
+        Ok(())
+    }
+
+    pub fn record_ipos_first_day_close(&mut self, time: &TimeHandler) {
+        let now = time.get_now_unix_timestamp();
+
+        for ipo in self.ipos.mapping.values_mut() {
+            if ipo.status != IpoStatus::Listed
+                || ipo.first_day_close.is_some()
+                || now < time.get_n_days_from_unix_timestamp(ipo.date, 1)
+            {
+                continue;
+            }
+
+            ipo.first_day_close = self.prices.get_average_price(&ipo.symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{
+        company::{CompanySymbol, Ipo, IpoAllotmentMethod, IpoSubscription},
+        investor::InvestorId,
+        stock_exchange::ExchangeId,
+        test_fixtures::{build_company, build_money},
+    };
+    use rand::SeedableRng;
+
+    fn build_ipo(
+        symbol: &CompanySymbol,
+        investor_id: InvestorId,
+        lots: u64,
+        status: IpoStatus,
+    ) -> Ipo {
+        let offer_price = build_money(2.0);
+        let allotted_lots = if status == IpoStatus::Offering {
+            0
+        } else {
+            lots
+        };
+
+        Ipo {
+            allotment_method: IpoAllotmentMethod::Ballot,
+            date: 0,
+            exchange: ExchangeId("SH".to_string()),
+            first_day_close: None,
+            free_float: 1.0,
+            lot_size: 100,
+            offer_end: 0,
+            offer_price,
+            offer_start: 0,
+            offered_lots: 10,
+            shares: 1_000,
+            status,
+            subscriptions: vec![IpoSubscription {
+                allotted_lots,
+                investor_id,
+                locked_cash: offer_price.checked_mul(lots * 100).unwrap(),
+                lots,
+            }],
+            symbol: symbol.clone(),
+        }
+    }
+
+    #[test]
+    fn test_rejected_listing_changes_nothing() {
+        let time = TimeHandler::new(0, None, 1000);
+        let mut se = StockExchange::default();
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let investor_id = InvestorId::new(&InvestorId::init());
+
+        // A delisted company can't be listed again
+        se.companies.mapping.insert(
+            symbol.clone(),
+            build_company(&symbol, CompanyStatus::Delisted),
+        );
+        se.ipos.mapping.insert(
+            symbol.clone(),
+            build_ipo(&symbol, investor_id, 10, IpoStatus::Allotted),
+        );
+
+        assert!(se.list_ipos(&time).is_err());
+        assert_eq!(se.ipos.mapping[&symbol].status, IpoStatus::Allotted);
+        assert!(se.listed_companies.mapping.is_empty());
+        assert!(se.prices.0.is_empty());
+        assert_eq!(
+            se.owned_stocks
+                .get_quantity(&StockOwner::Investor(investor_id), &symbol),
+            0
+        );
+    }
+
+    #[test]
+    fn test_listing_on_the_ipo_exchange() {
+        let time = TimeHandler::new(0, None, 1000);
+        let mut se = StockExchange {
+            home_exchange: ExchangeId("HK".to_string()),
+            ..Default::default()
+        };
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let investor_id = InvestorId::new(&InvestorId::init());
+
+        se.companies.mapping.insert(
+            symbol.clone(),
+            build_company(&symbol, CompanyStatus::IpoPending),
+        );
+        se.ipos.mapping.insert(
+            symbol.clone(),
+            build_ipo(&symbol, investor_id, 10, IpoStatus::Allotted),
+        );
+
+        se.list_ipos(&time).unwrap();
+
+        assert_eq!(
+            se.listed_companies.mapping[&symbol].exchange,
+            ExchangeId("SH".to_string())
+        );
+        assert_eq!(
+            se.owned_stocks
+                .get_quantity(&StockOwner::Investor(investor_id), &symbol),
+            1_000
+        );
+    }
+
+    #[test]
+    fn test_refund_to_a_missing_subscriber_fails() {
+        let time = TimeHandler::new(0, None, 1000);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut se = StockExchange::default();
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let investor_id = InvestorId::new(&InvestorId::init());
+
+        se.companies.mapping.insert(
+            symbol.clone(),
+            build_company(&symbol, CompanyStatus::IpoPending),
+        );
+        // Oversubscribed, so half of the locked cash is refunded
+        se.ipos.mapping.insert(
+            symbol.clone(),
+            build_ipo(&symbol, investor_id, 20, IpoStatus::Offering),
+        );
+
+        assert!(se.allot_ipos(&time, &mut rng).is_err());
+        assert_eq!(se.ipos.mapping[&symbol].status, IpoStatus::Offering);
+    }
+}
@@ -1,33 +1,20 @@
 use super::StockExchange;
 use crate::core::{
+    journal::JournalEvent,
     order::{CentralOrderBook, Order},
     time::TimeHandler,
 };
-use std::collections::BTreeSet;
 
 impl StockExchange {
+    /// Whether any of the exchanges is in session
     pub fn can_trade_now(&self, time: &TimeHandler) -> bool {
-        let num_weekday = time.get_weekday() as u8;
-
-        if !self.settings.trading_days.contains(&num_weekday) {
-            return false;
-        }
-
-        let current_day = time.get_virtual_day_formatted();
-        let current_year = time.get_virtual_year_formatted();
-        let default_holidays = BTreeSet::new();
-        let year_holidays = self
-            .holidays
-            .get(&current_year)
-            .unwrap_or(&default_holidays);
-
-        if year_holidays.contains(&current_day) {
-            return false;
-        }
-
-        let num_hour = time.get_day24hour() as u8;
+        self.exchanges
+            .values()
+            .any(|exchange| exchange.get_session(time).is_some())
+    }
 
-        self.settings.trading_hours.contains(&num_hour)
+    pub fn record_event(&mut self, event: JournalEvent) {
+        self.journal_events.push(event);
     }
 }
 
@@ -35,6 +22,7 @@ impl StockExchange {
 pub enum PlaceOrderError {
     CantTradeNow,
     InvalidOrder,
+    NotTradeable,
 }
 
 impl StockExchange {
@@ -43,13 +31,23 @@ impl StockExchange {
         order: &Order,
         time: &TimeHandler,
     ) -> Result<(), PlaceOrderError> {
-        if !self.can_trade_now(time) {
+        if self
+            .get_listing_exchange(&order.symbol)
+            .is_some_and(|exchange| !exchange.is_open(time))
+        {
             return Err(PlaceOrderError::CantTradeNow);
         }
 
         order.verify().map_err(|_| PlaceOrderError::InvalidOrder)?;
 
+        if !self.is_tradeable(&order.symbol) {
+            return Err(PlaceOrderError::NotTradeable);
+        }
+
         self.orders_book.0.push(order.clone());
+        self.record_event(JournalEvent::OrderPlaced {
+            order: order.clone(),
+        });
 
         Ok(())
     }
@@ -61,29 +59,172 @@ impl StockExchange {
 
         self.orders_book = CentralOrderBook::default();
     }
+
+    /// Cancels the orders of the exchanges that are not in session. The ones
+    /// of the lunch break are kept for the afternoon.
+    pub fn flush_closed_orders(&mut self, time: &TimeHandler) {
+        let open_exchanges = self.get_exchanges_in_any_session(time);
+        let listed_companies = &self.listed_companies;
+
+        self.orders_book.0.retain(|order| {
+            listed_companies
+                .mapping
+                .get(&order.symbol)
+                .is_some_and(|listed| open_exchanges.contains(&listed.exchange))
+        });
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::core::stock_exchange::StockExchangeSettings;
+    use crate::core::{
+        company::CompanySymbol,
+        session::{Session, SessionKind},
+        stock_exchange::{ExchangeId, StockExchangeSettings},
+        test_fixtures::{add_listing, build_listing, build_money},
+        time::Timezone,
+    };
 
     use super::*;
 
     #[test]
     fn test_can_trade_now() {
         let mut time = TimeHandler::new(0, Some(1), 100);
-        let se = StockExchange::new(StockExchangeSettings {
-            trading_days: vec![0, 1, 2, 3, 4],
-            trading_hours: vec![9, 10, 11, 12, 13, 14, 15],
-            ..Default::default()
-        });
+        let se = StockExchange::new(vec![
+            StockExchangeSettings {
+                id: ExchangeId("HK".to_string()),
+                trading_days: vec![0, 1, 2, 3, 4],
+                sessions: vec![Session::new(
+                    SessionKind::MorningContinuous,
+                    (9, 0),
+                    (16, 0),
+                )],
+                ..Default::default()
+            },
+            StockExchangeSettings {
+                id: ExchangeId("SH".to_string()),
+                trading_days: vec![0, 1, 2, 3, 4],
+                sessions: vec![Session::new(
+                    SessionKind::MorningContinuous,
+                    (10, 0),
+                    (11, 0),
+                )],
+                ..Default::default()
+            },
+        ]);
 
         assert_eq!(time.get_virtual_time_formatted(), "1970-01-01 08:00:00 HKT");
-        assert_eq!(se.can_trade_now(&time), false);
+        assert!(!se.can_trade_now(&time));
 
         time.set_time(60 * 60 * 10);
 
         assert_eq!(time.get_virtual_time_formatted(), "1970-01-01 09:00:00 HKT");
-        assert_eq!(se.can_trade_now(&time), true);
+        assert!(se.can_trade_now(&time));
+        assert_eq!(
+            se.get_open_exchanges(&time),
+            [ExchangeId("HK".to_string())].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_can_trade_now_with_daylight_saving() {
+        let se = StockExchange::new(vec![StockExchangeSettings {
+            id: ExchangeId("NY".to_string()),
+            timezone: Timezone(chrono_tz::America::New_York),
+            trading_days: vec![0, 1, 2, 3, 4],
+            sessions: vec![Session::new(
+                SessionKind::MorningContinuous,
+                (9, 30),
+                (16, 0),
+            )],
+            ..Default::default()
+        }]);
+        // 2024-01-15 13:30 UTC, 08:30 in New York (EST)
+        let winter = TimeHandler::new(1_705_325_400, Some(1), 100);
+        // 2024-07-15 13:30 UTC, 09:30 in New York (EDT)
+        let summer = TimeHandler::new(1_721_050_200, Some(1), 100);
+
+        assert!(!se.can_trade_now(&winter));
+        assert!(se.can_trade_now(&summer));
+    }
+
+    #[test]
+    fn test_lunch_break_and_half_day() {
+        let morning = vec![
+            Session::new(SessionKind::MorningContinuous, (9, 30), (12, 0)),
+            Session::new(SessionKind::Lunch, (12, 0), (13, 0)),
+            Session::new(SessionKind::AfternoonContinuous, (13, 0), (16, 0)),
+        ];
+        let se = StockExchange::new(vec![StockExchangeSettings {
+            half_days: [("12-24".to_string(), morning[..1].to_vec())]
+                .into_iter()
+                .collect(),
+            sessions: morning,
+            trading_days: vec![0, 1, 2, 3, 4],
+            ..Default::default()
+        }]);
+        let exchange = se.get_home_exchange().unwrap();
+        // 2024-12-23 12:30 and 14:00 HKT, a Monday
+        let lunch = TimeHandler::new(1_734_928_200, Some(1), 100);
+        let afternoon = TimeHandler::new(1_734_933_600, Some(1), 100);
+        // 2024-12-24 14:00 HKT
+        let half_day = TimeHandler::new(1_735_020_000, Some(1), 100);
+
+        assert_eq!(exchange.get_session(&lunch), Some(SessionKind::Lunch));
+        assert!(se.can_trade_now(&lunch));
+        assert!(se.get_open_exchanges(&lunch).is_empty());
+        assert_eq!(
+            exchange.get_session(&afternoon),
+            Some(SessionKind::AfternoonContinuous)
+        );
+        assert_eq!(exchange.get_session(&half_day), None);
+    }
+
+    #[test]
+    fn test_session_prices() {
+        let mut se = StockExchange::new(vec![StockExchangeSettings {
+            sessions: vec![Session::new(
+                SessionKind::MorningContinuous,
+                (9, 30),
+                (16, 0),
+            )],
+            trading_days: vec![0, 1, 2, 3, 4],
+            ..Default::default()
+        }]);
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let listing = build_listing(&symbol, &se.home_exchange);
+
+        add_listing(&mut se, listing, build_money(10.0));
+
+        // 2024-12-23 10:00 HKT, a Monday, and 2024-12-22 10:00 HKT
+        let open = TimeHandler::new(1_734_919_200, Some(1), 100);
+        let weekend = TimeHandler::new(1_734_832_800, Some(1), 100);
+
+        assert!(se.get_session_prices(&open).0.contains_key(&symbol));
+        assert!(se.get_session_prices(&weekend).0.is_empty());
+    }
+
+    #[test]
+    fn test_next_open_over_the_weekend() {
+        let se = StockExchange::new(vec![StockExchangeSettings {
+            sessions: vec![Session::new(
+                SessionKind::MorningContinuous,
+                (9, 30),
+                (16, 0),
+            )],
+            trading_days: vec![0, 1, 2, 3, 4],
+            ..Default::default()
+        }]);
+        // 2024-12-20 17:00 HKT, a Friday
+        let mut time = TimeHandler::new(1_734_685_200, Some(1), 100);
+        // 2024-12-23 09:30 HKT
+        let next_open = 1_734_917_400;
+
+        assert_eq!(se.get_next_open_timestamp(&time), Some(next_open));
+
+        time.jump_to(next_open);
+
+        assert_eq!(time.get_virtual_time_formatted(), "2024-12-23 09:30:00 HKT");
+        assert!(se.can_trade_now(&time));
     }
 }
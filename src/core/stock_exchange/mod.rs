@@ -2,48 +2,118 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use crate::core::{
     broker::Brokers,
-    company::{Companies, Ipos, ListedCompanies},
+    candle::CandleAggregator,
+    company::{Companies, CompanySymbol, DelistedCompanies, Ipos, ListedCompanies},
+    etf::Etfs,
+    fund::Funds,
+    fx::FxRates,
     investor::Investors,
+    journal::JournalEvent,
+    market_index::MarketIndex,
     market_maker::MarketMakers,
     money::Currency,
     order::CentralOrderBook,
-    price::Prices,
+    price::{PriceReturns, Prices},
+    session::Session,
     stock::OwnedStocks,
+    time::Timezone,
 };
 use serde::{Deserialize, Serialize};
 
+mod companies;
+mod correlations;
+mod delisting;
+mod estate;
+mod etfs;
+mod exchanges;
+mod funds;
+mod fx;
+mod index;
+mod ipos;
 mod methods;
 mod order_matching;
+mod wealth;
+
+pub use wealth::{get_gini, get_percentile};
+
+/// Code of an exchange, which is also the suffix of its secondary listings
+#[derive(Clone, Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd, Default)]
+#[serde(transparent)]
+pub struct ExchangeId(pub String);
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
 pub struct StockExchangeSettings {
     pub currency: Currency,
+    /** Shorter sessions of some dates, by "YYYY-MM-DD" or yearly by "MM-DD" */
+    pub half_days: BTreeMap<String, Vec<Session>>,
+    /** ICS or JSON calendar file with the holidays, which are random without it */
+    pub holidays_file: Option<String>,
+    pub id: ExchangeId,
     pub location: String,
     pub name: String,
-    pub timezone: String,
+    /** Sessions of a trading day, in the local time of the exchange */
+    pub sessions: Vec<Session>,
+    pub timezone: Timezone,
     pub trading_days: Vec<u8>,
-    pub trading_hours: Vec<u8>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Exchange {
+    pub holidays: BTreeMap<String, BTreeSet<String>>,
+    pub settings: StockExchangeSettings,
+}
+
+/// The simulated market, which clears the trades of all its exchanges. The
+/// investors, funds and ETFs are domiciled in the home exchange.
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct StockExchange {
     pub brokers: Brokers,
+    pub candles: CandleAggregator,
     pub companies: Companies,
-    pub holidays: BTreeMap<String, BTreeSet<String>>,
+    pub delisted_companies: DelistedCompanies,
+    pub etfs: Etfs,
+    pub exchanges: BTreeMap<ExchangeId, Exchange>,
+    /** Value of each company based on its fundamentals, with the price currency */
+    pub fair_values: BTreeMap<CompanySymbol, f64>,
+    pub funds: Funds,
+    pub fx_rates: FxRates,
+    pub home_exchange: ExchangeId,
+    pub index: MarketIndex,
     pub investors: Investors,
     pub ipos: Ipos,
+    /** Events since the last time the journal took them */
+    #[serde(skip)]
+    pub journal_events: Vec<JournalEvent>,
     pub listed_companies: ListedCompanies,
     pub market_makers: MarketMakers,
     pub orders_book: CentralOrderBook,
     pub owned_stocks: OwnedStocks,
     pub prices: Prices,
-    pub settings: StockExchangeSettings,
+    /** Missing in the states saved before it, which start it over */
+    #[serde(default)]
+    pub returns: PriceReturns,
 }
 
 impl StockExchange {
-    pub fn new(settings: StockExchangeSettings) -> Self {
+    /// The first exchange is the home one
+    pub fn new(exchanges: Vec<StockExchangeSettings>) -> Self {
         StockExchange {
-            settings,
+            home_exchange: exchanges
+                .first()
+                .map(|settings| settings.id.clone())
+                .unwrap_or_default(),
+            exchanges: exchanges
+                .into_iter()
+                .map(|settings| {
+                    (
+                        settings.id.clone(),
+                        Exchange {
+                            holidays: BTreeMap::new(),
+                            settings,
+                        },
+                    )
+                })
+                .collect(),
             ..Default::default()
         }
     }
@@ -1,125 +1,166 @@
 use super::StockExchange;
 use crate::core::{
-    money::Money,
-    order::{Order, OrderSide, OrderType},
+    journal::JournalEvent,
+    money::{Money, MoneyError},
+    order::{Order, OrderSide},
     stock::{Stock, StockOwner},
+    time::TimeHandler,
 };
+use log::error;
 use std::{cmp::max, collections::HashSet};
 
 impl StockExchange {
-    pub fn execute_orders(&mut self) {
+    fn can_pay(&self, payer_id: &StockOwner, total: &Money) -> bool {
+        let cash = match payer_id {
+            // The market makers have unlimited liquidity
+            StockOwner::MarketMaker(_) => return true,
+            // The custodians buy with the cash in lieu of the creations
+            StockOwner::Etf(id) => self.etfs.mapping.get(id).map(|etf| etf.cash),
+            StockOwner::Fund(id) => self.funds.mapping.get(id).map(|fund| fund.cash),
+            StockOwner::Investor(id) => self
+                .investors
+                .mapping
+                .get(id)
+                .map(|investor| investor.get_cash(&total.currency)),
+        };
+
+        cash.and_then(|cash| cash.checked_sub(total).ok())
+            .is_some_and(|rest| !rest.is_negative())
+    }
+
+    /// Moves the payment of a trade from the payer to the seller
+    fn transfer_payment(
+        &mut self,
+        payer_id: &StockOwner,
+        seller_id: &StockOwner,
+        total: &Money,
+    ) -> Result<(), MoneyError> {
+        match payer_id {
+            StockOwner::MarketMaker(_) => {}
+            StockOwner::Etf(id) => {
+                let payer = self.etfs.mapping.get_mut(id).unwrap();
+
+                payer.cash = payer.cash.checked_sub(total)?;
+            }
+            StockOwner::Fund(id) => {
+                let payer = self.funds.mapping.get_mut(id).unwrap();
+
+                payer.cash = payer.cash.checked_sub(total)?;
+            }
+            StockOwner::Investor(id) => {
+                self.investors
+                    .mapping
+                    .get_mut(id)
+                    .unwrap()
+                    .subtract_cash(total)?;
+            }
+        };
+
+        match seller_id {
+            StockOwner::MarketMaker(_) => {}
+            StockOwner::Etf(id) => {
+                let seller = self.etfs.mapping.get_mut(id).unwrap();
+
+                seller.cash = seller.cash.checked_add(total)?;
+            }
+            StockOwner::Fund(id) => {
+                let seller = self.funds.mapping.get_mut(id).unwrap();
+
+                seller.cash = seller.cash.checked_add(total)?;
+            }
+            StockOwner::Investor(id) => {
+                self.investors
+                    .mapping
+                    .get_mut(id)
+                    .unwrap()
+                    .add_cash(total)?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Matches the orders of the exchanges in a session that allows it, the
+    /// rest wait in the book
+    pub fn execute_orders(&mut self, time: &TimeHandler) {
         let mut orders_to_remove: HashSet<Order> = Default::default();
 
-        for order in &self.orders_book.0 {
-            let corresponding_orders = self
-                .orders_book
-                .get_matching_orders(order, Some(&orders_to_remove));
+        let orders = self.orders_book.0.clone();
+        // The orders only match the ones of their symbol
+        let symbol_books = self.orders_book.split_by_symbol();
+        let matching_exchanges = self.get_matching_exchanges(time);
+
+        for order in &orders {
+            if !self
+                .get_listing_exchange_id(&order.symbol)
+                .is_some_and(|exchange_id| matching_exchanges.contains(exchange_id))
+            {
+                continue;
+            }
 
             if orders_to_remove.contains(order) {
                 continue;
             }
 
+            let Some(price) = self
+                .prices
+                .get_average_price(&order.symbol)
+                .filter(|price| order.accepts_price(price))
+            else {
+                continue;
+            };
+            let corresponding_orders =
+                symbol_books[&order.symbol].get_matching_orders(order, Some(&orders_to_remove));
+
             let Some((payer_id, total_pay, affordable_order)) =
                 corresponding_orders.iter().find_map(|other_order| {
-                    let price = self.prices.get_average_price(&order.symbol)?;
-                    let total = price.value * Money::from_u64(other_order.shares);
+                    let total_pay = price.checked_mul(other_order.shares).ok()?;
                     let payer_id = if order.order_side == OrderSide::Buy {
                         &order.owner_id
                     } else {
                         &other_order.owner_id
                     };
 
-                    match order.clone().order_type {
-                        OrderType::Market => {}
-                        OrderType::Limit { price } => {
-                            let limit_price = Money {
-                                value: price.parse().unwrap(),
-                                currency: Default::default(),
-                            };
-
-                            if order.order_side == OrderSide::Buy {
-                                if limit_price.value < total {
-                                    return None;
-                                }
-                            } else if limit_price.value > total {
-                                return None;
-                            }
-                        }
-                    };
-
-                    match payer_id {
-                        StockOwner::Investor(id) => {
-                            let payer =
-                                self.investors.mapping.iter().find(|i| i.0 == id).unwrap().1;
-
-                            if payer.liquid_cash.value >= total {
-                                let total_pay = Money {
-                                    value: total,
-                                    currency: price.currency,
-                                };
-                                Some((payer_id, total_pay, other_order.clone()))
-                            } else {
-                                None
-                            }
-                        }
-                        StockOwner::MarketMaker(_) => {
-                            let total_pay = Money {
-                                value: total,
-                                currency: price.currency,
-                            };
-                            Some((payer_id, total_pay, other_order.clone()))
-                        }
+                    if !other_order.accepts_price(&price) {
+                        return None;
                     }
+
+                    self.can_pay(payer_id, &total_pay)
+                        .then(|| (payer_id, total_pay, other_order.clone()))
                 })
             else {
                 continue;
             };
 
-            orders_to_remove.insert(affordable_order.clone());
-            orders_to_remove.insert(order.clone());
+            let payer_id = *payer_id;
 
+            if let Err(e) = self.transfer_payment(&payer_id, &affordable_order.owner_id, &total_pay)
             {
-                match payer_id {
-                    StockOwner::MarketMaker(_) => {}
-                    StockOwner::Investor(payer_id) => {
-                        let payer = self
-                            .investors
-                            .mapping
-                            .iter_mut()
-                            .find(|i| i.0 == payer_id)
-                            .unwrap()
-                            .1;
-
-                        payer.subtract_cash(&total_pay);
-                    }
-                };
+                error!("Error paying the trade of {}: {}", order.symbol, e);
+                continue;
             }
 
-            {
-                match affordable_order.owner_id {
-                    StockOwner::MarketMaker(_) => {}
-                    StockOwner::Investor(owner_id) => {
-                        let seller = self
-                            .investors
-                            .mapping
-                            .iter_mut()
-                            .find(|i| i.0 == &owner_id)
-                            .unwrap()
-                            .1;
-
-                        seller.add_cash(&total_pay);
-                    }
-                };
-            }
+            self.candles
+                .record_trade(&order.symbol, affordable_order.shares, total_pay.to_f64());
+            self.record_event(JournalEvent::OrderFilled {
+                buyer: payer_id,
+                seller: affordable_order.owner_id,
+                shares: affordable_order.shares,
+                symbol: order.symbol.clone(),
+                total: total_pay,
+            });
+
+            orders_to_remove.insert(affordable_order.clone());
+            orders_to_remove.insert(order.clone());
 
             let new_stock = Stock {
-                owner: *payer_id,
-                price: self.prices.0.get(&order.symbol).unwrap().get_average(),
+                owner: payer_id,
+                price,
                 quantity: affordable_order.shares,
                 symbol: order.symbol.clone(),
             };
 
-            let payer_stocs = self.owned_stocks.entry_with_default(payer_id);
+            let payer_stocs = self.owned_stocks.entry_with_default(&payer_id);
 
             payer_stocs.push(new_stock.clone());
 
@@ -148,8 +189,8 @@ impl StockExchange {
             seller_all_stocks.retain(|stock| stock.quantity > 0);
         }
 
-        for order in orders_to_remove {
-            self.orders_book.0.retain(|o| o != &order);
-        }
+        self.orders_book
+            .0
+            .retain(|order| !orders_to_remove.contains(order));
     }
 }
@@ -0,0 +1,272 @@
+use super::StockExchange;
+use crate::core::{
+    company::CompanySymbol,
+    investor::InvestorId,
+    money::{Currency, Money},
+    price::{Price, Prices},
+    stock::{Stock, StockOwner},
+};
+use std::collections::BTreeMap;
+
+/// Gini coefficient of the values, where negative values count as zero
+pub fn get_gini(values: &[f64]) -> Option<f64> {
+    let mut values = values.iter().map(|v| v.max(0.0)).collect::<Vec<_>>();
+    let total = values.iter().sum::<f64>();
+
+    if values.is_empty() || total <= 0.0 {
+        return None;
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+
+    let n = values.len() as f64;
+    let weighted_sum = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (i + 1) as f64 * value)
+        .sum::<f64>();
+
+    Some(2.0 * weighted_sum / (n * total) - (n + 1.0) / n)
+}
+
+/// Nearest-rank percentile, `percentile` being between 0 and 100. The values
+/// have to be sorted.
+pub fn get_percentile(sorted_values: &[f64], percentile: f64) -> Option<f64> {
+    if sorted_values.is_empty() {
+        return None;
+    }
+
+    let rank = (percentile / 100.0 * sorted_values.len() as f64).ceil() as usize;
+
+    Some(sorted_values[rank.clamp(1, sorted_values.len()) - 1])
+}
+
+impl StockExchange {
+    /// Cash of the investors, funds and ETFs in each currency they have
+    pub fn get_cash_balances(&self) -> BTreeMap<StockOwner, BTreeMap<Currency, Money>> {
+        let investors = self.investors.mapping.values().map(|investor| {
+            let balances = investor
+                .foreign_cash
+                .iter()
+                .map(|(currency, cash)| (*currency, *cash))
+                .chain([(investor.liquid_cash.currency, investor.liquid_cash)]);
+
+            (StockOwner::Investor(investor.id), balances.collect())
+        });
+        let funds = self.funds.mapping.values().map(|fund| {
+            let balances = [(fund.cash.currency, fund.cash)].into_iter().collect();
+
+            (StockOwner::Fund(fund.id), balances)
+        });
+        let etfs = self.etfs.mapping.values().map(|etf| {
+            let balances = [(etf.cash.currency, etf.cash)].into_iter().collect();
+
+            (StockOwner::Etf(etf.id), balances)
+        });
+
+        investors.chain(funds).chain(etfs).collect()
+    }
+
+    /// Shares of each symbol of every owner, adding up their entries
+    pub fn get_holding_balances(&self) -> BTreeMap<StockOwner, BTreeMap<CompanySymbol, u64>> {
+        self.owned_stocks
+            .0
+            .iter()
+            .map(|(owner, stocks)| {
+                let mut balances = BTreeMap::new();
+
+                for stock in stocks {
+                    *balances.entry(stock.symbol.clone()).or_insert(0) += stock.quantity;
+                }
+
+                (*owner, balances)
+            })
+            .collect()
+    }
+
+    /// Value of the shares of the owner with the prices, in the currency of
+    /// the home exchange. The ones without price or rate count as zero.
+    pub fn get_holdings_value(&self, owner: &StockOwner, prices: &Prices) -> f64 {
+        let currency = self.get_currency();
+        // The holdings can have many entries of the same symbol
+        let mut symbol_prices = BTreeMap::new();
+
+        self.owned_stocks.0.get(owner).map_or(0.0, |stocks| {
+            stocks
+                .iter()
+                .filter_map(|stock| {
+                    let price = *symbol_prices.entry(&stock.symbol).or_insert_with(|| {
+                        let price = prices.get_average_price(&stock.symbol)?;
+
+                        self.convert_money(&price, &currency)
+                            .ok()
+                            .map(|price| price.to_f64())
+                    });
+
+                    Some(price? * stock.quantity as f64)
+                })
+                .sum()
+        })
+    }
+
+    /// Net worth of the investor: cash in all the currencies plus holdings at
+    /// the current price and fund units at the last NAV, minus the debt
+    pub fn get_investor_wealth(&self, investor_id: &InvestorId) -> Option<f64> {
+        let investor = self.investors.mapping.get(investor_id)?;
+        let holdings = self.get_holdings_value(&StockOwner::Investor(*investor_id), &self.prices);
+
+        let units = self.get_investor_units_value(investor_id);
+        let cash =
+            investor.liquid_cash.to_f64() + self.get_investor_foreign_cash_value(investor_id);
+
+        Some(cash + holdings + units - investor.debt.to_f64())
+    }
+
+    pub fn get_investors_wealth(&self) -> Vec<f64> {
+        self.investors
+            .mapping
+            .keys()
+            .filter_map(|investor_id| self.get_investor_wealth(investor_id))
+            .collect()
+    }
+
+    /// Sells all the holdings of the investor to a market maker at the bid
+    /// price, redeems the fund units and exchanges the foreign cash. The
+    /// proceeds repay the debt first.
+    pub fn liquidate_investor_holdings(&mut self, investor_id: &InvestorId) -> Result<(), String> {
+        let owner = StockOwner::Investor(*investor_id);
+        let Some(market_maker_id) = self.market_makers.mapping.keys().next().cloned() else {
+            return Err("There is no market maker to liquidate the holdings".to_string());
+        };
+
+        self.redeem_investor_units(investor_id)?;
+
+        self.orders_book.0.retain(|order| order.owner_id != owner);
+
+        let stocks = self.owned_stocks.0.remove(&owner).unwrap_or_default();
+        let market_maker = StockOwner::MarketMaker(market_maker_id);
+
+        for stock in stocks {
+            let price = self
+                .prices
+                .0
+                .get(&stock.symbol)
+                .filter(|_| self.is_tradeable(&stock.symbol));
+            let Some(Price { bid, .. }) = price.cloned() else {
+                // Suspended or without a price, so it can't be liquidated
+                self.owned_stocks.entry_with_default(&owner).push(stock);
+                continue;
+            };
+            let proceeds = bid.checked_mul(stock.quantity).map_err(|e| e.to_string())?;
+
+            if let Some(investor) = self.investors.mapping.get_mut(investor_id) {
+                investor.add_cash(&proceeds).map_err(|e| e.to_string())?;
+            }
+
+            self.owned_stocks
+                .entry_with_default(&market_maker)
+                .push(Stock {
+                    owner: market_maker,
+                    price: bid,
+                    ..stock
+                });
+        }
+
+        // Including the proceeds of the listings in other currencies
+        self.repatriate_investor_cash(investor_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{
+        company::CompanyStatus,
+        market_maker::{MarketMaker, MarketMakerId},
+        order::{Order, OrderSide, OrderStatus, OrderType},
+        test_fixtures::{
+            add_holding, add_listing, build_investor, build_listing, build_money, build_symbol,
+        },
+    };
+
+    #[test]
+    fn test_gini() {
+        assert_eq!(get_gini(&[5.0, 5.0, 5.0, 5.0]), Some(0.0));
+        assert_eq!(get_gini(&[0.0, 0.0, 0.0, 10.0]), Some(0.75));
+        assert_eq!(get_gini(&[]), None);
+
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+
+        assert_eq!(get_percentile(&sorted, 50.0), Some(5.0));
+        assert_eq!(get_percentile(&sorted, 99.0), Some(10.0));
+        assert_eq!(get_percentile(&sorted, 0.0), Some(1.0));
+    }
+
+    #[test]
+    fn test_liquidate_investor_holdings() {
+        let mut se = StockExchange::default();
+        let investor_id = InvestorId::new(&InvestorId::init());
+        let owner = StockOwner::Investor(investor_id);
+        let market_maker_id = MarketMakerId::init();
+
+        se.market_makers.mapping.insert(
+            market_maker_id,
+            MarketMaker {
+                id: market_maker_id,
+                permit_end_time: 1,
+                permit_start_time: 0,
+            },
+        );
+        se.investors
+            .mapping
+            .insert(investor_id, build_investor(investor_id, build_money(0.0)));
+
+        // FOO trades, BAR is suspended and BAZ has no price
+        for (symbol, price) in [("FOO", 10.0), ("BAR", 5.0), ("BAZ", 2.0)] {
+            let symbol = build_symbol(symbol);
+            let listing = build_listing(&symbol, &se.home_exchange);
+
+            add_listing(&mut se, listing, build_money(price));
+            add_holding(&mut se, owner, &symbol, build_money(1.0), 100);
+            se.orders_book.0.push(Order {
+                order_side: OrderSide::Sell,
+                order_type: OrderType::Market,
+                owner_id: owner,
+                shares: 10,
+                status: OrderStatus::Init,
+                symbol,
+            });
+        }
+
+        se.companies
+            .mapping
+            .get_mut(&build_symbol("BAR"))
+            .unwrap()
+            .status = CompanyStatus::Suspended;
+        se.prices.0.remove(&build_symbol("BAZ"));
+
+        se.liquidate_investor_holdings(&investor_id).unwrap();
+
+        // Only FOO is sold, at the bid
+        assert_eq!(
+            se.investors.mapping[&investor_id].liquid_cash.value,
+            build_money(1000.0).value
+        );
+        assert!(se.orders_book.0.is_empty());
+
+        for (symbol, quantity) in [("FOO", 0), ("BAR", 100), ("BAZ", 100)] {
+            assert_eq!(
+                se.owned_stocks.get_quantity(&owner, &build_symbol(symbol)),
+                quantity
+            );
+        }
+
+        assert_eq!(
+            se.owned_stocks.get_quantity(
+                &StockOwner::MarketMaker(market_maker_id),
+                &build_symbol("FOO")
+            ),
+            100
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use super::{
+    company::{Company, CompanyStatus, CompanySymbol, InstrumentKind, ListedCompany, Sector},
+    investor::{Investor, InvestorId},
+    money::{Currency, Money},
+    price::Price,
+    stock::{Stock, StockOwner},
+    stock_exchange::{ExchangeId, StockExchange},
+};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+/// Amount in the default currency
+pub fn build_money(value: f64) -> Money {
+    build_money_in(Default::default(), value)
+}
+
+/// Amount that isn't rounded to the minor unit, so the rounding itself can be
+/// tested
+pub fn build_money_in(currency: Currency, value: f64) -> Money {
+    Money {
+        currency,
+        value: Decimal::from_f64(value).unwrap(),
+    }
+}
+
+pub fn build_symbol(symbol: &str) -> CompanySymbol {
+    CompanySymbol::new(symbol.to_string())
+}
+
+/// Quote without spread
+pub fn build_price(value: Money) -> Price {
+    Price {
+        ask: value,
+        bid: value,
+    }
+}
+
+pub fn build_company(symbol: &CompanySymbol, status: CompanyStatus) -> Company {
+    Company {
+        industry: "Software".to_string(),
+        name: symbol.to_string(),
+        sector: Sector::InformationTechnology,
+        status,
+        status_changes: Vec::new(),
+        symbol: symbol.clone(),
+    }
+}
+
+/// Investor without debt, income nor accounts in other exchanges
+pub fn build_investor(id: InvestorId, cash: Money) -> Investor {
+    let zero = Money::zero(cash.currency);
+
+    Investor {
+        accounts: Default::default(),
+        debt: zero,
+        dob: 0,
+        foreign_cash: Default::default(),
+        id,
+        in_debt_since: None,
+        liquid_cash: cash,
+        monthly_expenses: zero,
+        monthly_income: zero,
+        name: "Foo".to_string(),
+        profile: Default::default(),
+        strategy: Default::default(),
+    }
+}
+
+/// Primary listing of 1000 shares in lots of 10, all of them free float
+pub fn build_listing(symbol: &CompanySymbol, exchange: &ExchangeId) -> ListedCompany {
+    ListedCompany {
+        exchange: exchange.clone(),
+        free_float: 1.0,
+        instrument: InstrumentKind::Stock,
+        lot_size: 10,
+        primary_symbol: None,
+        symbol: symbol.clone(),
+        total_stocks: 1000,
+    }
+}
+
+/// Lists the company at the price, adding the company if it's not there yet
+pub fn add_listing(se: &mut StockExchange, listing: ListedCompany, price: Money) {
+    let company_symbol = listing
+        .primary_symbol
+        .clone()
+        .unwrap_or_else(|| listing.symbol.clone());
+
+    se.companies
+        .mapping
+        .entry(company_symbol.clone())
+        .or_insert_with(|| build_company(&company_symbol, CompanyStatus::Listed));
+    se.prices
+        .0
+        .insert(listing.symbol.clone(), build_price(price));
+    se.listed_companies
+        .mapping
+        .insert(listing.symbol.clone(), listing);
+}
+
+pub fn add_holding(
+    se: &mut StockExchange,
+    owner: StockOwner,
+    symbol: &CompanySymbol,
+    price: Money,
+    quantity: u64,
+) {
+    se.owned_stocks.entry_with_default(&owner).push(Stock {
+        owner,
+        price,
+        quantity,
+        symbol: symbol.clone(),
+    });
+}
@@ -1,28 +1,54 @@
-use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike};
 use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
+#[cfg(test)]
 mod test;
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub enum Timezone {
-    Utc,
-    Hk,
+/// IANA timezone, like "America/New_York", serialized by its name. The
+/// daylight-saving transitions come from the tz database.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Timezone(pub Tz);
+
+impl std::fmt::Display for Timezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.name())
+    }
 }
 
-impl Timezone {
-    pub fn to_string(&self) -> String {
-        match self {
-            Timezone::Utc => "UTC".to_string(),
-            Timezone::Hk => "UTC+08".to_string(),
+impl TryFrom<String> for Timezone {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        // The names of the zones that were supported before, which can be
+        // in the persisted state
+        match name.as_str() {
+            "Utc" => Ok(Timezone(chrono_tz::UTC)),
+            "Hk" => Ok(DEFAULT_TIMEZONE),
+            _ => name
+                .parse::<Tz>()
+                .map(Timezone)
+                .map_err(|_| format!("Unknown timezone {:?}", name)),
         }
     }
+}
+
+impl From<Timezone> for String {
+    fn from(timezone: Timezone) -> Self {
+        timezone.to_string()
+    }
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        DEFAULT_TIMEZONE
+    }
+}
 
+impl Timezone {
     pub fn get_tz(&self) -> Tz {
-        match self {
-            Timezone::Utc => chrono_tz::UTC,
-            Timezone::Hk => chrono_tz::Asia::Hong_Kong,
-        }
+        self.0
     }
 }
 
@@ -32,12 +58,30 @@ pub struct TimeHandler {
     /** How many seconds in real life is one second in the simulation */
     pub secs_factor: u64,
     pub tz: Timezone,
+    /** The loop doesn't tick while paused, except for the requested steps */
+    #[serde(default)]
+    pub paused: bool,
 
     millis_to_wait_millis: u64,
+    /** Real milliseconds run until the last speed change */
+    #[serde(default)]
+    rebase_running_millis: u64,
+    /** Tick of the last speed change */
+    #[serde(default)]
+    rebase_tick: u64,
+    /** Virtual seconds passed until the last speed change */
+    #[serde(default)]
+    rebase_virtual_seconds: f64,
     time: u64,
 }
 
-pub const DEFAULT_TIMEZONE: Timezone = Timezone::Hk;
+#[derive(Debug, PartialEq)]
+pub enum SetSpeedError {
+    InvalidSecsFactor,
+    InvalidWait,
+}
+
+pub const DEFAULT_TIMEZONE: Timezone = Timezone(chrono_tz::Asia::Hong_Kong);
 
 // Pure
 impl TimeHandler {
@@ -47,6 +91,10 @@ impl TimeHandler {
         TimeHandler {
             initial_time,
             millis_to_wait_millis,
+            paused: false,
+            rebase_running_millis: 0,
+            rebase_tick: 0,
+            rebase_virtual_seconds: 0.0,
             secs_factor,
             time: 0,
             tz: DEFAULT_TIMEZONE,
@@ -55,54 +103,82 @@ impl TimeHandler {
 
     pub fn get_n_days_from_now_unix_timestamp(&self, n: u64) -> u64 {
         let now = self.get_now_unix_timestamp();
-        now + n * 24 * 60 * 60
+        self.get_n_days_from_unix_timestamp(now, n)
+    }
+
+    pub fn get_n_days_from_unix_timestamp(&self, timestamp: u64, n: u64) -> u64 {
+        timestamp + n * 24 * 60 * 60
     }
 
+    /// The ticks since the last speed change are at the current speed, so
+    /// the time doesn't jump back when it changes
     pub fn get_now_unix_timestamp(&self) -> u64 {
-        let factor = self.millis_to_wait_millis as f64 / 1000.0;
+        let ticks = (self.time - self.rebase_tick) as f64;
 
-        (factor * (self.time as f64) * (self.secs_factor as f64) + (self.initial_time as f64))
+        (self.get_tick_seconds() * ticks + self.rebase_virtual_seconds + (self.initial_time as f64))
             as u64
     }
 
-    pub fn get_running_seconds(&self) -> u64 {
-        let millis_span = self.time * self.millis_to_wait_millis;
-        let seconds_passed =
-            chrono::Duration::milliseconds(millis_span as i64).num_seconds() as u64;
+    /// Virtual seconds that pass on each tick
+    pub fn get_tick_seconds(&self) -> f64 {
+        self.millis_to_wait_millis as f64 / 1000.0 * self.secs_factor as f64
+    }
+
+    fn get_running_millis(&self) -> u64 {
+        (self.time - self.rebase_tick) * self.millis_to_wait_millis + self.rebase_running_millis
+    }
 
-        seconds_passed
+    pub fn get_running_seconds(&self) -> u64 {
+        let millis_span = self.get_running_millis();
+        chrono::Duration::milliseconds(millis_span as i64).num_seconds() as u64
     }
 
     fn get_virtual_time(&self) -> DateTime<Tz> {
+        self.get_local_time(&self.tz)
+    }
+
+    /// Virtual time in another timezone than the one of the clock, like the
+    /// one of an exchange
+    pub fn get_local_time(&self, timezone: &Timezone) -> DateTime<Tz> {
         let now = self.get_now_unix_timestamp();
-        let tz = &self.tz.get_tz();
 
-        tz.timestamp_opt(now as i64, 0).unwrap()
+        timezone.get_tz().timestamp_opt(now as i64, 0).unwrap()
     }
 
     pub fn get_weekday(&self) -> u32 {
         self.get_virtual_time().weekday().num_days_from_monday()
     }
 
+    /// The calendar dates don't depend on the timezone, and iterating them
+    /// instead of the local midnights avoids the daylight-saving transitions
     pub fn get_year_weekdays(&self, year: &str) -> Vec<String> {
-        let tz = &self.tz.get_tz();
-        let mut weekdays = Vec::new();
         let year_num = year.parse::<i32>().unwrap();
+        let first_day = NaiveDate::from_ymd_opt(year_num, 1, 1).unwrap();
+
+        first_day
+            .iter_days()
+            .take_while(|date| date.year() == year_num)
+            .filter(|date| date.weekday().num_days_from_monday() < 5)
+            .map(|date| format!("{}", date.format("%Y-%m-%d")))
+            .collect()
+    }
 
-        for i in 0..=366 {
-            let date =
-                tz.with_ymd_and_hms(year_num, 1, 1, 0, 0, 0).unwrap() + chrono::Duration::days(i);
-            let weekday = date.weekday().num_days_from_monday();
-            let year = date.year();
-
-            if weekday == 5 || weekday == 6 || year != year_num {
-                continue;
-            }
-
-            weekdays.push(format!("{}", date.format("%Y-%m-%d")));
-        }
+    /// Start of the next day in the timezone of the clock
+    pub fn get_next_midnight_unix_timestamp(&self) -> u64 {
+        let next_day = self.get_virtual_time().date_naive() + chrono::Days::new(1);
+        let midnight = next_day.and_hms_opt(0, 0, 0).unwrap();
+
+        self.tz
+            .get_tz()
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map_or(self.get_n_days_from_now_unix_timestamp(1), |date| {
+                date.timestamp() as u64
+            })
+    }
 
-        weekdays
+    pub fn get_month_day(&self) -> u32 {
+        self.get_virtual_time().day()
     }
 
     pub fn get_day24hour(&self) -> u32 {
@@ -126,7 +202,7 @@ impl TimeHandler {
     }
 
     pub fn get_time_running(&self) -> String {
-        let millis_span = self.time * self.millis_to_wait_millis;
+        let millis_span = self.get_running_millis();
         let seconds_passed =
             chrono::Duration::milliseconds(millis_span as i64).num_seconds() as u64;
 
@@ -162,4 +238,44 @@ impl TimeHandler {
     pub fn tick(&mut self) {
         self.time += 1;
     }
+
+    /// Moves the virtual time forward to the timestamp, keeping the speed of
+    /// the next ticks
+    pub fn jump_to(&mut self, timestamp: u64) {
+        let now = self.get_now_unix_timestamp();
+
+        if timestamp <= now {
+            return;
+        }
+
+        self.rebase_virtual_seconds = timestamp as f64 - self.initial_time as f64;
+        self.rebase_running_millis = self.get_running_millis();
+        self.rebase_tick = self.time;
+    }
+
+    /// Changes the real wait between ticks and the virtual seconds per real
+    /// second from the current tick on
+    pub fn set_speed(
+        &mut self,
+        millis_to_wait_millis: Option<u64>,
+        secs_factor: Option<u64>,
+    ) -> Result<(), SetSpeedError> {
+        // With a zero the virtual time would stop
+        if millis_to_wait_millis == Some(0) {
+            return Err(SetSpeedError::InvalidWait);
+        }
+
+        if secs_factor == Some(0) {
+            return Err(SetSpeedError::InvalidSecsFactor);
+        }
+
+        self.rebase_virtual_seconds =
+            self.get_now_unix_timestamp() as f64 - self.initial_time as f64;
+        self.rebase_running_millis = self.get_running_millis();
+        self.rebase_tick = self.time;
+        self.millis_to_wait_millis = millis_to_wait_millis.unwrap_or(self.millis_to_wait_millis);
+        self.secs_factor = secs_factor.unwrap_or(self.secs_factor);
+
+        Ok(())
+    }
 }
@@ -1,33 +1,73 @@
-#[cfg(test)]
-mod test {
+use crate::core::time::TimeHandler;
+
+impl TimeHandler {
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+    }
+}
+
+mod get_time_running {
     use crate::core::time::TimeHandler;
 
-    impl TimeHandler {
-        pub fn set_time(&mut self, time: u64) {
-            self.time = time;
-        }
+    #[test]
+    fn returns_correct_values() {
+        let mut time_handler = TimeHandler::new(0, None, 100);
+
+        time_handler.millis_to_wait_millis = 1000;
+
+        time_handler.time = 10;
+        assert_eq!(time_handler.get_time_running(), "10s");
+
+        time_handler.time = 30;
+        assert_eq!(time_handler.get_time_running(), "30s");
+
+        time_handler.time = 90;
+        assert_eq!(time_handler.get_time_running(), "1m30s");
+
+        time_handler.time = 3600 + 6 * 60 + 40;
+        assert_eq!(time_handler.get_time_running(), "1h6m40s");
     }
+}
+
+mod timezone {
+    use crate::core::time::{Timezone, DEFAULT_TIMEZONE};
 
-    mod get_time_running {
-        use crate::core::time::TimeHandler;
+    #[test]
+    fn parses_iana_and_previous_names() {
+        let parse = |name: &str| Timezone::try_from(name.to_string());
+
+        assert_eq!(
+            parse("Europe/London"),
+            Ok(Timezone(chrono_tz::Europe::London))
+        );
+        assert_eq!(parse("Hk"), Ok(DEFAULT_TIMEZONE));
+        assert!(parse("Mars/Olympus_Mons").is_err());
+        assert_eq!(String::from(DEFAULT_TIMEZONE), "Asia/Hong_Kong");
+    }
+}
 
-        #[test]
-        fn returns_correct_values() {
-            let mut time_handler = TimeHandler::new(0, None, 100);
+mod set_speed {
+    use crate::core::time::{SetSpeedError, TimeHandler};
 
-            time_handler.millis_to_wait = 1000;
+    #[test]
+    fn keeps_time_monotonic() {
+        let mut time_handler = TimeHandler::new(1000, Some(60), 1000);
 
-            time_handler.time = 10;
-            assert_eq!(time_handler.get_time_running(), "10s");
+        time_handler.time = 10;
+        assert_eq!(time_handler.get_now_unix_timestamp(), 1000 + 600);
 
-            time_handler.time = 30;
-            assert_eq!(time_handler.get_time_running(), "30s");
+        time_handler.set_speed(Some(500), Some(10)).unwrap();
+        assert_eq!(time_handler.get_now_unix_timestamp(), 1000 + 600);
+        assert_eq!(time_handler.get_running_seconds(), 10);
 
-            time_handler.time = 90;
-            assert_eq!(time_handler.get_time_running(), "1m30s");
+        time_handler.tick();
+        time_handler.tick();
+        assert_eq!(time_handler.get_now_unix_timestamp(), 1000 + 600 + 10);
+        assert_eq!(time_handler.get_running_seconds(), 11);
 
-            time_handler.time = 3600 + 6 * 60 + 40;
-            assert_eq!(time_handler.get_time_running(), "1h6m40s");
-        }
+        assert_eq!(
+            time_handler.set_speed(Some(0), None),
+            Err(SetSpeedError::InvalidWait)
+        );
     }
 }
@@ -0,0 +1,229 @@
+use crate::{
+    core::{
+        company::CompanyStatus,
+        stock_exchange::{get_gini, get_percentile, StockExchange},
+        time::{TimeHandler, DEFAULT_TIMEZONE},
+    },
+    server::{storage_wrappers::RedisPriceStorage, DEFAULT_SEED},
+    simulation::{
+        journal::{JournalFile, JournalHeader},
+        settings::SimulationSettings,
+        Simulation,
+    },
+    storage::memory::StorageMemoryImpl,
+};
+use chrono::NaiveDate;
+use log::info;
+use serde_json::{json, Value};
+use std::time::Instant;
+
+pub struct HeadlessOptions {
+    /** Virtual days to simulate */
+    pub days: u64,
+    /** Path of a JSONL journal of the events, to replay the run */
+    pub journal: Option<String>,
+    /** Path of the JSON summary, which is printed without it */
+    pub output: Option<String>,
+    /** Changes the default seed, for batches of runs */
+    pub seed: Option<u64>,
+    /** Local date of the home exchange where the run starts, today by default */
+    pub start: Option<NaiveDate>,
+}
+
+pub fn get_seed(seed: Option<u64>) -> [u8; 32] {
+    let mut full_seed = DEFAULT_SEED;
+
+    if let Some(seed) = seed {
+        full_seed[..8].copy_from_slice(&seed.to_le_bytes());
+    }
+
+    full_seed
+}
+
+fn build_summary(
+    se: &StockExchange,
+    start_time: &TimeHandler,
+    time: &TimeHandler,
+    start_index_level: f64,
+    ticks: u64,
+    real_seconds: f64,
+) -> Value {
+    let mut investors_wealth = se.get_investors_wealth();
+    let gini = get_gini(&investors_wealth);
+
+    investors_wealth.sort_by(|a, b| a.total_cmp(b));
+
+    let companies = [
+        CompanyStatus::Private,
+        CompanyStatus::IpoPending,
+        CompanyStatus::Listed,
+        CompanyStatus::Suspended,
+        CompanyStatus::Delisted,
+    ]
+    .iter()
+    .map(|status| {
+        let count = se
+            .companies
+            .mapping
+            .keys()
+            .filter(|symbol| se.get_company_status(symbol).as_ref() == Some(status))
+            .count();
+
+        (format!("{:?}", status), json!(count))
+    })
+    .collect::<serde_json::Map<_, _>>();
+
+    json!({
+        "companies": companies,
+        "from": start_time.get_virtual_time_formatted(),
+        "funds": se
+            .funds
+            .mapping
+            .values()
+            .map(|fund| fund.get_summary())
+            .collect::<Vec<_>>(),
+        "fx_rates": se.fx_rates.rates,
+        "index": {
+            "end_level": se.index.level,
+            "name": se.index.settings.name,
+            "start_level": start_index_level,
+        },
+        "investors": {
+            "count": se.investors.mapping.len(),
+            "wealth_gini": gini,
+            "wealth_p50": get_percentile(&investors_wealth, 50.0),
+        },
+        "real_seconds": real_seconds,
+        "ticks": ticks,
+        "to": time.get_virtual_time_formatted(),
+    })
+}
+
+/// Runs the simulation without waiting between the ticks, nor the server,
+/// Redis or Prometheus, and writes a summary at the end
+pub fn run_headless(
+    simulation_settings: SimulationSettings,
+    options: HeadlessOptions,
+) -> Result<(), String> {
+    let mut se = StockExchange::new(simulation_settings.exchanges.list.clone());
+    let timezone = se
+        .get_home_exchange()
+        .map_or(DEFAULT_TIMEZONE, |exchange| exchange.settings.timezone);
+    let start = options.start.unwrap_or_else(|| {
+        chrono::Utc::now()
+            .with_timezone(&timezone.get_tz())
+            .date_naive()
+    });
+    let start_timestamp = start
+        .and_hms_opt(0, 0, 0)
+        .and_then(|start| start.and_local_timezone(timezone.get_tz()).earliest())
+        .ok_or_else(|| format!("Invalid start date {}", start))?
+        .timestamp() as u64;
+    let mut time = TimeHandler::new(
+        start_timestamp,
+        None,
+        simulation_settings.time_to_wait_millis,
+    );
+    time.tz = timezone;
+
+    let end_timestamp = time.get_n_days_from_now_unix_timestamp(options.days);
+    let seed = get_seed(options.seed);
+    let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+    let mut simulation = Simulation::new(seed, simulation_settings.clone(), price_storage);
+
+    if let Some(path) = &options.journal {
+        let header = JournalHeader {
+            seed,
+            settings: simulation_settings,
+            time: time.clone(),
+        };
+
+        simulation.start_journal(JournalFile::create(path)?, &header)?;
+    }
+
+    simulation.init(&mut se, &time)?;
+
+    let start_time = time.clone();
+    let start_index_level = se.index.level;
+    let started_at = Instant::now();
+    let mut ticks = 0;
+
+    while time.get_now_unix_timestamp() < end_timestamp {
+        simulation.run(&mut se, &time)?;
+        time.tick();
+        simulation.skip_closed_time(&se, &mut time);
+        ticks += 1;
+    }
+
+    let real_seconds = started_at.elapsed().as_secs_f64();
+
+    info!(
+        "Simulated {} days in {:.1}s, {} ticks",
+        options.days, real_seconds, ticks
+    );
+
+    let summary = build_summary(
+        &se,
+        &start_time,
+        &time,
+        start_index_level,
+        ticks,
+        real_seconds,
+    );
+    let summary =
+        serde_json::to_string_pretty(&summary).map_err(|e| format!("Invalid summary: {}", e))?;
+
+    match options.output {
+        Some(path) => std::fs::write(&path, summary)
+            .map_err(|e| format!("Could not write the summary to {}: {}", path, e))?,
+        None => println!("{}", summary),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_run_headless_writes_the_summary() {
+        // As the command runs it by default
+        let mut settings = SimulationSettings {
+            skip_closed_time: true,
+            ..Default::default()
+        };
+
+        settings.demographics.initial_investors = 100;
+
+        let tick_seconds =
+            TimeHandler::new(0, None, settings.time_to_wait_millis).get_tick_seconds();
+        let output = std::env::temp_dir().join(format!("summary-{}.json", std::process::id()));
+        let options = HeadlessOptions {
+            days: 3,
+            journal: None,
+            output: Some(output.to_string_lossy().to_string()),
+            seed: Some(1),
+            start: NaiveDate::from_ymd_opt(2024, 1, 5),
+        };
+
+        run_headless(settings, options).unwrap();
+
+        let summary: Value =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+
+        std::fs::remove_file(&output).unwrap();
+
+        assert_eq!(summary["from"], "2024-01-05 00:00:00 HKT");
+        assert_eq!(summary["to"], "2024-01-08 00:00:00 HKT");
+
+        // From Friday to Monday, the weekend is skipped, so there are fewer
+        // ticks than in two days
+        let ticks = summary["ticks"].as_u64().unwrap() as f64;
+
+        assert!(ticks > 0.0 && ticks < 2.0 * 86400.0 / tick_seconds);
+        assert!(summary["investors"]["count"].as_u64().unwrap() > 0);
+        assert!(summary["companies"]["Listed"].as_u64().unwrap() > 0);
+        assert!(summary["index"]["end_level"].as_f64().unwrap() > 0.0);
+    }
+}
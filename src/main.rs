@@ -2,11 +2,16 @@
 #![allow(dead_code)]
 
 use cli::{Action, Cli};
+use headless::run_headless;
+use logger::Logger;
+use replay::run_replay;
 use server::run_server;
 
 mod cli;
 mod core;
+mod headless;
 mod logger;
+mod replay;
 mod server;
 mod simulation;
 mod storage;
@@ -17,6 +22,22 @@ async fn main() {
     let action = Cli::parse().await;
 
     match action {
+        Action::Replay(settings, options) => {
+            Logger::new().setup_level(&log::LevelFilter::Info);
+
+            run_replay(settings, options).unwrap_or_else(|e| {
+                eprintln!("Failed to replay the journal: {}", e);
+                std::process::exit(1);
+            });
+        }
+        Action::RunHeadless(settings, options) => {
+            Logger::new().setup_level(&log::LevelFilter::Info);
+
+            run_headless(settings, options).unwrap_or_else(|e| {
+                eprintln!("Failed to run the simulation: {}", e);
+                std::process::exit(1);
+            });
+        }
         Action::StartServer(opts) => {
             run_server(opts).await.unwrap();
         }
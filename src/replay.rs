@@ -0,0 +1,420 @@
+use crate::{
+    core::{
+        journal::{JournalEntry, JournalEvent},
+        stock_exchange::StockExchange,
+        time::TimeHandler,
+    },
+    server::storage_wrappers::{load_journal, RedisPriceStorage},
+    simulation::{
+        journal::{load_journal_file, JournalHeader, JournalVerifier},
+        settings::SimulationSettings,
+        Simulation, SimulationProgress, SimulationState,
+    },
+    storage::memory::StorageMemoryImpl,
+};
+use log::info;
+use serde_json::json;
+use std::time::Instant;
+
+pub struct ReplayOptions {
+    /** JSONL journal of a run, the one of the server in Redis without it */
+    pub journal: Option<String>,
+    /** Path to write the state at the end of the replay, as the server saves it */
+    pub output: Option<String>,
+    /** Sequence number of the checkpoint to stop at, the last one by default */
+    pub to: Option<u64>,
+}
+
+fn is_checkpoint(entry: &JournalEntry) -> bool {
+    matches!(entry.event, JournalEvent::Checkpoint { .. })
+}
+
+/// State of the replay at one of the checkpoints of the journal
+struct Replay {
+    checkpoint: u64,
+    progress: SimulationProgress,
+    se: StockExchange,
+    ticks: usize,
+    time: TimeHandler,
+}
+
+/// What the replay runs again, in the order of the journal
+enum ReplayStep {
+    Resume([u8; 32]),
+    Tick(TimeHandler),
+}
+
+/// Runs the ticks of the journal again from its seed, up to the checkpoint,
+/// checking that every event and state hash matches the recorded ones, so
+/// that the simulation is deterministic. It stops after whole ticks, so any
+/// other sequence is an error.
+fn replay_journal(
+    header: JournalHeader,
+    entries: Vec<JournalEntry>,
+    to: Option<u64>,
+) -> Result<Replay, String> {
+    // The last tick could be incomplete if the simulation stopped in it
+    let last_checkpoint = entries
+        .iter()
+        .rev()
+        .find(|entry| is_checkpoint(entry))
+        .map(|entry| entry.sequence)
+        .ok_or("The journal has no checkpoints")?;
+    let checkpoint = to.unwrap_or(last_checkpoint);
+
+    if checkpoint > last_checkpoint {
+        return Err(format!(
+            "The sequence {} is after the last checkpoint of the journal, {}",
+            checkpoint, last_checkpoint
+        ));
+    }
+
+    if !entries
+        .iter()
+        .any(|entry| entry.sequence == checkpoint && is_checkpoint(entry))
+    {
+        return Err(format!(
+            "The sequence {} is not a checkpoint, the state can only be rebuilt at the end of a tick",
+            checkpoint
+        ));
+    }
+
+    let steps = entries
+        .iter()
+        .take_while(|entry| entry.sequence <= checkpoint)
+        .filter_map(|entry| match &entry.event {
+            JournalEvent::Resumed { seed } => Some(ReplayStep::Resume(*seed)),
+            JournalEvent::TickStarted { time } => Some(ReplayStep::Tick(time.clone())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut ticks = 0;
+
+    let mut se = StockExchange::new(header.settings.exchanges.list.clone());
+    let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+    let mut simulation = Simulation::new(header.seed, header.settings.clone(), price_storage);
+
+    simulation.start_journal(JournalVerifier::new(entries), &header)?;
+    simulation.init(&mut se, &header.time)?;
+
+    let mut time = header.time;
+
+    for step in steps {
+        match step {
+            // As the server after loading the saved state
+            ReplayStep::Resume(seed) => {
+                let progress = simulation.get_progress();
+
+                simulation.resume(&mut se, progress, seed);
+            }
+            ReplayStep::Tick(tick_time) => {
+                simulation.run(&mut se, &tick_time)?;
+                time = tick_time;
+                ticks += 1;
+            }
+        }
+    }
+
+    Ok(Replay {
+        checkpoint,
+        progress: simulation.get_progress(),
+        se,
+        ticks,
+        time,
+    })
+}
+
+/// Replays a journal, from a file or the one of the server, and prints where
+/// it stopped
+pub fn run_replay(
+    simulation_settings: SimulationSettings,
+    options: ReplayOptions,
+) -> Result<(), String> {
+    let (header, entries): (JournalHeader, Vec<JournalEntry>) = match &options.journal {
+        Some(path) => load_journal_file(path)?,
+        None => load_journal(&simulation_settings)?,
+    };
+    let started_at = Instant::now();
+    let Replay {
+        checkpoint,
+        progress,
+        se,
+        ticks,
+        time,
+    } = replay_journal(header, entries, options.to)?;
+
+    info!(
+        "Replayed {} ticks in {:.1}s, the events and states match the journal",
+        ticks,
+        started_at.elapsed().as_secs_f64()
+    );
+
+    let summary = json!({
+        "sequence": checkpoint,
+        "ticks": ticks,
+        "time": time.get_virtual_time_formatted(),
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&summary).map_err(|e| format!("Invalid summary: {}", e))?
+    );
+
+    if let Some(path) = options.output {
+        let state = serde_json::to_string(&SimulationState { progress, se, time })
+            .map_err(|e| format!("Failed to serialize state: {}", e))?;
+
+        std::fs::write(&path, state)
+            .map_err(|e| format!("Could not write the state to {}: {}", path, e))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{journal::get_state_hash, money::Money, stock::StockOwner},
+        simulation::journal::JournalStorage,
+    };
+    use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+    /// Keeps the entries where the test can read them after the run
+    #[derive(Clone, Default)]
+    struct JournalMemory {
+        entries: Rc<RefCell<Vec<JournalEntry>>>,
+    }
+
+    impl JournalStorage for JournalMemory {
+        fn start(&mut self, _header: &JournalHeader) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn resume(&mut self, sequence: u64) -> Result<(), String> {
+            let entries = self.entries.borrow();
+
+            match entries.last() {
+                Some(entry) if entry.sequence == sequence => Ok(()),
+                _ => Err(format!("The journal is not at the sequence {}", sequence)),
+            }
+        }
+
+        fn append(&mut self, entries: &[JournalEntry]) -> Result<(), String> {
+            self.entries.borrow_mut().extend_from_slice(entries);
+
+            Ok(())
+        }
+    }
+
+    fn get_hash(se: &StockExchange) -> u64 {
+        get_state_hash(&serde_json::to_vec(se).unwrap())
+    }
+
+    fn get_checkpoints(entries: &[JournalEntry]) -> Vec<(u64, u64)> {
+        entries
+            .iter()
+            .filter_map(|entry| match entry.event {
+                JournalEvent::Checkpoint { state_hash } => Some((entry.sequence, state_hash)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn get_non_zero<K: Ord, V>(
+        balances: BTreeMap<StockOwner, BTreeMap<K, V>>,
+        is_zero: impl Fn(&V) -> bool,
+    ) -> BTreeMap<StockOwner, BTreeMap<K, V>> {
+        balances
+            .into_iter()
+            .map(|(owner, balances)| {
+                let balances = balances
+                    .into_iter()
+                    .filter(|(_, balance)| !is_zero(balance))
+                    .collect::<BTreeMap<_, _>>();
+
+                (owner, balances)
+            })
+            .filter(|(_, balances)| !balances.is_empty())
+            .collect()
+    }
+
+    /// Records a short run as the headless one does, returning its final
+    /// state. The server can save the state and load it again in the middle.
+    fn record_run(
+        ticks: usize,
+        resume_at: Option<usize>,
+    ) -> (JournalHeader, Vec<JournalEntry>, StockExchange) {
+        let mut settings = SimulationSettings {
+            max_orders_per_tick: 200,
+            skip_closed_time: true,
+            ..Default::default()
+        };
+
+        settings.demographics.initial_investors = 200;
+
+        // 2024-12-23 10:00 HKT, a Monday
+        let mut time = TimeHandler::new(1_734_919_200, None, settings.time_to_wait_millis);
+        let header = JournalHeader {
+            seed: [1; 32],
+            settings: settings.clone(),
+            time: time.clone(),
+        };
+        let mut se = StockExchange::new(settings.exchanges.list.clone());
+        let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+        let mut simulation = Simulation::new(header.seed, settings, price_storage);
+        let journal = JournalMemory::default();
+
+        simulation
+            .start_journal(Box::new(journal.clone()), &header)
+            .unwrap();
+        simulation.init(&mut se, &time).unwrap();
+
+        for tick in 0..ticks {
+            if resume_at == Some(tick) {
+                let state = SimulationState {
+                    progress: simulation.get_progress(),
+                    se,
+                    time,
+                };
+                let state = serde_json::to_string(&state).unwrap();
+                let state: SimulationState = serde_json::from_str(&state).unwrap();
+                let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+
+                se = state.se;
+                time = state.time;
+                simulation = Simulation::new(header.seed, header.settings.clone(), price_storage);
+                simulation.resume(&mut se, state.progress, [2; 32]);
+                simulation
+                    .resume_journal(Box::new(journal.clone()))
+                    .unwrap();
+            }
+
+            simulation.run(&mut se, &time).unwrap();
+            time.tick();
+            simulation.skip_closed_time(&se, &mut time);
+        }
+
+        let entries = journal.entries.borrow().clone();
+
+        (header, entries, se)
+    }
+
+    #[test]
+    fn test_replay_rebuilds_the_recorded_state() {
+        let (header, entries, se) = record_run(20, None);
+        let checkpoints = get_checkpoints(&entries);
+        let (last_sequence, last_hash) = *checkpoints.last().unwrap();
+
+        // One after the initialization and one every 10 ticks, which trade
+        assert_eq!(checkpoints.len(), 3);
+        assert!(entries
+            .iter()
+            .any(|entry| matches!(entry.event, JournalEvent::OrderFilled { .. })));
+        assert_eq!(last_hash, get_hash(&se));
+
+        let replay = replay_journal(header.clone(), entries.clone(), None).unwrap();
+
+        assert_eq!(replay.checkpoint, last_sequence);
+        assert_eq!(replay.ticks, 20);
+        assert_eq!(get_hash(&replay.se), get_hash(&se));
+
+        // Up to the checkpoint of an earlier tick
+        let (sequence, state_hash) = checkpoints[1];
+        let replay = replay_journal(header.clone(), entries.clone(), Some(sequence)).unwrap();
+
+        assert_eq!(replay.ticks, 10);
+        assert_eq!(get_hash(&replay.se), state_hash);
+
+        // The events within a tick can't be replayed on their own
+        assert!(
+            replay_journal(header.clone(), entries.clone(), Some(sequence - 1))
+                .is_err_and(|e| e.contains("not a checkpoint"))
+        );
+        assert!(replay_journal(header, entries, Some(last_sequence + 1)).is_err());
+    }
+
+    #[test]
+    fn test_replay_continues_a_resumed_run() {
+        let (header, entries, se) = record_run(20, Some(5));
+        let (_, uninterrupted_entries, uninterrupted_se) = record_run(20, None);
+        let (last_sequence, last_hash) = *get_checkpoints(&entries).last().unwrap();
+
+        assert_eq!(last_hash, get_hash(&se));
+        // The new seed changes the rest of the run
+        assert_ne!(get_hash(&se), get_hash(&uninterrupted_se));
+        assert!(entries
+            .iter()
+            .zip(uninterrupted_entries.iter())
+            .take_while(|(entry, _)| !matches!(entry.event, JournalEvent::Resumed { .. }))
+            .all(|(entry, uninterrupted)| {
+                serde_json::to_value(entry).unwrap() == serde_json::to_value(uninterrupted).unwrap()
+            }));
+
+        let replay = replay_journal(header, entries, None).unwrap();
+
+        assert_eq!(replay.checkpoint, last_sequence);
+        assert_eq!(replay.ticks, 20);
+        assert_eq!(get_hash(&replay.se), get_hash(&se));
+    }
+
+    #[test]
+    fn test_replay_journals_the_balances() {
+        let (_, entries, se) = record_run(10, None);
+        let mut cash = BTreeMap::new();
+        let mut holdings = BTreeMap::new();
+
+        for entry in entries {
+            match entry.event {
+                JournalEvent::CashChanged {
+                    cash: amount,
+                    owner,
+                } => {
+                    cash.entry(owner)
+                        .or_insert_with(BTreeMap::new)
+                        .insert(amount.currency, amount);
+                }
+                JournalEvent::HoldingChanged {
+                    owner,
+                    shares,
+                    symbol,
+                } => {
+                    holdings
+                        .entry(owner)
+                        .or_insert_with(BTreeMap::new)
+                        .insert(symbol, shares);
+                }
+                _ => {}
+            }
+        }
+
+        // The events add up to the balances at the end
+        let is_zero_cash = |cash: &Money| cash.value.is_zero();
+
+        assert_eq!(
+            get_non_zero(cash, is_zero_cash),
+            get_non_zero(se.get_cash_balances(), is_zero_cash)
+        );
+        assert_eq!(
+            get_non_zero(holdings, |shares| *shares == 0),
+            get_non_zero(se.get_holding_balances(), |shares| *shares == 0)
+        );
+    }
+
+    #[test]
+    fn test_replay_fails_on_a_different_state() {
+        let (header, mut entries, _) = record_run(10, None);
+        let checkpoint = entries
+            .iter_mut()
+            .filter(|entry| is_checkpoint(entry))
+            .nth(1)
+            .unwrap();
+
+        checkpoint.event = JournalEvent::Checkpoint { state_hash: 0 };
+
+        let sequence = checkpoint.sequence;
+
+        assert!(replay_journal(header, entries, None)
+            .is_err_and(|e| e.contains(&format!("sequence {}", sequence))));
+    }
+}
@@ -12,14 +12,35 @@ pub fn build_json_metrics(
     simulation_settings: SimulationSettings,
     se: StockExchange,
 ) -> Result<Value, String> {
-    let current_year = time.get_virtual_year_formatted();
     let default_holidays = BTreeSet::new();
-    let current_year_holidays = se.holidays.get(&current_year).unwrap_or(&default_holidays);
+    let current_year_holidays = se
+        .get_home_exchange()
+        .and_then(|exchange| exchange.holidays.get(&exchange.get_local_year(&time)))
+        .unwrap_or(&default_holidays);
+    let exchanges = se
+        .exchanges
+        .values()
+        .map(|exchange| {
+            json!({
+                "currency": exchange.settings.currency,
+                "id": exchange.settings.id,
+                "is_open": exchange.is_open(&time),
+                "name": exchange.settings.name,
+                "session": exchange.get_session(&time),
+                "timezone": exchange.settings.timezone,
+                "year_holidays": exchange
+                    .holidays
+                    .get(&exchange.get_local_year(&time))
+                    .unwrap_or(&default_holidays),
+            })
+        })
+        .collect::<Vec<_>>();
 
     let response = json!({
         "current_time": time.get_virtual_time_formatted(),
         "year_holidays": current_year_holidays,
-        "currency": se.settings.currency,
+        "currency": se.get_currency(),
+        "exchanges": exchanges,
         "simulation_settings": {
             "flush_storage": simulation_settings.flush_storage,
             "max_duration_seconds": simulation_settings.max_duration_seconds,
@@ -1,32 +1,49 @@
 use crate::{
     core::{
-        stock_exchange::{StockExchange, StockExchangeSettings},
+        candle::CandleInterval,
+        company::CompanySymbol,
+        price::PriceHistoryPage,
+        stock_exchange::{ExchangeId, StockExchange},
         time::{TimeHandler, DEFAULT_TIMEZONE},
     },
     logger::Logger,
-    simulation::{settings::SimulationSettings, Simulation, SimulationState},
+    simulation::{
+        journal::JournalHeader, settings::SimulationSettings, PriceStorage, Simulation,
+        SimulationState,
+    },
     storage::{prometheus::StoragePrometheusImpl, redis::StorageRedisImpl},
 };
-use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use actix_web::{get, post, web, App, HttpResponse, HttpServer, Responder};
+use chrono::NaiveDate;
 use json_metrics::build_json_metrics;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use prometheus_metrics::build_server_prometheus_metrics;
+use serde::Deserialize;
 use std::{
     process,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
     thread,
 };
 use storage_wrappers::{
-    load_simulation_state, save_simulation_state, LoadSimulationStateError, RedisPriceStorage,
+    load_candles, load_simulation_state, save_simulation_state, LoadSimulationStateError,
+    RedisJournal, RedisPriceStorage,
 };
 
 mod json_metrics;
 mod prometheus_metrics;
-mod storage_wrappers;
+pub mod storage_wrappers;
 
+/** Locked before the time when both are held, so the simulation loop and the handlers can't deadlock */
 type SEWrapper = Arc<RwLock<StockExchange>>;
 type TimeWrapper = Arc<RwLock<TimeHandler>>;
 type SimulationSettingsWrapper = Arc<RwLock<SimulationSettings>>;
+/** Ticks to run while the clock is paused */
+type PendingStepsWrapper = Arc<AtomicU64>;
+/** Set by the handlers that change the clock, so the loop saves the state even while paused */
+type SaveRequestWrapper = Arc<AtomicBool>;
 
 impl StockExchange {
     fn get_json(&self) -> serde_json::Value {
@@ -51,17 +68,10 @@ async fn get_prometheus_metrics(
     time: web::Data<TimeWrapper>,
     simulation_settings: web::Data<SimulationSettingsWrapper>,
 ) -> actix_web::Result<HttpResponse> {
-    let (time, se, simulation_settings) = {
-        let time_inner = time.read().unwrap();
-        let se_inner = se.read().unwrap();
-        let simulation_settings = simulation_settings.read().unwrap();
-
-        (
-            time_inner.clone(),
-            se_inner.clone(),
-            simulation_settings.clone(),
-        )
-    };
+    // Each one is copied under its own lock
+    let time = time.read().unwrap().clone();
+    let se = se.read().unwrap().clone();
+    let simulation_settings = simulation_settings.read().unwrap().clone();
 
     build_server_prometheus_metrics(time, se, simulation_settings).map_or_else(
         |e| {
@@ -78,17 +88,10 @@ async fn get_grafana_data(
     time_wrapper: web::Data<TimeWrapper>,
     simulation_settings_wrapper: web::Data<SimulationSettingsWrapper>,
 ) -> actix_web::Result<HttpResponse> {
-    let (time, se, simulation_settings) = {
-        let time_inner = time_wrapper.read().unwrap();
-        let simulation_settings = simulation_settings_wrapper.read().unwrap();
-        let se_inner = se_wrapper.read().unwrap();
-
-        (
-            time_inner.clone(),
-            se_inner.clone(),
-            simulation_settings.clone(),
-        )
-    };
+    // Each one is copied under its own lock
+    let time = time_wrapper.read().unwrap().clone();
+    let se = se_wrapper.read().unwrap().clone();
+    let simulation_settings = simulation_settings_wrapper.read().unwrap().clone();
 
     build_json_metrics(time, simulation_settings, se).map_or_else(
         |e| {
@@ -99,41 +102,378 @@ async fn get_grafana_data(
     )
 }
 
-const DEFAULT_SEED: [u8; 32] = [
+#[get("/companies/delisted")]
+async fn get_delisted_companies(se_wrapper: web::Data<SEWrapper>) -> impl Responder {
+    let delisted_companies = {
+        let se_inner = se_wrapper.read().unwrap();
+
+        se_inner.delisted_companies.clone()
+    };
+
+    HttpResponse::Ok().json(delisted_companies)
+}
+
+#[get("/prices/correlations")]
+async fn get_price_correlations(se_wrapper: web::Data<SEWrapper>) -> impl Responder {
+    let correlation_matrix = {
+        let se_inner = se_wrapper.read().unwrap();
+
+        se_inner.returns.get_correlation_matrix()
+    };
+
+    HttpResponse::Ok().json(correlation_matrix)
+}
+
+#[get("/funds")]
+async fn get_funds(se_wrapper: web::Data<SEWrapper>) -> impl Responder {
+    let funds = {
+        let se_inner = se_wrapper.read().unwrap();
+
+        se_inner
+            .funds
+            .mapping
+            .values()
+            .map(|fund| fund.get_summary())
+            .collect::<Vec<_>>()
+    };
+
+    HttpResponse::Ok().json(funds)
+}
+
+#[get("/fx")]
+async fn get_fx_rates(se_wrapper: web::Data<SEWrapper>) -> impl Responder {
+    let fx_rates = {
+        let se_inner = se_wrapper.read().unwrap();
+
+        se_inner.fx_rates.clone()
+    };
+
+    HttpResponse::Ok().json(fx_rates)
+}
+
+fn get_control_status(
+    time: &TimeHandler,
+    pending_steps: &PendingStepsWrapper,
+) -> serde_json::Value {
+    serde_json::json!({
+        "current_time": time.get_virtual_time_formatted(),
+        "paused": time.paused,
+        "pending_steps": pending_steps.load(Ordering::SeqCst),
+        "secs_factor": time.secs_factor,
+        "time_to_wait_millis": time.get_wait_millis(),
+    })
+}
+
+/// Saves the state as it is, which only the simulation loop does so an older
+/// copy can't overwrite a newer one
+fn save_state(
+    se_wrapper: &SEWrapper,
+    time_wrapper: &TimeWrapper,
+    simulation: &Simulation,
+    redis_storage: &mut StorageRedisImpl,
+) {
+    let se_inner = se_wrapper.read().unwrap();
+    let time_inner = time_wrapper.read().unwrap();
+
+    let simulation_state = SimulationState {
+        progress: simulation.get_progress(),
+        se: se_inner.clone(),
+        time: time_inner.clone(),
+    };
+
+    save_simulation_state(redis_storage, &simulation_state).unwrap_or_else(|e| {
+        error!("Failed to save simulation state: {}", e);
+        std::process::exit(1);
+    });
+}
+
+#[get("/control")]
+async fn get_control(
+    time_wrapper: web::Data<TimeWrapper>,
+    pending_steps: web::Data<PendingStepsWrapper>,
+) -> impl Responder {
+    let time = time_wrapper.read().unwrap().clone();
+
+    HttpResponse::Ok().json(get_control_status(&time, &pending_steps))
+}
+
+#[post("/control/pause")]
+async fn pause_simulation(
+    time_wrapper: web::Data<TimeWrapper>,
+    save_request: web::Data<SaveRequestWrapper>,
+) -> impl Responder {
+    time_wrapper.write().unwrap().paused = true;
+    save_request.store(true, Ordering::SeqCst);
+
+    HttpResponse::Ok().finish()
+}
+
+#[post("/control/resume")]
+async fn resume_simulation(
+    time_wrapper: web::Data<TimeWrapper>,
+    pending_steps: web::Data<PendingStepsWrapper>,
+    save_request: web::Data<SaveRequestWrapper>,
+) -> impl Responder {
+    time_wrapper.write().unwrap().paused = false;
+    pending_steps.store(0, Ordering::SeqCst);
+    save_request.store(true, Ordering::SeqCst);
+
+    HttpResponse::Ok().finish()
+}
+
+// @settings
+const MAX_STEP_TICKS: u64 = 10_000;
+const PAUSED_POLL_MILLIS: u64 = 100;
+
+#[derive(Deserialize)]
+struct StepQuery {
+    ticks: Option<u64>,
+}
+
+/// The steps run as fast as possible, and the state is saved after each one
+#[post("/control/step")]
+async fn step_simulation(
+    query: web::Query<StepQuery>,
+    time_wrapper: web::Data<TimeWrapper>,
+    pending_steps: web::Data<PendingStepsWrapper>,
+) -> impl Responder {
+    let ticks = query.ticks.unwrap_or(1);
+
+    if !time_wrapper.read().unwrap().paused {
+        return HttpResponse::Conflict().body("The simulation is not paused");
+    }
+
+    let added = pending_steps.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |steps| {
+        steps
+            .checked_add(ticks)
+            .filter(|steps| ticks > 0 && *steps <= MAX_STEP_TICKS)
+    });
+
+    if added.is_err() {
+        return HttpResponse::BadRequest().body("Invalid number of ticks");
+    }
+
+    // A resume in between already cleared the steps, or clears them after
+    if !time_wrapper.read().unwrap().paused {
+        let _ = pending_steps.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |steps| {
+            Some(steps.saturating_sub(ticks))
+        });
+
+        return HttpResponse::Conflict().body("The simulation is not paused");
+    }
+
+    let time = time_wrapper.read().unwrap().clone();
+
+    HttpResponse::Ok().json(get_control_status(&time, &pending_steps))
+}
+
+#[derive(Deserialize)]
+struct SpeedQuery {
+    secs_factor: Option<u64>,
+    time_to_wait_millis: Option<u64>,
+}
+
+#[post("/control/speed")]
+async fn set_simulation_speed(
+    query: web::Query<SpeedQuery>,
+    time_wrapper: web::Data<TimeWrapper>,
+    save_request: web::Data<SaveRequestWrapper>,
+) -> impl Responder {
+    let result = time_wrapper
+        .write()
+        .unwrap()
+        .set_speed(query.time_to_wait_millis, query.secs_factor);
+
+    if let Err(e) = result {
+        return HttpResponse::BadRequest().body(format!("{:?}", e));
+    }
+
+    save_request.store(true, Ordering::SeqCst);
+
+    HttpResponse::Ok().finish()
+}
+
+// @settings
+const DEFAULT_CALENDAR_DAYS: u64 = 30;
+const MAX_CALENDAR_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+struct CalendarQuery {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// Holidays and trading days of an exchange, from its current local date by
+/// default
+#[get("/exchanges/{id}/calendar")]
+async fn get_exchange_calendar(
+    path: web::Path<String>,
+    query: web::Query<CalendarQuery>,
+    se_wrapper: web::Data<SEWrapper>,
+    time_wrapper: web::Data<TimeWrapper>,
+) -> actix_web::Result<HttpResponse> {
+    let exchange_id = ExchangeId(path.into_inner());
+    let parse_date = |date: &Option<String>| {
+        date.as_ref()
+            .map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+            .transpose()
+    };
+    let (Ok(from), Ok(to)) = (parse_date(&query.from), parse_date(&query.to)) else {
+        return Ok(HttpResponse::BadRequest().body("The dates must be YYYY-MM-DD"));
+    };
+    let time = time_wrapper.read().unwrap().clone();
+    let se_inner = se_wrapper.read().unwrap();
+
+    let Some(exchange) = se_inner.exchanges.get(&exchange_id) else {
+        return Ok(HttpResponse::NotFound().body("Unknown exchange"));
+    };
+
+    let from = from.unwrap_or_else(|| {
+        time.get_local_time(&exchange.settings.timezone)
+            .date_naive()
+    });
+    let to = to.unwrap_or(from + chrono::Days::new(DEFAULT_CALENDAR_DAYS));
+
+    if from > to {
+        return Ok(HttpResponse::BadRequest().body("`from` is after `to`"));
+    }
+
+    if (to - from).num_days() >= MAX_CALENDAR_DAYS {
+        return Ok(HttpResponse::BadRequest().body("The range is too long"));
+    }
+
+    Ok(HttpResponse::Ok().json(exchange.get_calendar(&from, &to)))
+}
+
+#[derive(Deserialize)]
+struct CandlesQuery {
+    from: Option<u64>,
+    to: Option<u64>,
+}
+
+#[get("/candles/{interval}/{symbol}")]
+async fn get_candles(
+    path: web::Path<(String, String)>,
+    query: web::Query<CandlesQuery>,
+    se_wrapper: web::Data<SEWrapper>,
+    simulation_settings_wrapper: web::Data<SimulationSettingsWrapper>,
+) -> actix_web::Result<HttpResponse> {
+    let (interval, symbol) = path.into_inner();
+    let Ok(interval) = interval.parse::<CandleInterval>() else {
+        return Ok(HttpResponse::BadRequest().body("Invalid interval"));
+    };
+    let symbol = CompanySymbol::new(symbol);
+    let from = query.from.unwrap_or(0);
+    let to = query.to.unwrap_or(u64::MAX);
+
+    let (open_candle, simulation_settings) = {
+        let se_inner = se_wrapper.read().unwrap();
+        let simulation_settings = simulation_settings_wrapper.read().unwrap();
+
+        (
+            se_inner
+                .candles
+                .open_candles
+                .get(&interval)
+                .and_then(|candles| candles.get(&symbol))
+                .cloned(),
+            simulation_settings.clone(),
+        )
+    };
+
+    let mut candles = match load_candles(&simulation_settings, &interval, &symbol, from, to) {
+        Ok(candles) => candles,
+        Err(e) => {
+            error!("Failed to load candles: {}", e);
+            return Ok(HttpResponse::InternalServerError().finish());
+        }
+    };
+
+    // The bar in progress is included so the latest prices are also served
+    if let Some(open_candle) = open_candle.filter(|c| c.start >= from && c.start <= to) {
+        candles.push(open_candle);
+    }
+
+    Ok(HttpResponse::Ok().json(candles))
+}
+
+// @settings
+const DEFAULT_PRICE_HISTORY_LIMIT: usize = 1_000;
+const MAX_PRICE_HISTORY_LIMIT: usize = 10_000;
+
+#[derive(Deserialize)]
+struct PriceHistoryQuery {
+    from: Option<u64>,
+    limit: Option<usize>,
+    step: Option<u64>,
+    to: Option<u64>,
+}
+
+#[get("/prices/history/{symbol}")]
+async fn get_price_history(
+    path: web::Path<String>,
+    query: web::Query<PriceHistoryQuery>,
+    simulation_settings_wrapper: web::Data<SimulationSettingsWrapper>,
+) -> actix_web::Result<HttpResponse> {
+    let symbol = CompanySymbol::new(path.into_inner());
+    let from = query.from.unwrap_or(0);
+    let to = query.to.unwrap_or(u64::MAX);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PRICE_HISTORY_LIMIT)
+        .min(MAX_PRICE_HISTORY_LIMIT);
+
+    if from > to {
+        return Ok(HttpResponse::BadRequest().body("`from` is after `to`"));
+    }
+
+    let simulation_settings = simulation_settings_wrapper.read().unwrap().clone();
+    let mut price_storage = RedisPriceStorage::new(&simulation_settings);
+
+    match price_storage.load_historic_prices(&symbol, from, to) {
+        Ok(samples) => {
+            Ok(HttpResponse::Ok().json(PriceHistoryPage::new(samples, query.step, limit)))
+        }
+        Err(e) => {
+            error!("Failed to load the price history: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+pub const DEFAULT_SEED: [u8; 32] = [
     0x1b, 0x2e, 0x3d, 0x4c, 0x5a, 0x69, 0x78, 0x87, 0x96, 0xa5, 0xb4, 0xc3, 0xd2, 0xe1, 0xf0, 0x0f,
     0x1e, 0x2d, 0x3c, 0x4b, 0x5a, 0x69, 0x78, 0x87, 0x96, 0xa5, 0xb4, 0xc3, 0xd2, 0xe1, 0xf0, 0x0f,
 ];
 
 pub async fn run_server(simulation_settings: SimulationSettings) -> Result<(), String> {
-    let se_settings = StockExchangeSettings {
-        name: "Market Simulator".to_string(),
-        location: "Hong Kong".to_string(),
-        timezone: DEFAULT_TIMEZONE.to_string(),
-        trading_days: vec![0, 1, 2, 3, 4],
-        trading_hours: vec![9, 10, 11, 12, 13, 14, 15],
-        ..Default::default()
-    };
-
     let create_new_state = || {
-        let se = StockExchange::new(se_settings);
+        let se = StockExchange::new(simulation_settings.exchanges.list.clone());
+        // The clock, and so the daily steps and candles, follow the home
+        // exchange
+        let timezone = se
+            .get_home_exchange()
+            .map_or(DEFAULT_TIMEZONE, |exchange| exchange.settings.timezone);
         let beginning_of_today = chrono::Utc::now()
-            .with_timezone(&DEFAULT_TIMEZONE.get_tz())
+            .with_timezone(&timezone.get_tz())
             .date_naive()
             .and_hms_opt(0, 0, 0)
             .unwrap();
         let default_starting_time = beginning_of_today.and_utc().timestamp() as u64;
-        let time = TimeHandler::new(
+        let mut time = TimeHandler::new(
             default_starting_time,
             None,
             simulation_settings.time_to_wait_millis,
         );
+        time.tz = timezone;
 
         (se, time)
     };
     let mut redis_storage: StorageRedisImpl = simulation_settings.clone().into();
     let prometheus_storage: StoragePrometheusImpl = simulation_settings.clone().into();
 
-    let (se, time) = if simulation_settings.flush_storage {
+    // Without the progress of a saved state the simulation starts over
+    let (se, time, progress) = if simulation_settings.flush_storage {
         Simulation::flush_data(&mut redis_storage, &prometheus_storage)
             .await
             .unwrap_or_else(|e| {
@@ -141,30 +481,39 @@ pub async fn run_server(simulation_settings: SimulationSettings) -> Result<(), S
                 std::process::exit(1);
             });
 
-        create_new_state()
-    } else {
-        let simulation_state =
-            load_simulation_state(&simulation_settings).unwrap_or_else(|e| match e {
-                LoadSimulationStateError::Empty => {
-                    debug!("No simulation state found, creating a new one");
-                    let (se, time) = create_new_state();
-                    SimulationState { se, time }
-                }
-                _ => {
-                    error!("Failed to load simulation state");
-                    std::process::exit(1);
-                }
-            });
+        let (se, time) = create_new_state();
 
-        (simulation_state.se, simulation_state.time)
+        (se, time, None)
+    } else {
+        match load_simulation_state(&simulation_settings) {
+            Ok(simulation_state) => (
+                simulation_state.se,
+                simulation_state.time,
+                Some(simulation_state.progress),
+            ),
+            Err(LoadSimulationStateError::Empty) => {
+                debug!("No simulation state found, creating a new one");
+                let (se, time) = create_new_state();
+
+                (se, time, None)
+            }
+            Err(_) => {
+                error!("Failed to load simulation state");
+                std::process::exit(1);
+            }
+        }
     };
 
     let se_wrapper = Arc::new(RwLock::new(se));
     let time_wrapper = Arc::new(RwLock::new(time));
     let simulation_settings_wrapper = Arc::new(RwLock::new(simulation_settings.clone()));
+    let pending_steps_wrapper: PendingStepsWrapper = Arc::new(AtomicU64::new(0));
+    let save_request_wrapper: SaveRequestWrapper = Arc::new(AtomicBool::new(false));
 
     let se_1 = se_wrapper.clone();
     let time_1 = time_wrapper.clone();
+    let pending_steps_1 = pending_steps_wrapper.clone();
+    let save_request_1 = save_request_wrapper.clone();
 
     let sim_settings = simulation_settings.clone();
 
@@ -180,36 +529,69 @@ pub async fn run_server(simulation_settings: SimulationSettings) -> Result<(), S
         let logger = Logger::new();
         logger.setup_level(&log::LevelFilter::Debug);
 
-        println!("{}", simulation_settings.to_string());
+        println!("{}", simulation_settings);
 
         {
             let mut se_inner = se_1.write().unwrap();
             let time_inner = time_1.read().unwrap();
 
-            simulation
-                .init(&mut se_inner, &time_inner)
-                .unwrap_or_else(|e| {
-                    error!("Failed to initialize the simulator: {}", e);
-                    std::process::exit(1);
-                });
+            if let Some(progress) = progress {
+                // The seed is journaled, so a replay continues with it too
+                simulation.resume(&mut se_inner, progress, rand::random());
+                simulation
+                    .resume_journal(RedisJournal::new(&simulation_settings))
+                    .unwrap_or_else(|e| warn!("The events are not journaled: {}", e));
+            } else {
+                let header = JournalHeader {
+                    seed: DEFAULT_SEED,
+                    settings: simulation_settings.clone(),
+                    time: time_inner.clone(),
+                };
+
+                simulation
+                    .start_journal(RedisJournal::new(&simulation_settings), &header)
+                    .unwrap_or_else(|e| warn!("The events are not journaled: {}", e));
+                simulation
+                    .init(&mut se_inner, &time_inner)
+                    .unwrap_or_else(|e| {
+                        error!("Failed to initialize the simulator: {}", e);
+                        std::process::exit(1);
+                    });
+            }
         }
 
         let mut redis_storage: StorageRedisImpl = simulation_settings.clone().into();
 
         loop {
-            if simulation_settings.max_duration_seconds.is_some() {
+            if let Some(max_duration_seconds) = simulation_settings.max_duration_seconds {
                 let time_inner = time_1.read().unwrap();
-                if time_inner.get_running_seconds() as u64
-                    >= simulation_settings.max_duration_seconds.unwrap()
-                {
+                if time_inner.get_running_seconds() >= max_duration_seconds {
                     info!("Simulation reached max duration, stopping...");
                     process::exit(0);
                 }
             }
 
+            // While paused only the requested steps run, without waiting
+            let is_step = time_1.read().unwrap().paused;
+
+            if is_step
+                && pending_steps_1
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |steps| {
+                        steps.checked_sub(1)
+                    })
+                    .is_err()
             {
+                if save_request_1.swap(false, Ordering::SeqCst) {
+                    save_state(&se_1, &time_1, &simulation, &mut redis_storage);
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(PAUSED_POLL_MILLIS));
+                continue;
+            }
+
+            {
+                let time_inner = time_1.read().unwrap().clone();
                 let mut se_inner = se_1.write().unwrap();
-                let time_inner = time_1.read().unwrap();
 
                 simulation
                     .run(&mut se_inner, &time_inner)
@@ -219,24 +601,21 @@ pub async fn run_server(simulation_settings: SimulationSettings) -> Result<(), S
                     });
             }
 
+            // The exchange is locked first, as everywhere else
             {
+                let se_inner = se_1.read().unwrap();
                 let mut time_inner = time_1.write().unwrap();
+
                 time_inner.tick();
+                simulation.skip_closed_time(&se_inner, &mut time_inner);
             }
 
-            {
-                let se_inner = se_1.read().unwrap();
-                let time_inner = time_1.read().unwrap();
-
-                let simulation_state = crate::simulation::SimulationState {
-                    se: se_inner.clone(),
-                    time: time_inner.clone(),
-                };
+            // The saved state includes the changes of the clock until here
+            save_request_1.store(false, Ordering::SeqCst);
+            save_state(&se_1, &time_1, &simulation, &mut redis_storage);
 
-                save_simulation_state(&mut redis_storage, &simulation_state).unwrap_or_else(|e| {
-                    error!("Failed to save simulation state: {}", e);
-                    std::process::exit(1);
-                });
+            if is_step {
+                continue;
             }
 
             let time_to_wait = {
@@ -253,9 +632,23 @@ pub async fn run_server(simulation_settings: SimulationSettings) -> Result<(), S
             .app_data(web::Data::new(se_wrapper.clone()))
             .app_data(web::Data::new(time_wrapper.clone()))
             .app_data(web::Data::new(simulation_settings_wrapper.clone()))
+            .app_data(web::Data::new(pending_steps_wrapper.clone()))
+            .app_data(web::Data::new(save_request_wrapper.clone()))
             .service(get_health)
             .service(get_prometheus_metrics)
             .service(get_grafana_data)
+            .service(get_delisted_companies)
+            .service(get_price_correlations)
+            .service(get_funds)
+            .service(get_fx_rates)
+            .service(get_exchange_calendar)
+            .service(get_candles)
+            .service(get_price_history)
+            .service(get_control)
+            .service(pause_simulation)
+            .service(resume_simulation)
+            .service(step_simulation)
+            .service(set_simulation_speed)
     })
     .bind((
         sim_settings.address.clone(),
@@ -1,13 +1,26 @@
 use std::collections::BTreeMap;
 
 use crate::{
-    core::{stock_exchange::StockExchange, time::TimeHandler},
+    core::{
+        company::{CompanyStatus, IpoStatus},
+        investor::InvestorStrategyKind,
+        money::Currency,
+        session::SessionKind,
+        stock_exchange::{get_gini, get_percentile, StockExchange},
+        time::TimeHandler,
+    },
     simulation::{
         metrics::{
-            METRICS_PREFIX, METRIC_AVERAGE_STOCKS_PER_INVESTOR, METRIC_DAY_HOUR,
-            METRIC_RUNNING_SIMULATION_SECONDS, METRIC_TOTAL_COMPANIES, METRIC_TOTAL_INVESTORS,
+            METRICS_PREFIX, METRIC_AVERAGE_CORRELATION, METRIC_AVERAGE_INVESTOR_AGE,
+            METRIC_AVERAGE_STOCKS_PER_INVESTOR, METRIC_COMPANIES_BY_STATUS, METRIC_DAY_HOUR,
+            METRIC_ETF_INAV, METRIC_ETF_PREMIUM, METRIC_EXCHANGE_SESSION,
+            METRIC_EXCHANGE_TRADING_NOW, METRIC_FUND_NAV, METRIC_FUND_NAV_PER_UNIT, METRIC_FX_RATE,
+            METRIC_INDEX_LEVEL, METRIC_INVESTORS_BY_STRATEGY, METRIC_IPO_FIRST_DAY_RETURN,
+            METRIC_IPO_OVERSUBSCRIPTION, METRIC_LISTING_PREMIUM, METRIC_RUNNING_SIMULATION_SECONDS,
+            METRIC_TOTAL_COMPANIES, METRIC_TOTAL_DELISTED_COMPANIES, METRIC_TOTAL_INVESTORS,
             METRIC_TOTAL_IPOS, METRIC_TOTAL_LISTED_COMPANIES, METRIC_TOTAL_MARKET_MAKERS,
-            METRIC_TOTAL_STOCKS, METRIC_TRADING_NOW, METRIC_WEEKDAY,
+            METRIC_TOTAL_STOCKS, METRIC_TRADING_NOW, METRIC_WEALTH_GINI, METRIC_WEALTH_PERCENTILE,
+            METRIC_WEEKDAY,
         },
         settings::SimulationSettings,
     },
@@ -48,11 +61,27 @@ pub fn build_server_prometheus_metrics(
         exchange.investors.mapping.len() as f64,
     ));
 
+    metrics.push(PrometheusMetric::simple(
+        METRIC_AVERAGE_INVESTOR_AGE,
+        exchange
+            .investors
+            .mapping
+            .values()
+            .map(|investor| investor.get_age(&time))
+            .sum::<f64>()
+            / exchange.investors.mapping.len() as f64,
+    ));
+
     metrics.push(PrometheusMetric::simple(
         METRIC_TOTAL_LISTED_COMPANIES,
         exchange.listed_companies.mapping.len() as f64,
     ));
 
+    metrics.push(PrometheusMetric::simple(
+        METRIC_TOTAL_DELISTED_COMPANIES,
+        exchange.delisted_companies.mapping.len() as f64,
+    ));
+
     metrics.push(PrometheusMetric::simple(
         METRIC_TOTAL_MARKET_MAKERS,
         exchange.market_makers.mapping.len() as f64,
@@ -81,7 +110,7 @@ pub fn build_server_prometheus_metrics(
 
     metrics.push(PrometheusMetric::simple(
         METRIC_TOTAL_IPOS,
-        exchange.ipos.mapping.len() as f64,
+        exchange.ipos.get_pending_count() as f64,
     ));
 
     metrics.push(PrometheusMetric::simple(
@@ -93,15 +122,241 @@ pub fn build_server_prometheus_metrics(
         },
     ));
 
+    let mut investors_wealth = exchange.get_investors_wealth();
+
+    if let Some(gini) = get_gini(&investors_wealth) {
+        metrics.push(PrometheusMetric::simple(METRIC_WEALTH_GINI, gini));
+    }
+
+    investors_wealth.sort_by(|a, b| a.total_cmp(b));
+
+    for status in [
+        CompanyStatus::Private,
+        CompanyStatus::IpoPending,
+        CompanyStatus::Listed,
+        CompanyStatus::Suspended,
+        CompanyStatus::Delisted,
+    ] {
+        let labels: BTreeMap<String, String> =
+            vec![("status".to_string(), format!("{:?}", status))]
+                .into_iter()
+                .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_COMPANIES_BY_STATUS.to_string(),
+            value: exchange.companies.count_with_status(status) as f64,
+            labels,
+        });
+    }
+
+    for percentile in [10, 50, 90, 99] {
+        let Some(wealth) = get_percentile(&investors_wealth, percentile as f64) else {
+            continue;
+        };
+        let labels: BTreeMap<String, String> =
+            vec![("percentile".to_string(), percentile.to_string())]
+                .into_iter()
+                .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_WEALTH_PERCENTILE.to_string(),
+            value: wealth,
+            labels,
+        });
+    }
+
+    for strategy in InvestorStrategyKind::all() {
+        let labels: BTreeMap<String, String> =
+            vec![("strategy".to_string(), format!("{:?}", strategy))]
+                .into_iter()
+                .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_INVESTORS_BY_STRATEGY.to_string(),
+            value: exchange.investors.count_with_strategy(strategy) as f64,
+            labels,
+        });
+    }
+
+    let labels: BTreeMap<String, String> =
+        vec![("name".to_string(), exchange.index.settings.name.clone())]
+            .into_iter()
+            .collect();
+
+    metrics.push(PrometheusMetric {
+        name: METRIC_INDEX_LEVEL.to_string(),
+        value: exchange.index.level,
+        labels,
+    });
+
+    for fund in exchange.funds.mapping.values() {
+        let labels: BTreeMap<String, String> = vec![
+            ("kind".to_string(), format!("{:?}", fund.kind)),
+            ("name".to_string(), fund.name.clone()),
+        ]
+        .into_iter()
+        .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_FUND_NAV.to_string(),
+            value: fund.get_nav(),
+            labels: labels.clone(),
+        });
+        metrics.push(PrometheusMetric {
+            name: METRIC_FUND_NAV_PER_UNIT.to_string(),
+            value: fund.nav_per_unit,
+            labels,
+        });
+    }
+
+    for etf in exchange.etfs.mapping.values() {
+        let labels: BTreeMap<String, String> = vec![("symbol".to_string(), etf.symbol.0.clone())]
+            .into_iter()
+            .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_ETF_INAV.to_string(),
+            value: etf.inav,
+            labels: labels.clone(),
+        });
+
+        if let Some(premium) = exchange
+            .prices
+            .get_average_price(&etf.symbol)
+            .and_then(|price| etf.get_premium(price.to_f64()))
+        {
+            metrics.push(PrometheusMetric {
+                name: METRIC_ETF_PREMIUM.to_string(),
+                value: premium,
+                labels,
+            });
+        }
+    }
+
+    for exchange_item in exchange.exchanges.values() {
+        let labels: BTreeMap<String, String> =
+            vec![("exchange".to_string(), exchange_item.settings.id.0.clone())]
+                .into_iter()
+                .collect();
+        let current_session = exchange_item.get_session(&time);
+
+        // One series per session state, with the current one at 1
+        for session in SessionKind::all().map(Some).into_iter().chain([None]) {
+            let mut labels = labels.clone();
+            let name = session.map_or("Closed".to_string(), |session| format!("{:?}", session));
+
+            labels.insert("session".to_string(), name);
+
+            metrics.push(PrometheusMetric {
+                name: METRIC_EXCHANGE_SESSION.to_string(),
+                value: if session == current_session { 1.0 } else { 0.0 },
+                labels,
+            });
+        }
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_EXCHANGE_TRADING_NOW.to_string(),
+            value: if exchange_item.is_open(&time) {
+                1.0
+            } else {
+                0.0
+            },
+            labels,
+        });
+    }
+
+    for listed in exchange.listed_companies.mapping.values() {
+        let Some(premium) = exchange.get_listing_premium(&listed.symbol) else {
+            continue;
+        };
+        let labels: BTreeMap<String, String> =
+            vec![("symbol".to_string(), listed.symbol.0.clone())]
+                .into_iter()
+                .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_LISTING_PREMIUM.to_string(),
+            value: premium,
+            labels,
+        });
+    }
+
+    // Units of the currency of the home exchange per unit of each currency
+    for currency in Currency::all() {
+        let Some(rate) = exchange
+            .fx_rates
+            .get_rate(&currency, &exchange.get_currency())
+        else {
+            continue;
+        };
+        let labels: BTreeMap<String, String> =
+            vec![("currency".to_string(), currency.get_code().to_string())]
+                .into_iter()
+                .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_FX_RATE.to_string(),
+            value: rate,
+            labels,
+        });
+    }
+
+    let (intra_sector_correlation, inter_sector_correlation) =
+        exchange.get_average_sector_correlations();
+
+    for (scope, correlation) in [
+        ("intra_sector", intra_sector_correlation),
+        ("inter_sector", inter_sector_correlation),
+    ] {
+        let Some(correlation) = correlation else {
+            continue;
+        };
+        let labels: BTreeMap<String, String> = vec![("scope".to_string(), scope.to_string())]
+            .into_iter()
+            .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_AVERAGE_CORRELATION.to_string(),
+            value: correlation,
+            labels,
+        });
+    }
+
+    for ipo in exchange.ipos.mapping.values() {
+        if ipo.status == IpoStatus::Withdrawn {
+            continue;
+        }
+
+        let labels: BTreeMap<String, String> = vec![("symbol".to_string(), ipo.symbol.0.clone())]
+            .into_iter()
+            .collect();
+
+        metrics.push(PrometheusMetric {
+            name: METRIC_IPO_OVERSUBSCRIPTION.to_string(),
+            value: ipo.get_oversubscription_ratio(),
+            labels: labels.clone(),
+        });
+
+        if let Some(first_day_close) = ipo.first_day_close {
+            let offer_price = ipo.offer_price.to_f64();
+
+            metrics.push(PrometheusMetric {
+                name: METRIC_IPO_FIRST_DAY_RETURN.to_string(),
+                value: (first_day_close.to_f64() - offer_price) / offer_price,
+                labels,
+            });
+        }
+    }
+
     for (company_id, price) in exchange.prices.0.iter() {
-        let company = exchange.companies.mapping.get(company_id);
+        let company = exchange.get_company(company_id);
         if company.is_none() {
             continue;
         }
         let company = company.unwrap();
         let labels: BTreeMap<String, String> = vec![
             ("name".to_string(), company.name.clone()),
-            ("symbol".to_string(), company.symbol.0.clone()),
+            ("symbol".to_string(), company_id.0.clone()),
         ]
         .into_iter()
         .collect();
@@ -115,5 +370,5 @@ pub fn build_server_prometheus_metrics(
 
     let metrics_text = prometheus_storage.get_metrics_text(METRICS_PREFIX, &metrics)?;
 
-    return Ok(metrics_text);
+    Ok(metrics_text)
 }
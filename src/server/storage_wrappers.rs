@@ -1,7 +1,19 @@
 use super::SimulationState;
 use crate::{
-    core::{price::Prices, time::TimeHandler},
-    simulation::{settings::SimulationSettings, PriceStorage, SaveHistoricPriceError},
+    core::{
+        candle::{Candle, CandleInterval, ClosedCandle},
+        company::CompanySymbol,
+        fund::Funds,
+        journal::JournalEntry,
+        market_index::MarketIndex,
+        price::{HistoricPrice, Prices},
+        time::TimeHandler,
+    },
+    simulation::{
+        journal::{JournalHeader, JournalStorage},
+        settings::SimulationSettings,
+        LoadHistoricPriceError, PriceStorage, SaveHistoricPriceError,
+    },
     storage::{prometheus::StoragePrometheusImpl, redis::StorageRedisImpl},
     storage_interface::StorageRedis,
 };
@@ -38,8 +50,31 @@ pub fn load_simulation_state(
     Ok(state)
 }
 
-pub struct RedisPriceStorage {
-    redis: StorageRedisImpl,
+fn get_candles_key(interval: &CandleInterval, symbol: &CompanySymbol) -> String {
+    format!("candles:{}:{}", interval, symbol)
+}
+
+pub fn load_candles(
+    settings: &SimulationSettings,
+    interval: &CandleInterval,
+    symbol: &CompanySymbol,
+    from: u64,
+    to: u64,
+) -> Result<Vec<Candle>, String> {
+    let mut redis: StorageRedisImpl = settings.clone().into();
+
+    redis
+        .get_sorted_set_range(&get_candles_key(interval, symbol), from, to)?
+        .iter()
+        .map(|candle_str| {
+            serde_json::from_str(candle_str).map_err(|e| format!("Invalid candle: {}", e))
+        })
+        .collect()
+}
+
+/// Historic prices in Redis, or in another storage with its semantics
+pub struct RedisPriceStorage<R: StorageRedis = StorageRedisImpl> {
+    redis: R,
 }
 
 impl RedisPriceStorage {
@@ -50,7 +85,13 @@ impl RedisPriceStorage {
     }
 }
 
-impl PriceStorage for RedisPriceStorage {
+impl<R: StorageRedis> RedisPriceStorage<R> {
+    pub fn with_storage(redis: R) -> Box<Self> {
+        Box::new(Self { redis })
+    }
+}
+
+impl<R: StorageRedis> PriceStorage for RedisPriceStorage<R> {
     fn save_historic_price(
         &mut self,
         prices: &Prices,
@@ -60,18 +101,191 @@ impl PriceStorage for RedisPriceStorage {
 
         for (symbol, price) in prices.0.iter() {
             let symbol_str = symbol.to_string();
+            let average = price
+                .get_average()
+                .map_err(|e| SaveHistoricPriceError::Unknown(e.to_string()))?;
 
             self.redis
                 .append_sorted_set(
                     &format!("price:{}", symbol_str),
                     time,
-                    &format!("{},{}", time, price.get_average().value),
+                    &format!("{},{}", time, average.value),
+                )
+                .map_err(SaveHistoricPriceError::Unknown)?;
+        }
+
+        Ok(())
+    }
+
+    fn save_historic_index(
+        &mut self,
+        index: &MarketIndex,
+        time: &TimeHandler,
+    ) -> Result<(), SaveHistoricPriceError> {
+        let time = time.get_now_unix_timestamp();
+
+        self.redis
+            .append_sorted_set(
+                &format!("index:{}", index.settings.name),
+                time,
+                &format!("{},{}", time, index.level),
+            )
+            .map_err(SaveHistoricPriceError::Unknown)?;
+
+        Ok(())
+    }
+
+    fn save_historic_navs(
+        &mut self,
+        funds: &Funds,
+        time: &TimeHandler,
+    ) -> Result<(), SaveHistoricPriceError> {
+        let time = time.get_now_unix_timestamp();
+
+        for fund in funds.mapping.values() {
+            self.redis
+                .append_sorted_set(
+                    &format!("fund_nav:{}", fund.name),
+                    time,
+                    &format!("{},{}", time, fund.nav_per_unit),
                 )
                 .map_err(SaveHistoricPriceError::Unknown)?;
         }
 
         Ok(())
     }
+
+    fn load_historic_prices(
+        &mut self,
+        symbol: &CompanySymbol,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<HistoricPrice>, LoadHistoricPriceError> {
+        self.redis
+            .get_sorted_set_range(&format!("price:{}", symbol), from, to)
+            .map_err(LoadHistoricPriceError::Unknown)?
+            .iter()
+            .map(|value| {
+                let (time, price) = value
+                    .split_once(',')
+                    .ok_or_else(|| LoadHistoricPriceError::Invalid(value.clone()))?;
+
+                Ok(HistoricPrice {
+                    price: price
+                        .parse()
+                        .map_err(|_| LoadHistoricPriceError::Invalid(value.clone()))?,
+                    time: time
+                        .parse()
+                        .map_err(|_| LoadHistoricPriceError::Invalid(value.clone()))?,
+                })
+            })
+            .collect()
+    }
+
+    fn save_candles(&mut self, candles: &[ClosedCandle]) -> Result<(), SaveHistoricPriceError> {
+        for closed_candle in candles {
+            let candle_str = serde_json::to_string(&closed_candle.candle)
+                .map_err(|e| SaveHistoricPriceError::Unknown(e.to_string()))?;
+
+            self.redis
+                .append_sorted_set(
+                    &get_candles_key(&closed_candle.interval, &closed_candle.symbol),
+                    closed_candle.candle.start,
+                    &candle_str,
+                )
+                .map_err(SaveHistoricPriceError::Unknown)?;
+        }
+
+        Ok(())
+    }
+}
+
+const JOURNAL_HEADER_KEY: &str = "journal_header";
+const JOURNAL_KEY: &str = "journal";
+
+/// Journal in a sorted set by the sequence numbers, with the header apart
+pub struct RedisJournal<R: StorageRedis = StorageRedisImpl> {
+    redis: R,
+}
+
+impl RedisJournal {
+    pub fn new(settings: &SimulationSettings) -> Box<Self> {
+        let redis: StorageRedisImpl = settings.clone().into();
+
+        Box::new(Self { redis })
+    }
+}
+
+impl<R: StorageRedis> JournalStorage for RedisJournal<R> {
+    fn start(&mut self, header: &JournalHeader) -> Result<(), String> {
+        // The events of another simulation would have the same sequences
+        if !self
+            .redis
+            .get_sorted_set_range(JOURNAL_KEY, 0, u64::MAX)?
+            .is_empty()
+        {
+            return Err("The journal of a previous simulation is in Redis".to_string());
+        }
+
+        let header_str = serde_json::to_string(header)
+            .map_err(|e| format!("Failed to serialize the journal header: {}", e))?;
+
+        self.redis.save_key(JOURNAL_HEADER_KEY, &header_str)
+    }
+
+    fn resume(&mut self, sequence: u64) -> Result<(), String> {
+        self.redis
+            .load_key(JOURNAL_HEADER_KEY)
+            .map_err(|_| "There is no journal to continue in Redis".to_string())?;
+
+        // The simulation could stop after journaling a tick and before
+        // saving its state
+        if !self
+            .redis
+            .get_sorted_set_range(JOURNAL_KEY, sequence + 1, u64::MAX)?
+            .is_empty()
+        {
+            return Err(format!(
+                "The journal in Redis has events after the saved state, at the sequence {}",
+                sequence
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn append(&mut self, entries: &[JournalEntry]) -> Result<(), String> {
+        for entry in entries {
+            let entry_str = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize the journal entry: {}", e))?;
+
+            self.redis
+                .append_sorted_set(JOURNAL_KEY, entry.sequence, &entry_str)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn load_journal(
+    settings: &SimulationSettings,
+) -> Result<(JournalHeader, Vec<JournalEntry>), String> {
+    let mut redis: StorageRedisImpl = settings.clone().into();
+    let header = redis
+        .load_key(JOURNAL_HEADER_KEY)
+        .map_err(|_| "There is no journal in Redis".to_string())
+        .and_then(|header_str| {
+            serde_json::from_str(&header_str).map_err(|e| format!("Invalid journal header: {}", e))
+        })?;
+    let entries = redis
+        .get_sorted_set_range(JOURNAL_KEY, 0, u64::MAX)?
+        .iter()
+        .map(|entry_str| {
+            serde_json::from_str(entry_str).map_err(|e| format!("Invalid journal entry: {}", e))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((header, entries))
 }
 
 impl From<SimulationSettings> for StoragePrometheusImpl {
@@ -88,3 +302,42 @@ impl From<SimulationSettings> for StorageRedisImpl {
         Self::new(&settings.redis_url)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{journal::JournalEvent, time::TimeHandler},
+        storage::memory::StorageMemoryImpl,
+    };
+
+    #[test]
+    fn test_journal_resumes_at_the_last_sequence() {
+        let mut journal = RedisJournal {
+            redis: StorageMemoryImpl::default(),
+        };
+
+        assert!(journal.resume(0).is_err());
+
+        let header = JournalHeader {
+            seed: [0; 32],
+            settings: Default::default(),
+            time: TimeHandler::new(0, None, 1),
+        };
+        let entries = (1..=3)
+            .map(|sequence| JournalEntry {
+                event: JournalEvent::Checkpoint { state_hash: 0 },
+                sequence,
+            })
+            .collect::<Vec<_>>();
+
+        journal.start(&header).unwrap();
+        journal.append(&entries).unwrap();
+
+        assert!(journal.resume(3).is_ok());
+        // The saved state is behind the journal
+        assert!(journal.resume(2).is_err());
+        // A new simulation can't start over it
+        assert!(journal.start(&header).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::investor_profile::Distribution;
+
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// Annual probability of death by age
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MortalityTable {
+    /** `a + b * e^(c * age)` */
+    GompertzMakeham { a: f64, b: f64, c: f64 },
+    /** Rate of each age, applied until the next age of the table */
+    Table { rates: BTreeMap<u64, f64> },
+}
+
+impl Default for MortalityTable {
+    fn default() -> Self {
+        MortalityTable::GompertzMakeham {
+            a: 0.0005,
+            b: 0.00003,
+            c: 0.1,
+        }
+    }
+}
+
+impl MortalityTable {
+    pub fn verify(&self) -> Result<(), String> {
+        match self {
+            MortalityTable::GompertzMakeham { a, b, .. } if *a < 0.0 || *b < 0.0 => {
+                Err("The mortality parameters can't be negative".to_string())
+            }
+            MortalityTable::Table { rates }
+                if rates.is_empty() || rates.values().any(|q| !(0.0..=1.0).contains(q)) =>
+            {
+                Err("The mortality table needs rates between 0 and 1".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get_annual_rate(&self, age: f64) -> f64 {
+        let rate = match self {
+            MortalityTable::GompertzMakeham { a, b, c } => a + b * (c * age).exp(),
+            MortalityTable::Table { rates } => rates
+                .range(..=(age.max(0.0) as u64))
+                .next_back()
+                .or_else(|| rates.iter().next())
+                .map_or(0.0, |(_, rate)| *rate),
+        };
+
+        rate.clamp(0.0, 1.0)
+    }
+
+    pub fn get_daily_rate(&self, age: f64) -> f64 {
+        1.0 - (1.0 - self.get_annual_rate(age)).powf(1.0 / DAYS_PER_YEAR)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DemographicsSettings {
+    /** New investors per year, as a ratio of the current population */
+    pub annual_entry_rate: f64,
+    pub entry_age: Distribution,
+    pub initial_age: Distribution,
+    pub initial_investors: usize,
+    pub mortality: MortalityTable,
+}
+
+impl Default for DemographicsSettings {
+    fn default() -> Self {
+        Self {
+            annual_entry_rate: 0.03,
+            entry_age: Distribution::Normal {
+                mean: 30.0,
+                std_dev: 8.0,
+                min: 18.0,
+                max: 80.0,
+            },
+            initial_age: Distribution::Normal {
+                mean: 45.0,
+                std_dev: 15.0,
+                min: 18.0,
+                max: 95.0,
+            },
+            initial_investors: 1000,
+            mortality: Default::default(),
+        }
+    }
+}
+
+impl DemographicsSettings {
+    pub fn verify(&self) -> Result<(), String> {
+        self.entry_age.verify()?;
+        self.initial_age.verify()?;
+        self.mortality.verify()?;
+
+        if self.annual_entry_rate < 0.0 {
+            return Err("The entry rate can't be negative".to_string());
+        }
+
+        if self.initial_investors == 0 {
+            return Err("At least one initial investor is needed".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Expected number of new investors in one day
+    pub fn get_daily_entries(&self, population: usize) -> f64 {
+        population as f64 * self.annual_entry_rate / DAYS_PER_YEAR
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mortality_rates() {
+        let table = MortalityTable::Table {
+            rates: BTreeMap::from([(18, 0.001), (60, 0.01), (90, 0.2)]),
+        };
+
+        assert_eq!(table.get_annual_rate(10.0), 0.001);
+        assert_eq!(table.get_annual_rate(59.9), 0.001);
+        assert_eq!(table.get_annual_rate(75.0), 0.01);
+        assert_eq!(table.get_annual_rate(99.0), 0.2);
+
+        let daily = table.get_daily_rate(99.0);
+
+        assert!((1.0 - (1.0 - daily).powf(DAYS_PER_YEAR) - 0.2).abs() < 1e-9);
+
+        let gompertz = MortalityTable::default();
+
+        assert!(gompertz.get_annual_rate(80.0) > gompertz.get_annual_rate(40.0));
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{company::Sector, etf::EtfTracking};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EtfSettings {
+    /** ETF shares created or redeemed at once, multiple of the lot size */
+    pub creation_unit: u64,
+    pub lot_size: u64,
+    pub name: String,
+    pub symbol: String,
+    pub tracking: EtfTracking,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EtfsSettings {
+    /** Premium or discount to the iNAV from which the participants arbitrage */
+    pub arbitrage_threshold: f64,
+    pub etfs: Vec<EtfSettings>,
+    /** Standard deviation of the tick returns of the ETFs not explained by the basket */
+    pub tracking_noise: f64,
+}
+
+impl Default for EtfsSettings {
+    fn default() -> Self {
+        Self {
+            arbitrage_threshold: 0.005,
+            etfs: vec![
+                EtfSettings {
+                    creation_unit: 1000,
+                    lot_size: 100,
+                    name: "Market Index ETF".to_string(),
+                    symbol: "IDXETF".to_string(),
+                    tracking: EtfTracking::Index,
+                },
+                EtfSettings {
+                    creation_unit: 1000,
+                    lot_size: 100,
+                    name: "Financials ETF".to_string(),
+                    symbol: "FINETF".to_string(),
+                    tracking: EtfTracking::Sector {
+                        sector: Sector::Financials,
+                    },
+                },
+            ],
+            tracking_noise: 0.002,
+        }
+    }
+}
+
+impl EtfsSettings {
+    pub fn verify(&self) -> Result<(), String> {
+        if self.arbitrage_threshold < 0.0 || self.tracking_noise < 0.0 {
+            return Err(
+                "The ETF arbitrage threshold and tracking noise can't be negative".to_string(),
+            );
+        }
+
+        for etf in self.etfs.iter() {
+            if etf.creation_unit == 0 || !etf.creation_unit.is_multiple_of(etf.lot_size) {
+                return Err(format!(
+                    "The creation unit of {} has to be a multiple of the lot size",
+                    etf.symbol
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use super::{fx::FxSettings, holidays::load_holidays_file, investor_profile::Distribution};
+use crate::core::{
+    money::Currency,
+    session::{verify_sessions, Session, SessionKind},
+    stock_exchange::{ExchangeId, StockExchangeSettings},
+    time::{Timezone, DEFAULT_TIMEZONE},
+};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExchangesSettings {
+    /** Ratio of the cash that an investor exchanges to fund a foreign account */
+    pub account_cash_ratio: Distribution,
+    /** Probability of an investor having an account in each foreign exchange */
+    pub account_probability: f64,
+    /** Premium of a secondary listing to the primary one, after FX, from which the market makers arbitrage */
+    pub arbitrage_threshold: f64,
+    /** Probability of a company having a secondary listing in each foreign exchange */
+    pub dual_listing_probability: f64,
+    /** Shares of a secondary listing, as a ratio of the ones of the primary listing */
+    pub dual_listing_shares_ratio: f64,
+    /** The first one is the home exchange, where the investors, funds and ETFs are */
+    pub list: Vec<StockExchangeSettings>,
+}
+
+impl Default for ExchangesSettings {
+    fn default() -> Self {
+        Self {
+            account_cash_ratio: Distribution::Uniform { min: 0.1, max: 0.3 },
+            account_probability: 0.1,
+            arbitrage_threshold: 0.02,
+            dual_listing_probability: 0.1,
+            dual_listing_shares_ratio: 0.3,
+            list: vec![
+                StockExchangeSettings {
+                    currency: Currency::Hkd,
+                    // Only the morning on the eves of Christmas and the New Year
+                    half_days: ["12-24", "12-31"]
+                        .into_iter()
+                        .map(|date| {
+                            let sessions = vec![
+                                Session::new(SessionKind::PreOpen, (9, 0), (9, 30)),
+                                Session::new(SessionKind::MorningContinuous, (9, 30), (12, 0)),
+                                Session::new(SessionKind::ClosingAuction, (12, 0), (12, 10)),
+                            ];
+
+                            (date.to_string(), sessions)
+                        })
+                        .collect(),
+                    holidays_file: None,
+                    id: ExchangeId("HK".to_string()),
+                    location: "Hong Kong".to_string(),
+                    name: "Market Simulator".to_string(),
+                    sessions: vec![
+                        Session::new(SessionKind::PreOpen, (9, 0), (9, 30)),
+                        Session::new(SessionKind::MorningContinuous, (9, 30), (12, 0)),
+                        Session::new(SessionKind::Lunch, (12, 0), (13, 0)),
+                        Session::new(SessionKind::AfternoonContinuous, (13, 0), (16, 0)),
+                        Session::new(SessionKind::ClosingAuction, (16, 0), (16, 10)),
+                    ],
+                    timezone: DEFAULT_TIMEZONE,
+                    trading_days: vec![0, 1, 2, 3, 4],
+                },
+                StockExchangeSettings {
+                    currency: Currency::Cny,
+                    half_days: Default::default(),
+                    holidays_file: None,
+                    id: ExchangeId("SH".to_string()),
+                    location: "Shanghai".to_string(),
+                    name: "Shanghai Stock Exchange".to_string(),
+                    sessions: vec![
+                        Session::new(SessionKind::PreOpen, (9, 15), (9, 30)),
+                        Session::new(SessionKind::MorningContinuous, (9, 30), (11, 30)),
+                        Session::new(SessionKind::Lunch, (11, 30), (13, 0)),
+                        Session::new(SessionKind::AfternoonContinuous, (13, 0), (14, 57)),
+                        Session::new(SessionKind::ClosingAuction, (14, 57), (15, 0)),
+                    ],
+                    timezone: Timezone(chrono_tz::Asia::Shanghai),
+                    trading_days: vec![0, 1, 2, 3, 4],
+                },
+            ],
+        }
+    }
+}
+
+impl ExchangesSettings {
+    pub fn verify(&self, fx: &FxSettings) -> Result<(), String> {
+        if self.list.is_empty() {
+            return Err("There has to be at least one exchange".to_string());
+        }
+
+        for probability in [self.account_probability, self.dual_listing_probability] {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err("The exchanges probabilities must be between 0 and 1".to_string());
+            }
+        }
+
+        if self.arbitrage_threshold < 0.0 {
+            return Err("The dual listing arbitrage threshold can't be negative".to_string());
+        }
+
+        if self.dual_listing_shares_ratio <= 0.0 || self.dual_listing_shares_ratio > 1.0 {
+            return Err("The dual listing shares ratio must be between 0 and 1".to_string());
+        }
+
+        self.account_cash_ratio.verify()?;
+
+        let mut ids = BTreeSet::new();
+
+        for exchange in self.list.iter() {
+            let id = &exchange.id.0;
+
+            if id.is_empty() || id.contains(|c: char| !c.is_ascii_uppercase()) {
+                return Err(format!("Invalid exchange id {:?}", id));
+            }
+
+            if !ids.insert(id) {
+                return Err(format!("The exchange id {} is repeated", id));
+            }
+
+            if exchange.trading_days.iter().any(|day| *day > 6) {
+                return Err(format!("Invalid trading days for {}", id));
+            }
+
+            verify_sessions(&exchange.sessions).map_err(|e| format!("{}: {}", id, e))?;
+
+            for (date, sessions) in exchange.half_days.iter() {
+                // The yearly dates are checked in a leap year
+                let full_date = if date.len() == 5 {
+                    format!("2000-{}", date)
+                } else {
+                    date.clone()
+                };
+
+                if NaiveDate::parse_from_str(&full_date, "%Y-%m-%d").is_err() {
+                    return Err(format!("Invalid half day {:?} for {}", date, id));
+                }
+
+                verify_sessions(sessions).map_err(|e| format!("{} {}: {}", id, date, e))?;
+            }
+
+            if let Some(path) = &exchange.holidays_file {
+                load_holidays_file(path)?;
+            }
+
+            // The listings are converted between the currencies
+            if exchange.currency != fx.base && !fx.rates.contains_key(&exchange.currency) {
+                return Err(format!(
+                    "There is no FX rate for {}, the currency of {}",
+                    exchange.currency.get_code(),
+                    id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,18 +1,39 @@
+use super::{
+    investor_profile::{Distribution, InvestorProfileSettings},
+    wealth::WealthSettings,
+};
 use crate::core::{
-    company::{Companies, Company, CompanySymbol, Ipo, Ipos, ListedCompanies, ListedCompany},
+    company::{
+        Companies, Company, CompanyStatus, CompanySymbol, InstrumentKind, Ipo, IpoAllotmentMethod,
+        IpoStatus, Ipos, ListedCompanies, ListedCompany, Sector,
+    },
     investor::{Investor, InvestorId, Investors},
     market_maker::{MarketMaker, MarketMakerId, MarketMakers},
-    money::{Currency, Money},
+    money::{Currency, Money, RoundingMode},
+    stock_exchange::ExchangeId,
     time::TimeHandler,
 };
 use fake::{
     faker::{company, name},
     Fake,
 };
-use rand::{rngs::StdRng, Rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 use std::collections::{BTreeMap, BTreeSet};
 
+fn gen_free_float(rng: &mut StdRng) -> f64 {
+    (rng.gen_range(0.2..=1.0_f64) * 100.0).round() / 100.0
+}
+
+/// Rounds the sampled amount to the minor unit of the currency
+fn to_money(value: f64, currency: Currency) -> Money {
+    Money {
+        currency,
+        value: Decimal::from_f64(value).unwrap(),
+    }
+    .round(RoundingMode::Bankers)
+}
+
 impl Companies {
     pub fn gen_list(existing: &Self, n: usize, rng: &mut StdRng) -> Result<Self, String> {
         let mut symbols: BTreeSet<CompanySymbol> = BTreeSet::default();
@@ -49,8 +70,15 @@ impl Companies {
                 }
             };
 
+            let sector = *Sector::all().choose(rng).unwrap();
+            let industry = sector.get_industries().choose(rng).unwrap().to_string();
+
             let company = Company {
+                industry,
                 name,
+                sector,
+                status: CompanyStatus::Private,
+                status_changes: Vec::new(),
                 symbol: symbol.clone(),
             };
 
@@ -76,7 +104,11 @@ impl Companies {
 }
 
 impl ListedCompanies {
-    pub fn gen_list(companies: &Companies, rng: &mut StdRng) -> Result<Self, String> {
+    pub fn gen_list(
+        companies: &Companies,
+        exchange: &ExchangeId,
+        rng: &mut StdRng,
+    ) -> Result<Self, String> {
         let mut list = Vec::with_capacity(companies.mapping.len());
 
         for (_, company) in companies.mapping.iter() {
@@ -84,7 +116,11 @@ impl ListedCompanies {
             let total_stocks = rng.gen_range(10..100) * lot_size;
 
             let company = ListedCompany {
+                exchange: exchange.clone(),
+                free_float: gen_free_float(rng),
+                instrument: InstrumentKind::Stock,
                 lot_size,
+                primary_symbol: None,
                 total_stocks,
                 symbol: company.symbol.clone(),
             };
@@ -108,6 +144,8 @@ impl ListedCompanies {
 impl Ipos {
     pub fn gen_list(
         companies: &Companies,
+        exchange: &ExchangeId,
+        currency: Currency,
         time: &TimeHandler,
         rng: &mut StdRng,
     ) -> Result<Self, String> {
@@ -116,12 +154,31 @@ impl Ipos {
         for (_, company) in companies.mapping.iter() {
             let lot_size = (100.0 / (rng.gen::<f64>() + 1.0)).ceil() as u64;
             let total_stocks = rng.gen_range(10..100) * lot_size;
-            let random_days = rng.gen_range(1..30);
+            let random_days = rng.gen_range(3..30);
+            let offer_price = Money {
+                value: Money::gen_from_range(rng, (1.0, 100.0)),
+                currency,
+            };
+            let allotment_method = if rng.gen_bool(0.5) {
+                IpoAllotmentMethod::Ballot
+            } else {
+                IpoAllotmentMethod::ProRata
+            };
 
             let company = Ipo {
+                allotment_method,
+                free_float: gen_free_float(rng),
                 date: time.get_n_days_from_now_unix_timestamp(random_days),
+                exchange: exchange.clone(),
+                first_day_close: None,
                 lot_size,
+                offer_end: time.get_n_days_from_now_unix_timestamp(random_days - 1),
+                offer_price,
+                offer_start: time.get_now_unix_timestamp(),
+                offered_lots: total_stocks / lot_size,
                 shares: total_stocks,
+                status: IpoStatus::Offering,
+                subscriptions: Vec::new(),
                 symbol: company.symbol.clone(),
             };
 
@@ -137,8 +194,20 @@ impl Ipos {
     }
 }
 
+const MIN_INVESTOR_AGE: f64 = 18.0;
+const MAX_INVESTOR_AGE: f64 = 99.9;
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
 impl Investors {
-    pub fn gen_list(n: usize, time: &TimeHandler, rng: &mut StdRng) -> Result<Self, String> {
+    pub fn gen_list(
+        n: usize,
+        ages: &Distribution,
+        profile_settings: &InvestorProfileSettings,
+        wealth_settings: &WealthSettings,
+        currency: Currency,
+        time: &TimeHandler,
+        rng: &mut StdRng,
+    ) -> Result<Self, String> {
         let mut names: BTreeSet<String> = BTreeSet::default();
         let mut list = Vec::with_capacity(n);
         let mut failures = 0;
@@ -162,25 +231,29 @@ impl Investors {
                 }
             };
 
-            let cash_raw = Decimal::from_f64(rng.gen::<f64>() * 100_000.0)
-                .unwrap()
-                .round_dp(2);
-            let liquid_cash = Money {
-                value: cash_raw,
-                currency: Currency::Hkd,
-            };
-            let dob = rng.gen::<u64>() % 1_000_000_000;
-            let debt = Money {
-                currency: Currency::Hkd,
-                value: Decimal::from_f64(0.0).unwrap().round_dp(2),
-            };
+            let liquid_cash = to_money(wealth_settings.initial_cash.sample(rng), currency);
+            let monthly_income = wealth_settings.monthly_income.sample(rng).max(0.0);
+            let monthly_expenses =
+                monthly_income * wealth_settings.monthly_expenses_ratio.sample(rng).max(0.0);
+            let age = ages.sample(rng).clamp(MIN_INVESTOR_AGE, MAX_INVESTOR_AGE);
+            let dob = time
+                .get_now_unix_timestamp()
+                .saturating_sub((age * SECONDS_PER_YEAR) as u64);
+            let debt = Money::zero(currency);
             last_investor_id = InvestorId::new(&last_investor_id);
             let investor = Investor {
+                accounts: Default::default(),
                 debt,
                 dob,
+                foreign_cash: Default::default(),
                 id: last_investor_id,
+                in_debt_since: None,
                 liquid_cash,
+                monthly_expenses: to_money(monthly_expenses, currency),
+                monthly_income: to_money(monthly_income, currency),
                 name,
+                profile: profile_settings.gen_profile(rng),
+                strategy: Default::default(),
             };
 
             if investor.verify(time).is_ok() {
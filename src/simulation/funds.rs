@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::investor_profile::Distribution;
+use crate::core::{
+    company::Sector,
+    fund::{Fund, FundId, FundKind, FundMandate, Funds},
+    money::{Currency, Money},
+};
+
+// @settings
+const INITIAL_NAV_PER_UNIT: f64 = 10.0;
+
+/// Amounts are in the currency of the exchange
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundSettings {
+    pub initial_capital: f64,
+    pub kind: FundKind,
+    pub mandate: FundMandate,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundsSettings {
+    /** Daily probability that a retail investor subscribes to or redeems from a fund */
+    pub daily_flow_probability: f64,
+    /** Ratio of the cash subscribed, or of the units redeemed, in each flow */
+    pub flow_ratio: Distribution,
+    pub funds: Vec<FundSettings>,
+    /** Largest order of a rebalance, as a ratio of the free float shares */
+    pub max_order_free_float: f64,
+    /** Distance to the target weight, as a ratio of the NAV, that triggers a trade */
+    pub rebalance_threshold: f64,
+}
+
+impl Default for FundsSettings {
+    fn default() -> Self {
+        Self {
+            daily_flow_probability: 0.005,
+            flow_ratio: Distribution::Uniform {
+                min: 0.05,
+                max: 0.3,
+            },
+            funds: vec![
+                FundSettings {
+                    initial_capital: 1_000_000.0,
+                    kind: FundKind::MutualFund,
+                    mandate: FundMandate {
+                        allowed_sectors: vec![],
+                        cash_ratio: 0.05,
+                        follow_benchmark: true,
+                        max_position_ratio: 0.1,
+                        min_market_cap: 0.0,
+                    },
+                    name: "Market Tracker Fund".to_string(),
+                },
+                FundSettings {
+                    initial_capital: 2_000_000.0,
+                    kind: FundKind::PensionFund,
+                    mandate: FundMandate {
+                        allowed_sectors: vec![],
+                        cash_ratio: 0.1,
+                        follow_benchmark: false,
+                        max_position_ratio: 0.05,
+                        min_market_cap: 50_000.0,
+                    },
+                    name: "Public Pension Fund".to_string(),
+                },
+                FundSettings {
+                    initial_capital: 1_000_000.0,
+                    kind: FundKind::Insurer,
+                    mandate: FundMandate {
+                        allowed_sectors: vec![
+                            Sector::ConsumerStaples,
+                            Sector::Financials,
+                            Sector::HealthCare,
+                            Sector::RealEstate,
+                            Sector::Utilities,
+                        ],
+                        cash_ratio: 0.3,
+                        follow_benchmark: true,
+                        max_position_ratio: 0.08,
+                        min_market_cap: 0.0,
+                    },
+                    name: "Mutual Insurance Group".to_string(),
+                },
+            ],
+            max_order_free_float: 0.05,
+            rebalance_threshold: 0.005,
+        }
+    }
+}
+
+impl FundsSettings {
+    pub fn verify(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.daily_flow_probability) {
+            return Err("The daily fund flow probability must be between 0 and 1".to_string());
+        }
+
+        if self.max_order_free_float <= 0.0 || self.rebalance_threshold < 0.0 {
+            return Err("The fund rebalance limits are invalid".to_string());
+        }
+
+        self.flow_ratio.verify()?;
+
+        for fund in self.funds.iter() {
+            if fund.initial_capital <= 0.0 {
+                return Err(format!(
+                    "The capital of the fund {} must be positive",
+                    fund.name
+                ));
+            }
+
+            fund.mandate
+                .verify()
+                .map_err(|e| format!("Invalid mandate for {}: {}", fund.name, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// The funds start with all the capital in cash, owned by the sponsor
+    pub fn build_funds(&self, currency: Currency) -> Funds {
+        let mut last_id = FundId::init();
+        let mut mapping = BTreeMap::new();
+
+        for settings in self.funds.iter() {
+            last_id = FundId::new(&last_id);
+
+            mapping.insert(
+                last_id,
+                Fund {
+                    cash: Money {
+                        currency,
+                        value: Money::from_f64(settings.initial_capital),
+                    },
+                    id: last_id,
+                    kind: settings.kind,
+                    last_valuation: 0,
+                    mandate: settings.mandate.clone(),
+                    name: settings.name.clone(),
+                    nav_per_unit: INITIAL_NAV_PER_UNIT,
+                    unitholders: BTreeMap::new(),
+                    units: settings.initial_capital / INITIAL_NAV_PER_UNIT,
+                },
+            );
+        }
+
+        Funds { last_id, mapping }
+    }
+}
@@ -0,0 +1,94 @@
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::price_model::sample_standard_normal;
+use crate::core::{fx::FxRates, money::Currency};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FxRateSettings {
+    /** Standard deviation of the daily log returns of the rate */
+    pub daily_volatility: f64,
+    /** Value of one unit in the base currency, which the rate reverts to */
+    pub initial: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FxSettings {
+    pub base: Currency,
+    /** Daily fraction of the distance to the initial rate that is closed */
+    pub mean_reversion: f64,
+    pub rates: BTreeMap<Currency, FxRateSettings>,
+}
+
+impl Default for FxSettings {
+    fn default() -> Self {
+        Self {
+            base: Currency::Usd,
+            mean_reversion: 0.02,
+            rates: [
+                (Currency::Cny, 0.138, 0.002),
+                (Currency::Eur, 1.08, 0.005),
+                // Pegged, so it barely moves
+                (Currency::Hkd, 0.128, 0.0003),
+                (Currency::Jpy, 0.0067, 0.006),
+            ]
+            .into_iter()
+            .map(|(currency, initial, daily_volatility)| {
+                (
+                    currency,
+                    FxRateSettings {
+                        daily_volatility,
+                        initial,
+                    },
+                )
+            })
+            .collect(),
+        }
+    }
+}
+
+impl FxSettings {
+    pub fn verify(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.mean_reversion) {
+            return Err("The FX mean reversion must be between 0 and 1".to_string());
+        }
+
+        for (currency, rate) in self.rates.iter() {
+            if rate.initial <= 0.0 || rate.daily_volatility < 0.0 {
+                return Err(format!("Invalid FX rate for {}", currency.get_code()));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn build_rates(&self) -> FxRates {
+        FxRates {
+            base: self.base,
+            last_update: 0,
+            rates: self
+                .rates
+                .iter()
+                .filter(|(currency, _)| **currency != self.base)
+                .map(|(currency, rate)| (*currency, rate.initial))
+                .collect(),
+        }
+    }
+
+    /// Moves every rate one day, as a mean-reverting random walk of its log
+    pub fn update_rates(&self, fx_rates: &mut FxRates, timestamp: u64, rng: &mut StdRng) {
+        for (currency, rate) in fx_rates.rates.iter_mut() {
+            let Some(settings) = self.rates.get(currency) else {
+                continue;
+            };
+            let log_rate = rate.ln();
+            let reversion = self.mean_reversion * (settings.initial.ln() - log_rate);
+            let shock = settings.daily_volatility * sample_standard_normal(rng);
+
+            *rate = (log_rate + reversion + shock).exp();
+        }
+
+        fx_rates.last_update = timestamp;
+    }
+}
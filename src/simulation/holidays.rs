@@ -0,0 +1,132 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HolidayEntry {
+    Date(String),
+    Named { date: String },
+}
+
+fn parse_date(value: &str, format: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, format)
+        .map_err(|e| format!("Invalid holiday date {:?}: {}", value, e))
+}
+
+/// A list of "YYYY-MM-DD" dates, or of objects with a `date` and other
+/// fields like the name of the holiday
+fn parse_json(content: &str) -> Result<Vec<NaiveDate>, String> {
+    let entries = serde_json::from_str::<Vec<HolidayEntry>>(content).map_err(|e| e.to_string())?;
+
+    entries
+        .iter()
+        .map(|entry| match entry {
+            HolidayEntry::Date(date) | HolidayEntry::Named { date } => parse_date(date, "%Y-%m-%d"),
+        })
+        .collect()
+}
+
+/// The events of an iCalendar file. The all-day ones end the day before
+/// their `DTEND`, and the rest are taken by the date of their start.
+fn parse_ics(content: &str) -> Result<Vec<NaiveDate>, String> {
+    // The long lines are folded into the next ones, which start with a space
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+
+    let mut dates = Vec::new();
+    let mut event: Option<(Option<NaiveDate>, Option<NaiveDate>)> = None;
+
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        let is_date = params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME");
+        let get_date = || parse_date(value.get(..8).unwrap_or(value), "%Y%m%d");
+
+        match (name, value, event.as_mut()) {
+            ("BEGIN", "VEVENT", _) => event = Some((None, None)),
+            ("DTSTART", _, Some((start, _))) => *start = Some(get_date()?),
+            ("DTEND", _, Some((_, end))) if is_date => *end = Some(get_date()?),
+            ("END", "VEVENT", Some((start, end))) => {
+                let start = start.ok_or("There is an event without DTSTART")?;
+                let end = end.unwrap_or(start + chrono::Duration::days(1));
+
+                dates.extend(start.iter_days().take_while(|date| *date < end));
+                event = None;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(dates)
+}
+
+/// Holidays of an exchange from an ICS or JSON file, by year
+pub fn load_holidays_file(path: &str) -> Result<BTreeMap<String, BTreeSet<String>>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read the holidays file {}: {}", path, e))?;
+    let dates = if path.to_lowercase().ends_with(".ics") {
+        parse_ics(&content)
+    } else {
+        parse_json(&content)
+    }
+    .map_err(|e| format!("Invalid holidays file {}: {}", path, e))?;
+    let mut holidays: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for date in dates {
+        holidays
+            .entry(date.year().to_string())
+            .or_default()
+            .insert(format!("{}", date.format("%Y-%m-%d")));
+    }
+
+    Ok(holidays)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_calendars() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Lunar New Year\r\n\
+            DTSTART;VALUE=DATE:20240210\r\n\
+            DTEND;VALUE=DATE:20240213\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Christmas\r\n\
+            DTSTART:20241225T000000\r\n\
+            DTEND:20241225T235959\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let format = |dates: Vec<NaiveDate>| {
+            dates
+                .iter()
+                .map(|date| format!("{}", date.format("%Y-%m-%d")))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            format(parse_ics(ics).unwrap()),
+            ["2024-02-10", "2024-02-11", "2024-02-12", "2024-12-25"]
+        );
+        assert_eq!(
+            format(
+                parse_json(r#"["2024-01-01", {"date": "2024-04-04", "name": "Ching Ming"}]"#)
+                    .unwrap()
+            ),
+            ["2024-01-01", "2024-04-04"]
+        );
+        assert!(parse_json(r#"["01/01/2024"]"#).is_err());
+    }
+}
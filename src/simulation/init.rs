@@ -1,7 +1,9 @@
 use super::Simulation;
 use crate::core::{
-    company::{Companies, Ipos, ListedCompanies},
-    investor::{Investor, Investors},
+    company::{Companies, CompanyStatus, CompanySymbol, Ipos, ListedCompanies, ListedCompany},
+    investor::{InvestorId, Investors},
+    journal::JournalEvent,
+    market_index::MarketIndex,
     market_maker::MarketMakers,
     money::{Currency, Money},
     price::Price,
@@ -9,46 +11,189 @@ use crate::core::{
     stock_exchange::StockExchange,
     time::TimeHandler,
 };
+use log::debug;
 use rand::{seq::SliceRandom, Rng};
 
 impl Simulation {
-    pub(super) fn create_valid_new_investor(
+    /// Adds an investor entering the market, with the id after the last one
+    pub(super) fn add_new_investor(
         &mut self,
-        se: &StockExchange,
+        se: &mut StockExchange,
         time: &TimeHandler,
-    ) -> Result<Investor, String> {
-        let new_investor = 'investor_loop: loop {
-            let potential = Investors::gen_list(1, time, &mut self.r)?;
-
-            for investor in potential.mapping.values() {
-                if !se.investors.mapping.contains_key(&investor.id) && investor.verify(time).is_ok()
-                {
-                    break 'investor_loop investor.clone();
+    ) -> Result<InvestorId, String> {
+        let generated = Investors::gen_list(
+            1,
+            &self.settings.demographics.entry_age,
+            &self.settings.investor_profile,
+            &self.settings.wealth,
+            se.get_currency(),
+            time,
+            &mut self.r,
+        )?;
+        let mut new_investor = generated.mapping.into_values().next().unwrap();
+
+        new_investor.id = se.investors.next_id();
+        new_investor.strategy = self.settings.strategy_mix.gen_strategy(&mut self.r);
+
+        let investor_id = new_investor.id;
+
+        se.investors.mapping.insert(investor_id, new_investor);
+        se.record_event(JournalEvent::InvestorAdded { investor_id });
+        self.gen_foreign_cash(se, &investor_id);
+        self.gen_accounts(se, &investor_id);
+
+        Ok(investor_id)
+    }
+
+    /// Some investors exchange part of their initial cash to a random foreign
+    /// currency
+    fn gen_foreign_cash(&mut self, se: &mut StockExchange, investor_id: &InvestorId) {
+        let settings = &self.settings.wealth;
+        let ratio = settings.foreign_cash_ratio.clone();
+
+        if !self.r.gen_bool(settings.foreign_cash_probability) {
+            return;
+        }
+
+        let currencies = Currency::all()
+            .into_iter()
+            .filter(|currency| {
+                currency != &se.get_currency()
+                    && se.fx_rates.get_rate(currency, &se.get_currency()).is_some()
+            })
+            .collect::<Vec<_>>();
+        let Some(currency) = currencies.choose(&mut self.r).cloned() else {
+            return;
+        };
+        let ratio = ratio.sample(&mut self.r).clamp(0.0, 1.0);
+        let cash = se.investors.mapping[investor_id].liquid_cash;
+        let amount = Money {
+            currency: cash.currency,
+            value: Money::from_f64(cash.to_f64() * ratio).min(cash.value),
+        };
+
+        if let Err(e) = se.exchange_investor_cash(investor_id, &amount, &currency) {
+            debug!("Could not exchange the cash of {:?}: {}", investor_id, e);
+        }
+    }
+
+    /// Some investors open accounts in the foreign exchanges, and fund them
+    /// with part of their cash
+    fn gen_accounts(&mut self, se: &mut StockExchange, investor_id: &InvestorId) {
+        let settings = &self.settings.exchanges;
+        let ratio = settings.account_cash_ratio.clone();
+        let home_currency = se.get_currency();
+        let foreign_exchanges = se
+            .exchanges
+            .values()
+            .filter(|exchange| exchange.settings.id != se.home_exchange)
+            .map(|exchange| (exchange.settings.id.clone(), exchange.settings.currency))
+            .collect::<Vec<_>>();
+
+        for (exchange_id, currency) in foreign_exchanges {
+            if !self.r.gen_bool(settings.account_probability) {
+                continue;
+            }
+
+            let ratio = ratio.sample(&mut self.r).clamp(0.0, 1.0);
+            let Some(investor) = se.investors.mapping.get_mut(investor_id) else {
+                return;
+            };
+
+            investor.accounts.insert(exchange_id);
+
+            if currency == home_currency {
+                continue;
+            }
+
+            let cash = investor.liquid_cash;
+            let amount = Money {
+                currency: cash.currency,
+                value: Money::from_f64(cash.to_f64() * ratio).min(cash.value),
+            };
+
+            if let Err(e) = se.exchange_investor_cash(investor_id, &amount, &currency) {
+                debug!("Could not fund the account of {:?}: {}", investor_id, e);
+            }
+        }
+    }
+
+    /// Some companies also list part of their shares in the foreign exchanges
+    /// where there are investors
+    fn gen_secondary_listings(&mut self, se: &mut StockExchange) -> Result<(), String> {
+        let settings = &self.settings.exchanges;
+        let foreign_exchanges = se
+            .exchanges
+            .keys()
+            .filter(|id| **id != se.home_exchange)
+            .filter(|id| {
+                se.investors
+                    .mapping
+                    .values()
+                    .any(|investor| investor.accounts.contains(id))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let primary_listings = se
+            .listed_companies
+            .get_list()
+            .into_iter()
+            .filter(|listed| listed.exchange == se.home_exchange)
+            .collect::<Vec<_>>();
+
+        for exchange_id in foreign_exchanges {
+            for primary in primary_listings.iter() {
+                if !self.r.gen_bool(settings.dual_listing_probability) {
+                    continue;
                 }
+
+                let lots = primary.total_stocks / primary.lot_size;
+                let lots = (lots as f64 * settings.dual_listing_shares_ratio).ceil() as u64;
+                let listed = ListedCompany {
+                    exchange: exchange_id.clone(),
+                    primary_symbol: Some(primary.symbol.clone()),
+                    symbol: primary.symbol.get_secondary(&exchange_id),
+                    total_stocks: lots.max(1) * primary.lot_size,
+                    ..primary.clone()
+                };
+
+                listed
+                    .verify()
+                    .map_err(|e| format!("Invalid listing {}: {:?}", listed.symbol, e))?;
+
+                se.listed_companies
+                    .mapping
+                    .insert(listed.symbol.clone(), listed);
             }
-        };
+        }
 
-        return Ok(new_investor);
+        Ok(())
     }
 
     fn assign_stocks_to_investors(&mut self, se: &mut StockExchange) {
-        let investors_list = se.investors.mapping.values().collect::<Vec<_>>();
-
         for company in &se.listed_companies.get_list() {
+            // Only the investors with an account in the exchange hold it
+            let investors_list = se
+                .investors
+                .mapping
+                .values()
+                .filter(|investor| se.can_investor_trade(investor, &company.symbol))
+                .map(|investor| investor.id)
+                .collect::<Vec<_>>();
+            let currency = se
+                .get_listing_exchange(&company.symbol)
+                .map_or(se.get_currency(), |exchange| exchange.settings.currency);
             let mut remaining_stocks = company.total_stocks;
 
             loop {
-                let investor = investors_list.choose(&mut self.r).unwrap();
+                let investor_id = investors_list.choose(&mut self.r).unwrap();
                 let remaining_lots = remaining_stocks / company.lot_size;
                 let random_lots = self.r.gen_range(1..=remaining_lots);
                 let quantity = random_lots * company.lot_size;
                 let value = Money::gen_from_range(&mut self.r, (1.0, 100.0));
-                let price = Money {
-                    value,
-                    currency: Currency::Hkd,
-                };
+                let price = Money { value, currency };
                 let stock = Stock {
-                    owner: StockOwner::Investor(investor.id),
+                    owner: StockOwner::Investor(*investor_id),
                     price,
                     quantity,
                     symbol: company.symbol.clone(),
@@ -65,11 +210,18 @@ impl Simulation {
         }
     }
 
-    fn calculate_prices(&mut self, se: &mut StockExchange) {
-        for company in &se.listed_companies.get_list() {
+    fn calculate_prices(&mut self, se: &mut StockExchange) -> Result<(), String> {
+        let (secondary_listings, primary_listings): (Vec<_>, Vec<_>) = se
+            .listed_companies
+            .get_list()
+            .into_iter()
+            .partition(|company| company.primary_symbol.is_some());
+
+        for company in &primary_listings {
             let company_stocks_prices = se.owned_stocks.get_prices(&company.symbol);
 
-            let average = Money::calculate_average(&company_stocks_prices);
+            let average = Money::calculate_average(&company_stocks_prices)
+                .map_err(|e| format!("Invalid prices of {}: {}", company.symbol, e))?;
             let price = Price {
                 ask: average,
                 bid: average,
@@ -77,26 +229,191 @@ impl Simulation {
 
             se.prices.0.insert(company.symbol.clone(), price);
         }
+
+        // The secondary listings start at the price of the primary ones
+        for company in &secondary_listings {
+            let primary_symbol = company.primary_symbol.as_ref().unwrap();
+            let currency = se
+                .get_listing_exchange(&company.symbol)
+                .map_or(se.get_currency(), |exchange| exchange.settings.currency);
+            let primary_price = se
+                .prices
+                .get_average_price(primary_symbol)
+                .ok_or(format!("There is no price for {}", primary_symbol))?;
+            let average = se.convert_money(&primary_price, &currency)?;
+
+            se.prices.0.insert(
+                company.symbol.clone(),
+                Price {
+                    ask: average,
+                    bid: average,
+                },
+            );
+        }
+
+        Ok(())
     }
 
     pub fn init(&mut self, se: &mut StockExchange, time: &TimeHandler) -> Result<(), String> {
         let companies = Companies::gen_list(&Default::default(), 100, &mut self.r)?;
         se.companies = companies;
-        se.listed_companies = ListedCompanies::gen_list(&se.companies, &mut self.r)?;
+        se.listed_companies =
+            ListedCompanies::gen_list(&se.companies, &se.home_exchange, &mut self.r)?;
+
+        for symbol in se
+            .listed_companies
+            .mapping
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            se.transition_company(&symbol, CompanyStatus::Listed, time)?;
+        }
 
         let ipos_companies = Companies::gen_list(&se.companies, 10, &mut self.r)?;
         se.companies.mapping.extend(ipos_companies.mapping.clone());
-        se.ipos = Ipos::gen_list(&ipos_companies, time, &mut self.r)?;
+        se.ipos = Ipos::gen_list(
+            &ipos_companies,
+            &se.home_exchange,
+            se.get_currency(),
+            time,
+            &mut self.r,
+        )?;
+
+        for symbol in ipos_companies.mapping.keys() {
+            se.transition_company(symbol, CompanyStatus::IpoPending, time)?;
+        }
+
+        se.fx_rates = self.settings.fx.build_rates();
+        se.investors = Investors::gen_list(
+            self.settings.demographics.initial_investors,
+            &self.settings.demographics.initial_age,
+            &self.settings.investor_profile,
+            &self.settings.wealth,
+            se.get_currency(),
+            time,
+            &mut self.r,
+        )?;
+
+        for investor in se.investors.mapping.values_mut() {
+            investor.strategy = self.settings.strategy_mix.gen_strategy(&mut self.r);
+        }
+
+        for investor_id in se.investors.mapping.keys().cloned().collect::<Vec<_>>() {
+            se.record_event(JournalEvent::InvestorAdded { investor_id });
+            self.gen_foreign_cash(se, &investor_id);
+            self.gen_accounts(se, &investor_id);
+        }
+
+        self.gen_secondary_listings(se)?;
 
-        se.investors = Investors::gen_list(1000, time, &mut self.r)?;
         se.market_makers = MarketMakers::gen_list(10, time, &mut self.r)?;
+        se.funds = self.settings.funds.build_funds(se.get_currency());
 
         self.assign_stocks_to_investors(se);
-        self.calculate_prices(se);
+        self.calculate_prices(se)?;
+
+        se.index = MarketIndex::new(self.settings.index.clone());
+        se.rebalance_index(time);
+
+        for etf in self.settings.etfs.etfs.iter() {
+            se.list_etf(
+                &etf.name,
+                &CompanySymbol::new(etf.symbol.clone()),
+                etf.tracking.clone(),
+                etf.creation_unit,
+                etf.lot_size,
+            )?;
+        }
 
         // TODO:
         // - Create brokers (not all humans)
 
-        Ok(())
+        self.write_journal(se)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{
+            investor::Investor,
+            stock_exchange::ExchangeId,
+            test_fixtures::{
+                add_holding, build_company, build_investor, build_listing, build_money_in,
+            },
+        },
+        server::storage_wrappers::RedisPriceStorage,
+        simulation::settings::SimulationSettings,
+        storage::memory::StorageMemoryImpl,
+    };
+
+    #[test]
+    fn test_secondary_listings_at_the_converted_price() {
+        let mut settings = SimulationSettings::default();
+
+        settings.exchanges.dual_listing_probability = 1.0;
+        settings.exchanges.dual_listing_shares_ratio = 0.3;
+
+        let mut se = StockExchange::new(settings.exchanges.list.clone());
+        let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+        let mut simulation = Simulation::new([0; 32], settings, price_storage);
+        let exchange_id = ExchangeId("SH".to_string());
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let investor_id = InvestorId::new(&InvestorId::init());
+
+        // One yuan is worth two Hong Kong dollars
+        se.fx_rates.base = Currency::Hkd;
+        se.fx_rates.rates.insert(Currency::Cny, 2.0);
+        se.companies.mapping.insert(
+            symbol.clone(),
+            build_company(&symbol, CompanyStatus::Listed),
+        );
+        se.listed_companies
+            .mapping
+            .insert(symbol.clone(), build_listing(&symbol, &se.home_exchange));
+
+        for (price, quantity) in [(8.0, 400), (12.0, 600)] {
+            add_holding(
+                &mut se,
+                StockOwner::Investor(investor_id),
+                &symbol,
+                build_money_in(Currency::Hkd, price),
+                quantity,
+            );
+        }
+
+        // Nobody could trade a listing in SH yet
+        simulation.gen_secondary_listings(&mut se).unwrap();
+
+        assert_eq!(se.listed_companies.mapping.len(), 1);
+
+        se.investors.mapping.insert(
+            investor_id,
+            Investor {
+                accounts: [exchange_id.clone()].into_iter().collect(),
+                ..build_investor(investor_id, build_money_in(Currency::Hkd, 0.0))
+            },
+        );
+        simulation.gen_secondary_listings(&mut se).unwrap();
+
+        let secondary_symbol = symbol.get_secondary(&exchange_id);
+        let secondary = &se.listed_companies.mapping[&secondary_symbol];
+
+        assert_eq!(secondary.exchange, exchange_id);
+        assert_eq!(secondary.primary_symbol, Some(symbol.clone()));
+        assert_eq!(secondary.total_stocks, 300);
+        assert_eq!(se.get_company(&secondary_symbol).unwrap().symbol, symbol);
+
+        simulation.calculate_prices(&mut se).unwrap();
+
+        let primary_price = se.prices.get_average_price(&symbol).unwrap();
+        let secondary_price = se.prices.get_average_price(&secondary_symbol).unwrap();
+
+        assert_eq!(primary_price.value, Money::from_f64(10.0));
+        assert_eq!(secondary_price.currency, Currency::Cny);
+        assert_eq!(secondary_price.value, Money::from_f64(5.0));
+        assert_eq!(se.get_listing_premium(&secondary_symbol), Some(0.0));
     }
 }
@@ -0,0 +1,177 @@
+use crate::core::investor::{InvestorProfile, OrderStyle};
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::price_model::sample_standard_normal;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Distribution {
+    Constant {
+        value: f64,
+    },
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    /** The samples are clamped to the range */
+    Normal {
+        mean: f64,
+        std_dev: f64,
+        min: f64,
+        max: f64,
+    },
+}
+
+impl Distribution {
+    pub fn verify(&self) -> Result<(), String> {
+        match self {
+            Distribution::Constant { .. } => Ok(()),
+            Distribution::Uniform { min, max } | Distribution::Normal { min, max, .. }
+                if min > max =>
+            {
+                Err("The distribution minimum is bigger than the maximum".to_string())
+            }
+            Distribution::Normal { std_dev, .. } if *std_dev < 0.0 => {
+                Err("The distribution standard deviation can't be negative".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut StdRng) -> f64 {
+        match self {
+            Distribution::Constant { value } => *value,
+            Distribution::Uniform { min, max } => {
+                if min == max {
+                    *min
+                } else {
+                    rng.gen_range(*min..*max)
+                }
+            }
+            Distribution::Normal {
+                mean,
+                std_dev,
+                min,
+                max,
+            } => (mean + std_dev * sample_standard_normal(rng)).clamp(*min, *max),
+        }
+    }
+
+    fn sample_in(&self, rng: &mut StdRng, min: f64, max: f64) -> f64 {
+        self.sample(rng).clamp(min, max)
+    }
+}
+
+/// Distributions used to generate the profile of each investor
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InvestorProfileSettings {
+    pub investment_horizon_days: Distribution,
+    pub limit_aggressiveness: Distribution,
+    /** Probability of preferring limit orders over market orders */
+    pub limit_order_probability: f64,
+    pub loss_aversion: Distribution,
+    pub risk_tolerance: Distribution,
+    pub trading_frequency: Distribution,
+}
+
+impl Default for InvestorProfileSettings {
+    fn default() -> Self {
+        Self {
+            investment_horizon_days: Distribution::Uniform {
+                min: 1.0,
+                max: 1000.0,
+            },
+            limit_aggressiveness: Distribution::Uniform { min: 0.0, max: 1.0 },
+            limit_order_probability: 0.5,
+            // Around the estimate from prospect theory
+            loss_aversion: Distribution::Normal {
+                mean: 2.25,
+                std_dev: 0.5,
+                min: 1.0,
+                max: 5.0,
+            },
+            risk_tolerance: Distribution::Uniform { min: 0.1, max: 1.0 },
+            trading_frequency: Distribution::Uniform { min: 0.1, max: 1.0 },
+        }
+    }
+}
+
+impl InvestorProfileSettings {
+    pub fn verify(&self) -> Result<(), String> {
+        self.investment_horizon_days.verify()?;
+        self.limit_aggressiveness.verify()?;
+        self.loss_aversion.verify()?;
+        self.risk_tolerance.verify()?;
+        self.trading_frequency.verify()?;
+
+        if !(0.0..=1.0).contains(&self.limit_order_probability) {
+            return Err("The limit order probability must be between 0 and 1".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn gen_profile(&self, rng: &mut StdRng) -> InvestorProfile {
+        InvestorProfile {
+            investment_horizon_days: self
+                .investment_horizon_days
+                .sample_in(rng, 1.0, f64::MAX)
+                .round() as u64,
+            limit_aggressiveness: self.limit_aggressiveness.sample_in(rng, 0.0, 1.0),
+            loss_aversion: self.loss_aversion.sample_in(rng, 1.0, f64::MAX),
+            order_style: if rng.gen_bool(self.limit_order_probability) {
+                OrderStyle::Limit
+            } else {
+                OrderStyle::Market
+            },
+            risk_tolerance: self.risk_tolerance.sample_in(rng, 0.0, 1.0),
+            trading_frequency: self.trading_frequency.sample_in(rng, 0.0, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_gen_profile() {
+        let settings = InvestorProfileSettings::default();
+        let gen_profiles = |seed| {
+            let mut rng = StdRng::from_seed([seed; 32]);
+
+            (0..10)
+                .map(|_| format!("{:?}", settings.gen_profile(&mut rng)))
+                .collect::<Vec<_>>()
+        };
+
+        // The same seed generates the same profiles
+        assert_eq!(gen_profiles(0), gen_profiles(0));
+        assert_ne!(gen_profiles(0), gen_profiles(1));
+
+        // The samples are kept in the range of each field
+        let settings = InvestorProfileSettings {
+            investment_horizon_days: Distribution::Constant { value: 0.2 },
+            limit_aggressiveness: Distribution::Uniform { min: 2.0, max: 3.0 },
+            limit_order_probability: 1.0,
+            loss_aversion: Distribution::Normal {
+                mean: 0.0,
+                std_dev: 1.0,
+                min: -1.0,
+                max: 0.5,
+            },
+            risk_tolerance: Distribution::Constant { value: -1.0 },
+            trading_frequency: Distribution::Constant { value: 0.3 },
+        };
+        let profile = settings.gen_profile(&mut StdRng::from_seed([0; 32]));
+
+        assert_eq!(profile.investment_horizon_days, 1);
+        assert_eq!(profile.limit_aggressiveness, 1.0);
+        assert_eq!(profile.loss_aversion, 1.0);
+        assert_eq!(profile.order_style, OrderStyle::Limit);
+        assert_eq!(profile.risk_tolerance, 0.0);
+        assert_eq!(profile.trading_frequency, 0.3);
+    }
+}
@@ -0,0 +1,334 @@
+use super::{settings::SimulationSettings, Simulation};
+use crate::core::{
+    company::CompanySymbol,
+    journal::{get_state_hash, JournalEntry, JournalEvent},
+    money::{Currency, Money},
+    stock::StockOwner,
+    stock_exchange::StockExchange,
+    time::TimeHandler,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+/// Ticks between the checkpoints, since hashing the whole state costs more
+/// than a tick
+// @settings
+const CHECKPOINT_TICKS: u64 = 10;
+
+/// What a replay starts from, before the first event
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JournalHeader {
+    pub seed: [u8; 32],
+    pub settings: SimulationSettings,
+    pub time: TimeHandler,
+}
+
+/// Cash and holdings of the owners, to journal only the ones that change
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct JournalBalances {
+    pub cash: BTreeMap<StockOwner, BTreeMap<Currency, Money>>,
+    pub holdings: BTreeMap<StockOwner, BTreeMap<CompanySymbol, u64>>,
+}
+
+impl JournalBalances {
+    pub fn new(se: &StockExchange) -> Self {
+        JournalBalances {
+            cash: se.get_cash_balances(),
+            holdings: se.get_holding_balances(),
+        }
+    }
+
+    /// Events of the balances that differ from the previous ones, where the
+    /// missing ones count as zero
+    pub fn get_changes(&self, previous: &JournalBalances) -> Vec<JournalEvent> {
+        let cash = get_balance_changes(&previous.cash, &self.cash, |currency| {
+            Money::zero(*currency)
+        })
+        .into_iter()
+        .map(|(owner, _, cash)| JournalEvent::CashChanged { cash, owner });
+        let holdings = get_balance_changes(&previous.holdings, &self.holdings, |_| 0)
+            .into_iter()
+            .map(|(owner, symbol, shares)| JournalEvent::HoldingChanged {
+                owner,
+                shares,
+                symbol,
+            });
+
+        cash.chain(holdings).collect()
+    }
+}
+
+fn get_balance_changes<K: Ord + Clone, V: PartialEq + Clone>(
+    previous: &BTreeMap<StockOwner, BTreeMap<K, V>>,
+    current: &BTreeMap<StockOwner, BTreeMap<K, V>>,
+    get_zero: impl Fn(&K) -> V,
+) -> Vec<(StockOwner, K, V)> {
+    let empty = BTreeMap::new();
+    let owners = previous
+        .keys()
+        .chain(current.keys())
+        .collect::<BTreeSet<_>>();
+    let mut changes = vec![];
+
+    for owner in owners {
+        let previous = previous.get(owner).unwrap_or(&empty);
+        let current = current.get(owner).unwrap_or(&empty);
+
+        for key in previous
+            .keys()
+            .chain(current.keys())
+            .collect::<BTreeSet<_>>()
+        {
+            let get_value = |balances: &BTreeMap<K, V>| {
+                balances.get(key).cloned().unwrap_or_else(|| get_zero(key))
+            };
+            let value = get_value(current);
+
+            if value != get_value(previous) {
+                changes.push((*owner, key.clone(), value));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Append-only log of the events, by their sequence number
+pub trait JournalStorage {
+    fn start(&mut self, header: &JournalHeader) -> Result<(), String>;
+
+    /// Continues the journal of a saved state, which is at the sequence
+    fn resume(&mut self, sequence: u64) -> Result<(), String>;
+
+    fn append(&mut self, entries: &[JournalEntry]) -> Result<(), String>;
+}
+
+/// JSON lines, with the header in the first one
+pub struct JournalFile {
+    path: String,
+    writer: BufWriter<File>,
+}
+
+impl JournalFile {
+    pub fn create(path: &str) -> Result<Box<Self>, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Could not create the journal {}: {}", path, e))?;
+
+        Ok(Box::new(JournalFile {
+            path: path.to_string(),
+            writer: BufWriter::new(file),
+        }))
+    }
+
+    fn write_line(&mut self, value: &impl Serialize) -> Result<(), String> {
+        serde_json::to_writer(&mut self.writer, value)
+            .map_err(|e| e.to_string())
+            .and_then(|_| writeln!(self.writer).map_err(|e| e.to_string()))
+            .map_err(|e| format!("Could not write to the journal {}: {}", self.path, e))
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("Could not write to the journal {}: {}", self.path, e))
+    }
+}
+
+impl JournalStorage for JournalFile {
+    fn start(&mut self, header: &JournalHeader) -> Result<(), String> {
+        self.write_line(header)?;
+        self.flush()
+    }
+
+    fn resume(&mut self, _sequence: u64) -> Result<(), String> {
+        Err(format!("The journal {} can't be continued", self.path))
+    }
+
+    fn append(&mut self, entries: &[JournalEntry]) -> Result<(), String> {
+        for entry in entries {
+            self.write_line(entry)?;
+        }
+
+        self.flush()
+    }
+}
+
+pub fn load_journal_file(path: &str) -> Result<(JournalHeader, Vec<JournalEntry>), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read the journal {}: {}", path, e))?;
+    let mut lines = content.lines();
+    let header = lines
+        .next()
+        .ok_or_else(|| format!("The journal {} is empty", path))
+        .and_then(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("Invalid header of the journal {}: {}", path, e))
+        })?;
+    let lines = lines.collect::<Vec<_>>();
+    let mut entries = Vec::with_capacity(lines.len());
+
+    for (index, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            // The simulation could stop in the middle of a line
+            Err(e) if index == lines.len() - 1 => {
+                warn!("Ignoring the incomplete last line of the journal: {}", e)
+            }
+            Err(e) => {
+                return Err(format!(
+                    "Invalid line {} of the journal {}: {}",
+                    index + 2,
+                    path,
+                    e
+                ))
+            }
+        }
+    }
+
+    Ok((header, entries))
+}
+
+/// Compares the events of a replay with the recorded ones, instead of
+/// storing them, and fails on the first difference
+pub struct JournalVerifier {
+    entries: Vec<JournalEntry>,
+    position: usize,
+}
+
+impl JournalVerifier {
+    pub fn new(entries: Vec<JournalEntry>) -> Box<Self> {
+        Box::new(JournalVerifier {
+            entries,
+            position: 0,
+        })
+    }
+}
+
+impl JournalStorage for JournalVerifier {
+    fn start(&mut self, _header: &JournalHeader) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn resume(&mut self, _sequence: u64) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn append(&mut self, entries: &[JournalEntry]) -> Result<(), String> {
+        for entry in entries {
+            let Some(expected) = self.entries.get(self.position) else {
+                return Err(format!(
+                    "The replay has more events than the journal, from the sequence {}",
+                    entry.sequence
+                ));
+            };
+            let to_value = |entry: &JournalEntry| {
+                serde_json::to_value(entry).map_err(|e| format!("Invalid event: {}", e))
+            };
+            let (expected_value, value) = (to_value(expected)?, to_value(entry)?);
+
+            if expected_value != value {
+                return Err(format!(
+                    "The replay diverged from the journal at the sequence {}, expected {} but got {}",
+                    expected.sequence, expected_value, value
+                ));
+            }
+
+            self.position += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl Simulation {
+    /// Records the events from now on, which must be from the start of the
+    /// simulation to replay them
+    pub fn start_journal(
+        &mut self,
+        mut journal: Box<dyn JournalStorage>,
+        header: &JournalHeader,
+    ) -> Result<(), String> {
+        journal.start(header)?;
+        self.journal = Some(journal);
+
+        Ok(())
+    }
+
+    /// Records the events of a resumed simulation after the ones of its saved
+    /// state
+    pub fn resume_journal(&mut self, mut journal: Box<dyn JournalStorage>) -> Result<(), String> {
+        journal.resume(self.progress.journal_sequence)?;
+        self.journal = Some(journal);
+
+        Ok(())
+    }
+
+    /// Appends the events since the last call, the cash and holdings that
+    /// changed, and a checkpoint with the hash of the state of the exchange
+    /// after the initialization and every few ticks
+    pub(super) fn write_journal(&mut self, se: &mut StockExchange) -> Result<(), String> {
+        let events = std::mem::take(&mut se.journal_events);
+        let Some(journal) = self.journal.as_mut() else {
+            return Ok(());
+        };
+        let balances = JournalBalances::new(se);
+        let balance_changes = balances.get_changes(&self.progress.journal_balances);
+
+        self.progress.journal_balances = balances;
+
+        let checkpoint = if self.progress.journal_ticks.is_multiple_of(CHECKPOINT_TICKS) {
+            let state = serde_json::to_vec(se).map_err(|e| format!("Invalid state: {}", e))?;
+
+            Some(JournalEvent::Checkpoint {
+                state_hash: get_state_hash(&state),
+            })
+        } else {
+            None
+        };
+
+        self.progress.journal_ticks += 1;
+
+        let entries = events
+            .into_iter()
+            .chain(balance_changes)
+            .chain(checkpoint)
+            .map(|event| {
+                self.progress.journal_sequence += 1;
+
+                JournalEntry {
+                    event,
+                    sequence: self.progress.journal_sequence,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        journal.append(&entries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verifier_fails_on_divergence() {
+        let entry = |state_hash, sequence| JournalEntry {
+            event: JournalEvent::Checkpoint { state_hash },
+            sequence,
+        };
+        let mut verifier = JournalVerifier::new(vec![entry(10, 1), entry(20, 2)]);
+
+        assert!(verifier.append(&[entry(10, 1)]).is_ok());
+        assert!(verifier
+            .append(&[entry(21, 2)])
+            .unwrap_err()
+            .contains("sequence 2"));
+        assert!(verifier.append(&[entry(20, 2)]).is_ok());
+        assert!(verifier.append(&[entry(30, 3)]).is_err());
+    }
+}
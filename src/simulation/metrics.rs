@@ -1,13 +1,31 @@
 pub const METRICS_PREFIX: &str = "market_sim";
 
+pub const METRIC_AVERAGE_INVESTOR_AGE: &str = "average_investor_age";
 pub const METRIC_AVERAGE_STOCKS_PER_INVESTOR: &str = "average_stocks_per_investor";
+pub const METRIC_COMPANIES_BY_STATUS: &str = "companies_by_status_count";
+pub const METRIC_AVERAGE_CORRELATION: &str = "average_correlation";
 pub const METRIC_DAY_HOUR: &str = "time_day_hour";
+pub const METRIC_INVESTORS_BY_STRATEGY: &str = "investors_by_strategy_count";
+pub const METRIC_ETF_INAV: &str = "etf_inav";
+pub const METRIC_ETF_PREMIUM: &str = "etf_premium";
+pub const METRIC_EXCHANGE_SESSION: &str = "exchange_session";
+pub const METRIC_EXCHANGE_TRADING_NOW: &str = "exchange_trading_now";
+pub const METRIC_FUND_NAV: &str = "fund_nav";
+pub const METRIC_FUND_NAV_PER_UNIT: &str = "fund_nav_per_unit";
+pub const METRIC_FX_RATE: &str = "fx_rate";
+pub const METRIC_INDEX_LEVEL: &str = "index_level";
+pub const METRIC_IPO_FIRST_DAY_RETURN: &str = "ipo_first_day_return";
+pub const METRIC_IPO_OVERSUBSCRIPTION: &str = "ipo_oversubscription_ratio";
+pub const METRIC_LISTING_PREMIUM: &str = "listing_premium";
 pub const METRIC_RUNNING_SIMULATION_SECONDS: &str = "running_simulation_seconds";
 pub const METRIC_TOTAL_COMPANIES: &str = "companies_count";
+pub const METRIC_TOTAL_DELISTED_COMPANIES: &str = "delisted_companies_count";
 pub const METRIC_TOTAL_INVESTORS: &str = "investors_count";
 pub const METRIC_TOTAL_IPOS: &str = "ipos_count";
 pub const METRIC_TOTAL_LISTED_COMPANIES: &str = "listed_companies_count";
 pub const METRIC_TOTAL_MARKET_MAKERS: &str = "market_makers_count";
 pub const METRIC_TOTAL_STOCKS: &str = "stocks_count";
 pub const METRIC_TRADING_NOW: &str = "trading_now";
+pub const METRIC_WEALTH_GINI: &str = "wealth_gini";
+pub const METRIC_WEALTH_PERCENTILE: &str = "wealth_percentile";
 pub const METRIC_WEEKDAY: &str = "time_weekday";
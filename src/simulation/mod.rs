@@ -1,16 +1,41 @@
 use crate::{
-    core::{price::Prices, stock_exchange::StockExchange, time::TimeHandler},
+    core::{
+        candle::ClosedCandle,
+        company::CompanySymbol,
+        fund::Funds,
+        investor::InvestorStrategyKind,
+        journal::JournalEvent,
+        market_index::MarketIndex,
+        price::{HistoricPrice, Prices},
+        stock_exchange::StockExchange,
+        time::TimeHandler,
+    },
     storage_interface::{StoragePrometheus, StorageRedis},
 };
+use journal::{JournalBalances, JournalStorage};
+use price_model::{PriceModel, PriceModelState};
 use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use settings::SimulationSettings;
+use std::collections::BTreeMap;
+use strategy::InvestorStrategy;
 
+pub mod demographics;
+pub mod etfs;
+pub mod exchanges;
 mod fake_data;
+pub mod funds;
+pub mod fx;
+mod holidays;
 mod init;
+pub mod investor_profile;
+pub mod journal;
 pub mod metrics;
+pub mod price_model;
 mod run;
 pub mod settings;
+pub mod strategy;
+pub mod wealth;
 
 #[derive(Debug)]
 pub enum SaveHistoricPriceError {
@@ -18,19 +43,68 @@ pub enum SaveHistoricPriceError {
     Unknown(String),
 }
 
+#[derive(Debug)]
+pub enum LoadHistoricPriceError {
+    Invalid(String),
+    Unknown(String),
+}
+
 pub trait PriceStorage {
     fn save_historic_price(
         &mut self,
         prices: &Prices,
         time_handler: &TimeHandler,
     ) -> Result<(), SaveHistoricPriceError>;
+
+    fn save_historic_index(
+        &mut self,
+        index: &MarketIndex,
+        time_handler: &TimeHandler,
+    ) -> Result<(), SaveHistoricPriceError>;
+
+    fn save_candles(&mut self, candles: &[ClosedCandle]) -> Result<(), SaveHistoricPriceError>;
+
+    /// NAV per unit of every fund at its last valuation
+    fn save_historic_navs(
+        &mut self,
+        funds: &Funds,
+        time_handler: &TimeHandler,
+    ) -> Result<(), SaveHistoricPriceError>;
+
+    /// Prices of the symbol between the two timestamps, both included
+    fn load_historic_prices(
+        &mut self,
+        symbol: &CompanySymbol,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<HistoricPrice>, LoadHistoricPriceError>;
+}
+
+/// What the simulation carries from one tick to the next besides the
+/// exchange, which a resumed run restores to continue where it was
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SimulationProgress {
+    pub daily_checks: Option<String>,
+    pub funds_rebalance_day: Option<String>,
+    /** Last cash and holdings written to the journal */
+    pub journal_balances: JournalBalances,
+    pub journal_sequence: u64,
+    /** Writes to the journal, the initialization included */
+    pub journal_ticks: u64,
+    pub last_prices_update: Option<u64>,
+    /** Deviation of each price caused by the orders imbalance, as a log return */
+    pub price_impacts: BTreeMap<CompanySymbol, f64>,
+    /** Of the models that keep a state, the rest start over the same */
+    pub price_models: BTreeMap<CompanySymbol, PriceModelState>,
 }
 
 pub struct Simulation {
+    journal: Option<Box<dyn JournalStorage>>,
+    progress: SimulationProgress,
     r: StdRng,
+    price_models: BTreeMap<CompanySymbol, Box<dyn PriceModel>>,
     price_storage: Box<dyn PriceStorage>,
-
-    daily_checks: Option<String>,
+    strategies: BTreeMap<InvestorStrategyKind, Box<dyn InvestorStrategy>>,
 
     pub settings: SimulationSettings,
 }
@@ -44,13 +118,49 @@ impl Simulation {
         let r = StdRng::from_seed(seed);
 
         Simulation {
-            daily_checks: None,
+            journal: None,
+            progress: SimulationProgress::default(),
+            price_models: BTreeMap::new(),
             price_storage,
             r,
+            strategies: BTreeMap::new(),
             settings,
         }
     }
 
+    pub fn get_progress(&self) -> SimulationProgress {
+        let price_models = self
+            .price_models
+            .iter()
+            .filter_map(|(symbol, model)| Some((symbol.clone(), model.get_state()?)))
+            .collect();
+
+        SimulationProgress {
+            price_models,
+            ..self.progress.clone()
+        }
+    }
+
+    /// Continues from the progress of a saved state. The random numbers
+    /// can't continue, so they start from the new seed, which is journaled.
+    pub fn resume(&mut self, se: &mut StockExchange, progress: SimulationProgress, seed: [u8; 32]) {
+        self.price_models = progress
+            .price_models
+            .iter()
+            .map(|(symbol, state)| {
+                let mut model = self.settings.get_price_model(symbol).build();
+
+                model.set_state(state);
+
+                (symbol.clone(), model)
+            })
+            .collect();
+        self.progress = progress;
+        self.r = StdRng::from_seed(seed);
+
+        se.record_event(JournalEvent::Resumed { seed });
+    }
+
     pub async fn flush_data(
         redis_storage: &mut dyn StorageRedis,
         prometheus_storage: &impl StoragePrometheus,
@@ -66,6 +176,9 @@ impl Simulation {
 pub struct SimulationState {
     pub time: TimeHandler,
     pub se: StockExchange,
+    /** Missing in the states saved before it, which start it over */
+    #[serde(default)]
+    pub progress: SimulationProgress,
 }
 
 mod test {
@@ -83,6 +196,38 @@ mod test {
         ) -> Result<(), SaveHistoricPriceError> {
             Ok(())
         }
+
+        fn save_historic_index(
+            &mut self,
+            _index: &MarketIndex,
+            _time_handler: &TimeHandler,
+        ) -> Result<(), SaveHistoricPriceError> {
+            Ok(())
+        }
+
+        fn save_candles(
+            &mut self,
+            _candles: &[ClosedCandle],
+        ) -> Result<(), SaveHistoricPriceError> {
+            Ok(())
+        }
+
+        fn save_historic_navs(
+            &mut self,
+            _funds: &Funds,
+            _time_handler: &TimeHandler,
+        ) -> Result<(), SaveHistoricPriceError> {
+            Ok(())
+        }
+
+        fn load_historic_prices(
+            &mut self,
+            _symbol: &CompanySymbol,
+            _from: u64,
+            _to: u64,
+        ) -> Result<Vec<HistoricPrice>, LoadHistoricPriceError> {
+            Ok(vec![])
+        }
     }
 
     #[test]
@@ -0,0 +1,65 @@
+use super::sample_standard_normal;
+use crate::core::company::Sector;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How much of the shock of each symbol comes from the market and from its
+/// sector. The rest is idiosyncratic, so the shock keeps a unit variance.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FactorLoadings {
+    pub market: f64,
+    pub sector: f64,
+}
+
+impl Default for FactorLoadings {
+    fn default() -> Self {
+        Self {
+            market: 0.5,
+            sector: 0.4,
+        }
+    }
+}
+
+impl FactorLoadings {
+    pub fn verify(&self) -> Result<(), String> {
+        if self.market < 0.0 || self.sector < 0.0 {
+            return Err("The factor loadings can't be negative".to_string());
+        }
+
+        if self.market.powi(2) + self.sector.powi(2) > 1.0 {
+            return Err("The squared factor loadings can't add up to more than 1".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn get_idiosyncratic(&self) -> f64 {
+        (1.0 - self.market.powi(2) - self.sector.powi(2)).sqrt()
+    }
+}
+
+pub struct FactorShocks {
+    market: f64,
+    sectors: BTreeMap<Sector, f64>,
+}
+
+impl FactorShocks {
+    pub fn gen(rng: &mut StdRng) -> Self {
+        let market = sample_standard_normal(rng);
+        let sectors = Sector::all()
+            .into_iter()
+            .map(|sector| (sector, sample_standard_normal(rng)))
+            .collect();
+
+        Self { market, sectors }
+    }
+
+    pub fn get_shock(&self, loadings: &FactorLoadings, sector: &Sector, rng: &mut StdRng) -> f64 {
+        let sector_shock = self.sectors.get(sector).copied().unwrap_or(0.0);
+
+        loadings.market * self.market
+            + loadings.sector * sector_shock
+            + loadings.get_idiosyncratic() * sample_standard_normal(rng)
+    }
+}
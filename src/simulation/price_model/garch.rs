@@ -0,0 +1,64 @@
+use super::{PriceModel, PriceModelState};
+use rand::rngs::StdRng;
+
+/// GARCH(1,1) on the log returns of each step, so big moves tend to be
+/// followed by big moves. The variance reverts to `long_run_volatility`.
+pub struct Garch {
+    pub drift: f64,
+    pub long_run_volatility: f64,
+    pub alpha: f64,
+    pub beta: f64,
+
+    last_shock: f64,
+    variance: Option<f64>,
+}
+
+impl Garch {
+    pub fn new(drift: f64, long_run_volatility: f64, alpha: f64, beta: f64) -> Self {
+        Self {
+            alpha,
+            beta,
+            drift,
+            last_shock: 0.0,
+            long_run_volatility,
+            variance: None,
+        }
+    }
+}
+
+impl PriceModel for Garch {
+    fn next_price(&mut self, price: f64, dt: f64, shock: f64, _rng: &mut StdRng) -> f64 {
+        let long_run_variance = self.long_run_volatility.powi(2) * dt;
+        let omega = (1.0 - self.alpha - self.beta) * long_run_variance;
+
+        let variance = match self.variance {
+            None => long_run_variance,
+            Some(variance) => omega + self.alpha * self.last_shock.powi(2) + self.beta * variance,
+        };
+
+        let scaled_shock = variance.sqrt() * shock;
+        let log_return = (self.drift * dt - 0.5 * variance) + scaled_shock;
+
+        self.variance = Some(variance);
+        self.last_shock = scaled_shock;
+
+        price * log_return.exp()
+    }
+
+    fn get_state(&self) -> Option<PriceModelState> {
+        Some(PriceModelState::Garch {
+            last_shock: self.last_shock,
+            variance: self.variance,
+        })
+    }
+
+    fn set_state(&mut self, state: &PriceModelState) {
+        let PriceModelState::Garch {
+            last_shock,
+            variance,
+        } = *state;
+
+        self.last_shock = last_shock;
+        self.variance = variance;
+    }
+}
@@ -0,0 +1,16 @@
+use super::PriceModel;
+use rand::rngs::StdRng;
+
+pub struct GeometricBrownianMotion {
+    pub drift: f64,
+    pub volatility: f64,
+}
+
+impl PriceModel for GeometricBrownianMotion {
+    fn next_price(&mut self, price: f64, dt: f64, shock: f64, _rng: &mut StdRng) -> f64 {
+        let log_return =
+            (self.drift - 0.5 * self.volatility.powi(2)) * dt + self.volatility * dt.sqrt() * shock;
+
+        price * log_return.exp()
+    }
+}
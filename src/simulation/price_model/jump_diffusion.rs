@@ -0,0 +1,30 @@
+use super::{sample_poisson, sample_standard_normal, PriceModel};
+use rand::rngs::StdRng;
+
+/// Merton jump diffusion: a GBM plus log-normal jumps arriving as a Poisson
+/// process. The drift is compensated so `drift` is still the expected return.
+pub struct JumpDiffusion {
+    pub drift: f64,
+    pub volatility: f64,
+    pub jump_intensity: f64,
+    pub jump_mean: f64,
+    pub jump_volatility: f64,
+}
+
+impl PriceModel for JumpDiffusion {
+    fn next_price(&mut self, price: f64, dt: f64, shock: f64, rng: &mut StdRng) -> f64 {
+        let expected_jump = (self.jump_mean + 0.5 * self.jump_volatility.powi(2)).exp() - 1.0;
+        let compensated_drift = self.drift - self.jump_intensity * expected_jump;
+
+        let mut log_return = (compensated_drift - 0.5 * self.volatility.powi(2)) * dt
+            + self.volatility * dt.sqrt() * shock;
+
+        let jumps = sample_poisson(self.jump_intensity * dt, rng);
+
+        for _ in 0..jumps {
+            log_return += self.jump_mean + self.jump_volatility * sample_standard_normal(rng);
+        }
+
+        price * log_return.exp()
+    }
+}
@@ -0,0 +1,239 @@
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+mod factors;
+mod garch;
+mod gbm;
+mod jump_diffusion;
+
+pub use factors::{FactorLoadings, FactorShocks};
+pub use garch::Garch;
+pub use gbm::GeometricBrownianMotion;
+pub use jump_diffusion::JumpDiffusion;
+
+pub trait PriceModel {
+    /// Returns the next price after a step of `dt` years. The `shock` is the
+    /// standard normal draw of the diffusion, which can be correlated with
+    /// other symbols.
+    fn next_price(&mut self, price: f64, dt: f64, shock: f64, rng: &mut StdRng) -> f64;
+
+    /// What the model carries from one step to the next, if anything, to
+    /// resume it after a restart
+    fn get_state(&self) -> Option<PriceModelState> {
+        None
+    }
+
+    fn set_state(&mut self, _state: &PriceModelState) {}
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceModelState {
+    Garch {
+        last_shock: f64,
+        variance: Option<f64>,
+    },
+}
+
+/// The rates and volatilities are annualized
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceModelSettings {
+    Gbm {
+        drift: f64,
+        volatility: f64,
+    },
+    JumpDiffusion {
+        drift: f64,
+        volatility: f64,
+        /** Expected number of jumps per year */
+        jump_intensity: f64,
+        /** Mean of the log jump size */
+        jump_mean: f64,
+        jump_volatility: f64,
+    },
+    Garch {
+        drift: f64,
+        long_run_volatility: f64,
+        alpha: f64,
+        beta: f64,
+    },
+}
+
+impl Default for PriceModelSettings {
+    fn default() -> Self {
+        PriceModelSettings::Gbm {
+            drift: 0.05,
+            volatility: 0.3,
+        }
+    }
+}
+
+impl PriceModelSettings {
+    pub fn verify(&self) -> Result<(), String> {
+        match self {
+            PriceModelSettings::Gbm { volatility, .. } if *volatility < 0.0 => {
+                Err("The GBM volatility can't be negative".to_string())
+            }
+            PriceModelSettings::JumpDiffusion {
+                volatility,
+                jump_intensity,
+                jump_volatility,
+                ..
+            } if *volatility < 0.0 || *jump_intensity < 0.0 || *jump_volatility < 0.0 => {
+                Err("The jump diffusion parameters can't be negative".to_string())
+            }
+            PriceModelSettings::Garch { alpha, beta, .. }
+                if *alpha < 0.0 || *beta < 0.0 || alpha + beta >= 1.0 =>
+            {
+                Err("The GARCH parameters must be positive and add up to less than 1".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn PriceModel> {
+        match self.clone() {
+            PriceModelSettings::Gbm { drift, volatility } => {
+                Box::new(GeometricBrownianMotion { drift, volatility })
+            }
+            PriceModelSettings::JumpDiffusion {
+                drift,
+                volatility,
+                jump_intensity,
+                jump_mean,
+                jump_volatility,
+            } => Box::new(JumpDiffusion {
+                drift,
+                volatility,
+                jump_intensity,
+                jump_mean,
+                jump_volatility,
+            }),
+            PriceModelSettings::Garch {
+                drift,
+                long_run_volatility,
+                alpha,
+                beta,
+            } => Box::new(Garch::new(drift, long_run_volatility, alpha, beta)),
+        }
+    }
+}
+
+// Box-Muller transform, to avoid depending on a distributions crate
+pub fn sample_standard_normal(rng: &mut StdRng) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+// Knuth's algorithm, fine for the small rates of a single step
+pub fn sample_poisson(lambda: f64, rng: &mut StdRng) -> u64 {
+    let limit = (-lambda).exp();
+    let mut count = 0;
+    let mut product = rng.gen::<f64>();
+
+    while product > limit {
+        count += 1;
+        product *= rng.gen::<f64>();
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    const DT: f64 = 1.0 / 365.0;
+
+    fn gen_path(settings: &PriceModelSettings, seed: u8) -> Vec<f64> {
+        let mut rng = StdRng::from_seed([seed; 32]);
+        let mut model = settings.build();
+        let mut price = 10.0;
+
+        (0..1_000)
+            .map(|_| {
+                let shock = sample_standard_normal(&mut rng);
+                price = model.next_price(price, DT, shock, &mut rng);
+                price
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_models_are_deterministic_and_positive() {
+        let all_settings = [
+            PriceModelSettings::default(),
+            PriceModelSettings::JumpDiffusion {
+                drift: 0.05,
+                volatility: 0.2,
+                jump_intensity: 10.0,
+                jump_mean: -0.05,
+                jump_volatility: 0.1,
+            },
+            PriceModelSettings::Garch {
+                drift: 0.05,
+                long_run_volatility: 0.3,
+                alpha: 0.1,
+                beta: 0.85,
+            },
+        ];
+
+        for settings in all_settings.iter() {
+            assert!(settings.verify().is_ok());
+
+            let path = gen_path(settings, 1);
+
+            assert_eq!(path, gen_path(settings, 1));
+            assert_ne!(path, gen_path(settings, 2));
+            assert!(path.iter().all(|price| *price > 0.0));
+        }
+    }
+
+    #[test]
+    fn test_gbm_without_volatility_follows_drift() {
+        let settings = PriceModelSettings::Gbm {
+            drift: 0.1,
+            volatility: 0.0,
+        };
+        let path = gen_path(&settings, 0);
+        let expected = 10.0 * (0.1 * DT * 1_000.0_f64).exp();
+
+        assert!((path.last().unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_garch_resumes_from_its_state() {
+        let settings = PriceModelSettings::Garch {
+            drift: 0.05,
+            long_run_volatility: 0.3,
+            alpha: 0.1,
+            beta: 0.85,
+        };
+        let mut rng = StdRng::from_seed([1; 32]);
+        let mut model = settings.build();
+        let mut price = 10.0;
+
+        for _ in 0..100 {
+            let shock = sample_standard_normal(&mut rng);
+            price = model.next_price(price, DT, shock, &mut rng);
+        }
+
+        let state = serde_json::to_string(&model.get_state().unwrap()).unwrap();
+        let mut resumed = settings.build();
+
+        resumed.set_state(&serde_json::from_str(&state).unwrap());
+
+        // A big shock moves the price by the variance after the path
+        let next_price = model.next_price(price, DT, 3.0, &mut rng);
+
+        assert_eq!(resumed.next_price(price, DT, 3.0, &mut rng), next_price);
+        assert_ne!(
+            settings.build().next_price(price, DT, 3.0, &mut rng),
+            next_price
+        );
+    }
+}
@@ -1,8 +1,11 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 use crate::core::{
-    order::{Order, OrderSide, OrderStatus, OrderType},
-    price::Prices,
+    company::CompanySymbol,
+    journal::JournalEvent,
+    money::{Money, RoundingMode},
+    order::{Order, OrderStatus},
+    price::{Price, Prices},
     stock::StockOwner,
     stock_exchange::StockExchange,
     time::TimeHandler,
@@ -10,199 +13,351 @@ use crate::core::{
 use rand::{seq::SliceRandom, Rng};
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 
-use super::Simulation;
+use super::{
+    price_model::{sample_standard_normal, FactorShocks},
+    strategy::apply_profile,
+    Simulation,
+};
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// Largest deviation of the price, as a log return, caused by the orders
+/// imbalance
+// @settings
+const MAX_IMPACT: f64 = 0.05;
+
+/// Part of the premium of an ETF to its iNAV that remains after a tick
+// @settings
+const ETF_PREMIUM_PERSISTENCE: f64 = 0.9;
 
+/// Part of the premium of a secondary listing to its primary one that remains
+/// after a tick, as the arbitrage is slower across exchanges
+// @settings
+const LISTING_PREMIUM_PERSISTENCE: f64 = 0.98;
+
+/// Standard deviation of the log premium that a secondary listing gets on
+/// each tick
+// @settings
+const LISTING_PREMIUM_NOISE: f64 = 0.002;
+
+mod arbitrage_etfs;
+mod arbitrage_listings;
+mod investor_finances;
+mod process_fund_flows;
+mod process_ipos;
+mod rebalance_funds;
+mod skip_closed_time;
+mod update_fair_values;
 mod verify_holidays;
 mod verify_investors;
+mod verify_listings;
 
 impl Simulation {
     fn create_new_orders(&mut self, se: &mut StockExchange, time: &TimeHandler) {
         let new_orders_num = self.r.gen_range(0..=self.settings.max_orders_per_tick);
+        // Collected once, instead of for each order, since the investors
+        // don't change here
+        let investor_ids = se.investors.mapping.keys().cloned().collect::<Vec<_>>();
+        let open_exchanges = se.get_open_exchanges(time);
+        // The market doesn't change either until the orders are executed
+        for strategy in self.strategies.values_mut() {
+            strategy.update(se);
+        }
+
+        let mut owners_with_orders = se
+            .orders_book
+            .0
+            .iter()
+            .map(|order| order.owner_id)
+            .collect::<HashSet<_>>();
 
         for _ in 0..new_orders_num {
-            let investor = se.investors.get_random(&mut self.r);
-            let lowest_bid_price = se.prices.get_lowest_bid_price();
-            let can_buy = if let Some(lowest_bid_price) = lowest_bid_price {
-                investor.liquid_cash.value > lowest_bid_price.value
-            } else {
-                true
+            let Some(investor_id) = investor_ids.choose(&mut self.r) else {
+                return;
             };
-            let stock_owner = StockOwner::Investor(investor.id);
-            let has_orders = se.orders_book.has_orders(&stock_owner);
+            let investor = &se.investors.mapping[investor_id];
+            let owner_id = StockOwner::Investor(investor.id);
 
             // @settings
-            if has_orders {
+            if owners_with_orders.contains(&owner_id) {
+                continue;
+            }
+
+            if !self.r.gen_bool(investor.profile.trading_frequency) {
                 continue;
             }
 
-            let stock_owner = StockOwner::Investor(investor.id);
-            let has_stocks = se.owned_stocks.has_stocks(&stock_owner);
-
-            let order_side = {
-                if can_buy && has_stocks {
-                    Some(if self.r.gen_bool(0.5) {
-                        OrderSide::Buy
-                    } else {
-                        OrderSide::Sell
-                    })
-                } else if can_buy {
-                    Some(OrderSide::Buy)
-                } else if has_stocks {
-                    Some(OrderSide::Sell)
-                } else {
-                    None
-                }
+            let strategy = self.strategies.entry(investor.strategy).or_insert_with(|| {
+                let mut strategy = investor.strategy.build();
+
+                strategy.update(se);
+                strategy
+            });
+
+            let Some((intent, order_type)) = strategy
+                .decide(investor, se, &mut self.r)
+                .and_then(|intent| apply_profile(intent, investor, se, &mut self.r))
+            else {
+                continue;
+            };
+            let Some(company) = se.listed_companies.mapping.get(&intent.symbol) else {
+                continue;
             };
 
-            if order_side.is_none() {
+            // The exchanges have different sessions
+            if se
+                .get_listing_exchange_id(&intent.symbol)
+                .is_some_and(|exchange_id| !open_exchanges.contains(exchange_id))
+            {
                 continue;
             }
 
-            let order_side = order_side.unwrap();
-
-            match order_side {
-                OrderSide::Sell => {
-                    let stock_to_sell = se
-                        .owned_stocks
-                        .0
-                        .get(&stock_owner)
-                        .unwrap()
-                        .choose(&mut self.r)
-                        .unwrap();
-
-                    let lot_size = se
-                        .listed_companies
-                        .mapping
-                        .values()
-                        .find(|company| company.symbol == stock_to_sell.symbol)
-                        .unwrap()
-                        .lot_size;
-                    let lots_to_sell = self.r.gen_range(1..=stock_to_sell.quantity / lot_size);
-                    let shares = lots_to_sell * lot_size;
-                    let owner_id = StockOwner::Investor(investor.id);
-                    let order_type = OrderType::Market;
-                    let new_order = Order {
-                        order_side: OrderSide::Sell,
-                        order_type,
-                        owner_id,
-                        shares,
-                        status: OrderStatus::Init,
-                        symbol: stock_to_sell.symbol.clone(),
-                    };
-
-                    se.place_order(&new_order, time).unwrap_or_else(|e| {
-                        println!("Error placing order: {:?}", e);
-                        std::process::exit(1);
-                    });
-                }
-                OrderSide::Buy => {
-                    let afforded_companies = se
-                        .listed_companies
-                        .mapping
-                        .values()
-                        .filter(|company| {
-                            let price = se.prices.get_ask_price(&company.symbol).unwrap();
-                            let price_per_lot = price
-                                .value
-                                .checked_mul(Decimal::new(company.lot_size as i64, 0))
-                                .unwrap();
-
-                            investor.liquid_cash.value > price_per_lot
-                        })
-                        .collect::<Vec<_>>();
-                    let afforded_company = afforded_companies.choose(&mut self.r);
-
-                    if afforded_company.is_none() {
-                        continue;
-                    }
-
-                    let company = afforded_company.unwrap();
-                    let max_affordable_lots = (investor.liquid_cash.value
-                        / (se
-                            .prices
-                            .get_ask_price(&company.symbol)
-                            .unwrap()
-                            .value
-                            .checked_mul(Decimal::new(company.lot_size as i64, 0)))
-                        .unwrap())
-                    .floor();
-
-                    if max_affordable_lots.is_zero() {
-                        continue;
-                    }
-
-                    let lots_to_buy = self
-                        .r
-                        .gen_range(1..=max_affordable_lots.try_into().unwrap());
-                    let shares = lots_to_buy * company.lot_size;
-                    let owner_id = StockOwner::Investor(investor.id);
-                    let order_type = OrderType::Market;
-                    let new_order = Order {
-                        order_side: OrderSide::Buy,
-                        order_type,
-                        owner_id,
-                        shares,
-                        status: OrderStatus::Init,
-                        symbol: company.symbol.clone(),
-                    };
-
-                    se.place_order(&new_order, time).unwrap_or_else(|e| {
-                        println!("Error placing order: {:?}", e);
-                        std::process::exit(1);
-                    });
-                }
-            }
+            let new_order = Order {
+                order_side: intent.side,
+                order_type,
+                owner_id,
+                shares: intent.lots * company.lot_size,
+                status: OrderStatus::Init,
+                symbol: intent.symbol,
+            };
+
+            se.place_order(&new_order, time).unwrap_or_else(|e| {
+                println!("Error placing order: {:?}", e);
+                std::process::exit(1);
+            });
+            owners_with_orders.insert(owner_id);
         }
     }
 
-    fn update_prices(&mut self, se: &mut StockExchange) {
+    /// The orders that couldn't be matched push the price in their direction,
+    /// so the big orders move the market. The impact is temporary: the price
+    /// evolves without it, and it returns the price before the last impact.
+    fn apply_price_impact(
+        &mut self,
+        se: &StockExchange,
+        imbalances: &BTreeMap<CompanySymbol, i128>,
+        symbol: &CompanySymbol,
+        price: f64,
+    ) -> (f64, f64) {
+        let impact = se
+            .listed_companies
+            .mapping
+            .get(symbol)
+            // The ETFs without units have no free float yet
+            .map(|company| company.get_free_float_shares().max(company.lot_size))
+            .filter(|shares| *shares > 0)
+            .map_or(0.0, |shares| {
+                imbalances.get(symbol).copied().unwrap_or(0) as f64 / shares as f64
+                    * self.settings.price_impact
+            })
+            .clamp(-MAX_IMPACT, MAX_IMPACT);
+        let previous_impact = self.progress.price_impacts.insert(symbol.clone(), impact);
+
+        (price / previous_impact.unwrap_or(0.0).exp(), impact)
+    }
+
+    fn get_new_quote(&mut self, price: &Price, next_price: f64) -> Price {
+        let currency = price.bid.currency;
+        let round = |value: Decimal| Money { currency, value }.round(RoundingMode::Bankers).value;
+        // One minor unit of the currency
+        let min_price = Decimal::new(1, currency.get_minor_units());
+        let new_price = round(Decimal::from_f64(next_price).unwrap_or(min_price)).max(min_price);
+
+        // @settings
+        let spread =
+            round(new_price * Money::gen_from_range(&mut self.r, (0.001, 0.01))).max(min_price);
+
+        price.get_with_spread(new_price, spread)
+    }
+
+    fn update_prices(&mut self, se: &mut StockExchange, time: &TimeHandler) {
         let mut new_prices = BTreeMap::new();
+        // The time since the last update, which is longer after skipping the
+        // closed time
+        let now = time.get_now_unix_timestamp();
+        let elapsed_seconds = self
+            .progress
+            .last_prices_update
+            .filter(|last_update| *last_update < now)
+            .map_or(time.get_tick_seconds(), |last_update| {
+                (now - last_update) as f64
+            });
+        let dt = elapsed_seconds / SECONDS_PER_YEAR;
+        let imbalances = se.orders_book.get_imbalances();
+
+        self.progress.last_prices_update = Some(now);
+
+        let factor_shocks = FactorShocks::gen(&mut self.r);
+
+        self.price_models
+            .retain(|symbol, _| se.prices.0.contains_key(symbol));
+        self.progress
+            .price_impacts
+            .retain(|symbol, _| se.prices.0.contains_key(symbol));
 
         for (symbol, price) in &se.prices.0 {
-            let average = price.get_average().value;
+            // The ETFs follow their basket and the secondary listings their
+            // primary one, below
+            if se.is_etf(symbol) || se.get_primary_symbol(symbol) != symbol {
+                continue;
+            }
+
+            let Ok(average) = price.get_average() else {
+                continue;
+            };
+            let sector = se.get_company(symbol).unwrap().sector;
+            let shock =
+                factor_shocks.get_shock(&self.settings.factor_loadings, &sector, &mut self.r);
+            let (base_price, impact) =
+                self.apply_price_impact(se, &imbalances, symbol, average.to_f64());
+            let model = self
+                .price_models
+                .entry(symbol.clone())
+                .or_insert_with(|| self.settings.get_price_model(symbol).build());
+
+            let next_price = model.next_price(base_price, dt, shock, &mut self.r) * impact.exp();
+
+            new_prices.insert(symbol.clone(), self.get_new_quote(price, next_price));
+        }
+
+        let mut new_prices = Prices(new_prices);
+
+        // The secondary listings are quoted around the price of the primary
+        // one in their currency, with a premium that fades unless the orders
+        // imbalance keeps it
+        for listed in se.listed_companies.mapping.values() {
+            let (Some(primary_symbol), Some(price), Some(average)) = (
+                listed.primary_symbol.as_ref(),
+                se.prices.0.get(&listed.symbol),
+                se.prices.get_average_price(&listed.symbol),
+            ) else {
+                continue;
+            };
+            let currency = &price.bid.currency;
+            let convert = |prices: &Prices| {
+                prices
+                    .get_average_price(primary_symbol)
+                    .and_then(|price| se.fx_rates.convert(&price, currency))
+                    .map(|price| price.to_f64())
+            };
+            let (Some(anchor), Some(new_anchor)) = (convert(&se.prices), convert(&new_prices))
+            else {
+                continue;
+            };
+            let (base_price, impact) =
+                self.apply_price_impact(se, &imbalances, &listed.symbol, average.to_f64());
+            let premium = if anchor > 0.0 && base_price > 0.0 {
+                (base_price / anchor).ln() * LISTING_PREMIUM_PERSISTENCE
+            } else {
+                0.0
+            };
+            let noise = sample_standard_normal(&mut self.r) * LISTING_PREMIUM_NOISE;
+            let next_price = new_anchor * (premium + noise + impact).exp();
+
+            new_prices
+                .0
+                .insert(listed.symbol.clone(), self.get_new_quote(price, next_price));
+        }
 
-            let price_change = Decimal::from_f64(self.r.gen_range(-0.1..=0.1))
-                .unwrap()
-                .round_dp(2);
-            let new_price = (average + price_change).round_dp(2);
-            let spread = Decimal::from_f64(self.r.gen_range(0.1..=2.0))
-                .unwrap()
-                .round_dp(2);
+        // The ETFs are quoted around their iNAV, with a premium that fades
+        // unless the orders imbalance keeps it
+        for etf in se.etfs.mapping.values() {
+            let (Some(price), Some(average)) = (
+                se.prices.0.get(&etf.symbol),
+                se.prices.get_average_price(&etf.symbol),
+            ) else {
+                continue;
+            };
+            let new_inav = se.get_etf_inav(etf, &new_prices);
+            let (base_price, impact) =
+                self.apply_price_impact(se, &imbalances, &etf.symbol, average.to_f64());
+            let premium = if etf.inav > 0.0 && base_price > 0.0 {
+                (base_price / etf.inav).ln() * ETF_PREMIUM_PERSISTENCE
+            } else {
+                0.0
+            };
+            let tracking_error =
+                sample_standard_normal(&mut self.r) * self.settings.etfs.tracking_noise;
+            let next_price = new_inav * (premium + tracking_error + impact).exp();
 
-            new_prices.insert(symbol.clone(), price.get_with_spread(new_price, spread));
+            new_prices
+                .0
+                .insert(etf.symbol.clone(), self.get_new_quote(price, next_price));
         }
 
-        se.prices = Prices(new_prices);
+        se.returns.record(&se.prices, &new_prices);
+        se.prices = new_prices;
+        se.index.update_level(&se.prices);
+        se.update_etf_inavs();
+        se.record_event(JournalEvent::PricesUpdated {
+            prices: se.prices.clone(),
+        });
     }
 
     pub fn run(&mut self, se: &mut StockExchange, time: &TimeHandler) -> Result<(), String> {
         // WIP: Steps to run:
         // - Introduce brokers
-        // - List new companies via IPO
-        // - Remove comanies via delisting
         // - Introduce random price changes due to good/bad news of companies
 
+        se.record_event(JournalEvent::TickStarted { time: time.clone() });
+
         let current_day = time.get_virtual_day_formatted();
-        if self.daily_checks != Some(current_day) {
+        if self.progress.daily_checks != Some(current_day) {
             self.verify_holidays(se, time)?;
+            self.settings.fx.update_rates(
+                &mut se.fx_rates,
+                time.get_now_unix_timestamp(),
+                &mut self.r,
+            );
             self.verify_investors(se, time)?;
+            self.verify_listings(se, time)?;
+            self.update_fair_values(se);
+
+            se.value_funds(time);
+            self.process_fund_flows(se)?;
+            self.price_storage
+                .save_historic_navs(&se.funds, time)
+                .map_err(|e| format!("Error saving historic NAVs: {:?}", e))?;
+
+            se.sync_index_shares();
+            se.rebalance_index_if_due(time);
+            se.update_etf_baskets();
 
             let current_day = time.get_virtual_day_formatted();
-            self.daily_checks = Some(current_day);
+            self.progress.daily_checks = Some(current_day);
         }
 
+        self.process_ipos(se, time)?;
+
         if se.can_trade_now(time) {
+            se.flush_closed_orders(time);
+            self.rebalance_funds(se, time);
             self.create_new_orders(se, time);
-            se.execute_orders();
+            self.arbitrage_etfs(se, time);
+            self.arbitrage_listings(se, time);
+            se.execute_orders(time);
         } else {
             se.flush_orders();
         }
 
-        self.update_prices(se);
+        self.update_prices(se, time);
         self.price_storage
             .save_historic_price(&se.prices, time)
             .map_err(|e| format!("Error saving historic price: {:?}", e))?;
+        self.price_storage
+            .save_historic_index(&se.index, time)
+            .map_err(|e| format!("Error saving historic index: {:?}", e))?;
+
+        // The closed hours don't have bars
+        let closed_candles = se.candles.record_tick(
+            &se.get_session_prices(time),
+            &se.get_listing_timezones(),
+            time,
+        );
+        self.price_storage
+            .save_candles(&closed_candles)
+            .map_err(|e| format!("Error saving candles: {:?}", e))?;
 
-        Ok(())
+        self.write_journal(se)
     }
 }
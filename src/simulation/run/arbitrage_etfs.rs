@@ -0,0 +1,178 @@
+use crate::core::{
+    etf::{Etf, EtfId},
+    order::{Order, OrderSide, OrderStatus, OrderType},
+    stock::StockOwner,
+    stock_exchange::StockExchange,
+    time::TimeHandler,
+};
+use log::debug;
+
+use super::Simulation;
+
+fn build_order(owner_id: StockOwner, etf: &Etf, order_side: OrderSide, shares: u64) -> Order {
+    Order {
+        order_side,
+        order_type: OrderType::Market,
+        owner_id,
+        shares,
+        status: OrderStatus::Init,
+        symbol: etf.symbol.clone(),
+    }
+}
+
+/// Replaces the orders of the owner for the symbols of the ETF. The orders
+/// that are still wanted keep resting in the book, so they can be matched by
+/// the next orders of the counterparties.
+fn replace_orders(
+    se: &mut StockExchange,
+    owner: &StockOwner,
+    etf_id: &EtfId,
+    orders: Vec<Order>,
+    time: &TimeHandler,
+) {
+    let etf = &se.etfs.mapping[etf_id];
+    let symbol = etf.symbol.clone();
+    let basket = etf.basket.clone();
+    let is_same = |a: &Order, b: &Order| a.symbol == b.symbol && a.order_side == b.order_side;
+
+    se.orders_book.0.retain(|order| {
+        &order.owner_id != owner
+            || (order.symbol != symbol && !basket.contains_key(&order.symbol))
+            || orders.iter().any(|o| is_same(o, order))
+    });
+
+    let new_orders = orders
+        .into_iter()
+        .filter(|order| {
+            !se.orders_book
+                .0
+                .iter()
+                .any(|o| &o.owner_id == owner && is_same(o, order))
+        })
+        .collect::<Vec<_>>();
+
+    for order in new_orders {
+        if let Err(e) = se.place_order(&order, time) {
+            debug!("{:?} could not place an order: {:?}", owner, e);
+        }
+    }
+}
+
+impl Simulation {
+    /// When the ETF is rich, the participant creates units and sells them.
+    /// When it is cheap, it buys ETF shares, redeems them and sells the
+    /// basket.
+    fn get_arbitrage_orders(
+        &self,
+        se: &mut StockExchange,
+        etf_id: &EtfId,
+        participant: &StockOwner,
+        premium: f64,
+    ) -> Vec<Order> {
+        let threshold = self.settings.etfs.arbitrage_threshold;
+        let etf = se.etfs.mapping[etf_id].clone();
+
+        if premium > threshold {
+            let baskets = se.count_etf_baskets(etf_id, participant).max(1);
+
+            if let Err(e) = se.create_etf_units(etf_id, participant, baskets) {
+                debug!("Could not create units of {}: {}", etf.symbol, e);
+            }
+
+            let held = se.owned_stocks.get_quantity(participant, &etf.symbol);
+
+            return (held > 0)
+                .then(|| build_order(*participant, &etf, OrderSide::Sell, held))
+                .into_iter()
+                .collect();
+        }
+
+        if premium < -threshold {
+            let held = se.owned_stocks.get_quantity(participant, &etf.symbol);
+
+            if held >= etf.creation_unit {
+                if let Err(e) = se.redeem_etf_units(etf_id, participant, held / etf.creation_unit) {
+                    debug!("Could not redeem units of {}: {}", etf.symbol, e);
+                }
+            }
+
+            let mut orders = etf
+                .basket
+                .keys()
+                .filter_map(|symbol| {
+                    let held = se.owned_stocks.get_quantity(participant, symbol);
+
+                    (held > 0).then(|| Order {
+                        symbol: symbol.clone(),
+                        ..build_order(*participant, &etf, OrderSide::Sell, held)
+                    })
+                })
+                .collect::<Vec<_>>();
+            let units = se.etfs.mapping[etf_id].units;
+            let held = se.owned_stocks.get_quantity(participant, &etf.symbol);
+            let missing = etf.creation_unit - held % etf.creation_unit;
+
+            // Only the outstanding units can be redeemed
+            if held + missing <= units {
+                orders.push(build_order(*participant, &etf, OrderSide::Buy, missing));
+            }
+
+            return orders;
+        }
+
+        vec![]
+    }
+
+    /// The custodian buys the shares that it lacks with the cash in lieu of
+    /// the creations, as far as the cash allows
+    fn get_custodian_orders(&self, se: &StockExchange, etf_id: &EtfId) -> Vec<Order> {
+        let etf = &se.etfs.mapping[etf_id];
+        let mut available_cash = etf.cash.to_f64();
+
+        se.get_etf_shares_to_buy(etf_id)
+            .into_iter()
+            .filter_map(|(symbol, missing)| {
+                let price = se.prices.get_average_price(&symbol)?.to_f64();
+                let shares = missing.min((available_cash / price.max(f64::EPSILON)) as u64);
+
+                available_cash -= shares as f64 * price;
+
+                (shares > 0).then(|| Order {
+                    symbol,
+                    ..build_order(StockOwner::Etf(*etf_id), etf, OrderSide::Buy, shares)
+                })
+            })
+            .collect()
+    }
+
+    /// The authorized participant, a market maker, closes the gaps between
+    /// the price of the ETFs and their iNAV through the order book
+    pub(super) fn arbitrage_etfs(&mut self, se: &mut StockExchange, time: &TimeHandler) {
+        // The first market maker takes the liquidations, so the last one is
+        // the participant
+        let Some(market_maker_id) = se.market_makers.mapping.keys().last().cloned() else {
+            return;
+        };
+        let participant = StockOwner::MarketMaker(market_maker_id);
+        let etf_ids = se.etfs.mapping.keys().cloned().collect::<Vec<_>>();
+
+        for etf_id in etf_ids {
+            let etf = &se.etfs.mapping[&etf_id];
+            let Some(premium) = se
+                .prices
+                .get_average_price(&etf.symbol)
+                .and_then(|price| etf.get_premium(price.to_f64()))
+            else {
+                continue;
+            };
+
+            let orders = self.get_arbitrage_orders(se, &etf_id, &participant, premium);
+
+            replace_orders(se, &participant, &etf_id, orders, time);
+
+            let orders = self.get_custodian_orders(se, &etf_id);
+
+            replace_orders(se, &StockOwner::Etf(etf_id), &etf_id, orders, time);
+        }
+    }
+}
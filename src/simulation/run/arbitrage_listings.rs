@@ -0,0 +1,208 @@
+use crate::core::{
+    company::CompanySymbol,
+    order::{Order, OrderSide, OrderStatus, OrderType},
+    stock::StockOwner,
+    stock_exchange::StockExchange,
+    time::TimeHandler,
+};
+use log::debug;
+
+use super::Simulation;
+
+impl Simulation {
+    /// Orders that close the gap between a secondary listing and its primary
+    /// one: buying the cheap listing and selling what is held of the rich
+    /// one. The size grows with the premium.
+    fn get_listing_arbitrage_orders(
+        &self,
+        se: &StockExchange,
+        arbitrageur: &StockOwner,
+        symbol: &CompanySymbol,
+        primary_symbol: &CompanySymbol,
+    ) -> Vec<Order> {
+        let (Some(premium), Some(listed)) = (
+            se.get_listing_premium(symbol),
+            se.listed_companies.mapping.get(symbol),
+        ) else {
+            return vec![];
+        };
+
+        if premium.abs() <= self.settings.exchanges.arbitrage_threshold {
+            return vec![];
+        }
+
+        let (rich, cheap) = if premium > 0.0 {
+            (symbol, primary_symbol)
+        } else {
+            (primary_symbol, symbol)
+        };
+        let lots = listed.get_free_float_shares() / listed.lot_size;
+        let shares = ((lots as f64 * premium.abs()).ceil() as u64).max(1) * listed.lot_size;
+        let held = se.owned_stocks.get_quantity(arbitrageur, rich);
+        let build_order = |order_side, shares, symbol: &CompanySymbol| Order {
+            order_side,
+            order_type: OrderType::Market,
+            owner_id: *arbitrageur,
+            shares,
+            status: OrderStatus::Init,
+            symbol: symbol.clone(),
+        };
+        let mut orders = vec![build_order(OrderSide::Buy, shares, cheap)];
+
+        if held > 0 {
+            orders.push(build_order(OrderSide::Sell, shares.min(held), rich));
+        }
+
+        orders
+    }
+
+    /// A market maker trades the differences between the prices of the same
+    /// company in different exchanges, after the FX conversion, while both of
+    /// them are in session
+    pub(super) fn arbitrage_listings(&mut self, se: &mut StockExchange, time: &TimeHandler) {
+        // The same participant as for the ETFs
+        let Some(market_maker_id) = se.market_makers.mapping.keys().last().cloned() else {
+            return;
+        };
+        let arbitrageur = StockOwner::MarketMaker(market_maker_id);
+        let open_exchanges = se.get_open_exchanges(time);
+        let pairs = se
+            .listed_companies
+            .mapping
+            .values()
+            .filter_map(|listed| Some((listed.symbol.clone(), listed.primary_symbol.clone()?)))
+            .filter(|(symbol, primary_symbol)| {
+                [symbol, primary_symbol].iter().all(|symbol| {
+                    se.is_tradeable(symbol)
+                        && se
+                            .listed_companies
+                            .mapping
+                            .get(*symbol)
+                            .is_some_and(|listed| open_exchanges.contains(&listed.exchange))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (symbol, primary_symbol) in pairs {
+            let orders =
+                self.get_listing_arbitrage_orders(se, &arbitrageur, &symbol, &primary_symbol);
+
+            se.orders_book.0.retain(|order| {
+                order.owner_id != arbitrageur
+                    || (order.symbol != symbol && order.symbol != primary_symbol)
+            });
+
+            for order in orders {
+                if let Err(e) = se.place_order(&order, time) {
+                    debug!("{:?} could not place an order: {:?}", arbitrageur, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{
+            company::ListedCompany,
+            market_maker::MarketMakers,
+            money::Currency,
+            stock_exchange::ExchangeId,
+            test_fixtures::{add_holding, add_listing, build_listing, build_money_in},
+        },
+        server::storage_wrappers::RedisPriceStorage,
+        simulation::settings::SimulationSettings,
+        storage::memory::StorageMemoryImpl,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// FOO is listed in HK and has a secondary listing in SH, of which the
+    /// market maker holds 100 shares
+    fn build_simulation() -> (Simulation, StockExchange, TimeHandler) {
+        let settings = SimulationSettings::default();
+        let mut se = StockExchange::new(settings.exchanges.list.clone());
+        let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+        let simulation = Simulation::new([0; 32], settings, price_storage);
+        // 2024-12-23 10:00 HKT, a Monday, when both exchanges are open
+        let time = TimeHandler::new(1_734_919_200, Some(1), 100);
+        let exchange_id = ExchangeId("SH".to_string());
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let secondary_symbol = symbol.get_secondary(&exchange_id);
+
+        se.market_makers =
+            MarketMakers::gen_list(1, &time, &mut StdRng::from_seed([0; 32])).unwrap();
+
+        let arbitrageur = StockOwner::MarketMaker(*se.market_makers.mapping.keys().last().unwrap());
+
+        // One yuan is worth two Hong Kong dollars, so the secondary listing
+        // trades at a premium of 20%
+        se.fx_rates.base = Currency::Hkd;
+        se.fx_rates.rates.insert(Currency::Cny, 2.0);
+
+        for (listing_symbol, exchange_id, currency, primary_symbol, price) in [
+            (&symbol, se.home_exchange.clone(), Currency::Hkd, None, 10.0),
+            (
+                &secondary_symbol,
+                exchange_id,
+                Currency::Cny,
+                Some(symbol.clone()),
+                6.0,
+            ),
+        ] {
+            let listing = ListedCompany {
+                primary_symbol,
+                ..build_listing(listing_symbol, &exchange_id)
+            };
+
+            add_listing(&mut se, listing, build_money_in(currency, price));
+        }
+
+        let price = se.prices.get_average_price(&secondary_symbol).unwrap();
+
+        add_holding(&mut se, arbitrageur, &secondary_symbol, price, 100);
+
+        (simulation, se, time)
+    }
+
+    #[test]
+    fn test_arbitrage_closes_the_listing_premium() {
+        let (mut simulation, mut se, time) = build_simulation();
+        let symbol = CompanySymbol::new("FOO".to_string());
+        let secondary_symbol = symbol.get_secondary(&ExchangeId("SH".to_string()));
+        let premium = se.get_listing_premium(&secondary_symbol).unwrap();
+
+        assert!((premium - 0.2).abs() < 1e-9);
+
+        // It buys the cheap listing and sells what it holds of the rich one
+        simulation.arbitrage_listings(&mut se, &time);
+
+        let orders = se
+            .orders_book
+            .0
+            .iter()
+            .map(|order| (order.order_side.clone(), order.symbol.clone(), order.shares))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            orders,
+            vec![
+                (OrderSide::Buy, symbol.clone(), 200),
+                (OrderSide::Sell, secondary_symbol.clone(), 100),
+            ]
+        );
+
+        // The imbalance of the orders pushes the prices towards each other,
+        // faster than the premium fades without them
+        let (mut idle_simulation, mut idle_se, _) = build_simulation();
+
+        simulation.update_prices(&mut se, &time);
+        idle_simulation.update_prices(&mut idle_se, &time);
+
+        let new_premium = se.get_listing_premium(&secondary_symbol).unwrap();
+        let idle_premium = idle_se.get_listing_premium(&secondary_symbol).unwrap();
+
+        assert!(new_premium > 0.0 && new_premium < idle_premium && idle_premium < premium);
+    }
+}
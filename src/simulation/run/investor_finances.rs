@@ -0,0 +1,190 @@
+use crate::core::{
+    money::{Money, RoundingMode},
+    stock_exchange::StockExchange,
+    time::TimeHandler,
+};
+use log::debug;
+
+use super::Simulation;
+
+const DAYS_PER_MONTH: f64 = 30.4375;
+
+impl Simulation {
+    /// Runs daily: the income is deposited on the first day of each month and
+    /// the living expenses are withdrawn every day. Investors in debt exchange
+    /// their foreign cash, and the ones that stay in debt longer than the
+    /// grace period have their holdings liquidated, and leave the market if
+    /// that doesn't repay the debt.
+    pub(super) fn process_investor_finances(
+        &mut self,
+        se: &mut StockExchange,
+        time: &TimeHandler,
+    ) -> Result<(), String> {
+        let now = time.get_now_unix_timestamp();
+        let is_payday = time.get_month_day() == 1;
+        let mut insolvent_investors = Vec::new();
+        let mut indebted_investors = Vec::new();
+
+        for investor in se.investors.mapping.values_mut() {
+            if is_payday {
+                let income = investor.monthly_income;
+
+                investor
+                    .add_cash(&income)
+                    .map_err(|e| format!("Error paying {:?}: {}", investor.id, e))?;
+            }
+
+            let daily_expenses = investor
+                .monthly_expenses
+                .checked_div(Money::from_f64(DAYS_PER_MONTH), RoundingMode::Bankers)
+                .map_err(|e| format!("Error charging {:?}: {}", investor.id, e))?;
+
+            investor
+                .subtract_cash(&daily_expenses)
+                .map_err(|e| format!("Error charging {:?}: {}", investor.id, e))?;
+
+            if !investor.debt.value.is_zero() && !investor.foreign_cash.is_empty() {
+                indebted_investors.push(investor.id);
+            }
+        }
+
+        for investor_id in indebted_investors {
+            se.repatriate_investor_cash(&investor_id)?;
+        }
+
+        for investor in se.investors.mapping.values_mut() {
+            if investor.debt.value.is_zero() {
+                investor.in_debt_since = None;
+                continue;
+            }
+
+            let in_debt_since = *investor.in_debt_since.get_or_insert(now);
+            let grace_end = time.get_n_days_from_unix_timestamp(
+                in_debt_since,
+                self.settings.wealth.insolvency_grace_days,
+            );
+
+            if now >= grace_end {
+                insolvent_investors.push(investor.id);
+            }
+        }
+
+        for investor_id in insolvent_investors {
+            se.liquidate_investor_holdings(&investor_id)?;
+
+            let investor = se.investors.mapping.get_mut(&investor_id).unwrap();
+
+            if investor.debt.value.is_zero() {
+                investor.in_debt_since = None;
+                continue;
+            }
+
+            debug!(
+                "Removed investor in simulation because insolvent: {:?}",
+                investor_id
+            );
+            se.settle_estate(&investor_id, None, time)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{
+            investor::{Investor, InvestorId},
+            market_maker::{MarketMaker, MarketMakerId},
+            stock::StockOwner,
+            test_fixtures::{
+                add_holding, add_listing, build_investor, build_listing, build_money, build_symbol,
+            },
+        },
+        server::storage_wrappers::RedisPriceStorage,
+        simulation::settings::SimulationSettings,
+        storage::memory::StorageMemoryImpl,
+    };
+
+    #[test]
+    fn test_process_investor_finances() {
+        let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+        let mut simulation = Simulation::new([0; 32], SimulationSettings::default(), price_storage);
+        let mut se = StockExchange::default();
+        // 2024-12-01 10:00 HKT, a payday
+        let time = TimeHandler::new(1_733_018_400, Some(1), 100);
+        let symbol = build_symbol("FOO");
+        let market_maker_id = MarketMakerId::init();
+        let earner = InvestorId::new(&InvestorId::init());
+        let liquidated = InvestorId::new(&earner);
+        let insolvent = InvestorId::new(&liquidated);
+        let indebted = InvestorId::new(&insolvent);
+
+        se.market_makers.mapping.insert(
+            market_maker_id,
+            MarketMaker {
+                id: market_maker_id,
+                permit_end_time: 1,
+                permit_start_time: 0,
+            },
+        );
+        let listing = build_listing(&symbol, &se.home_exchange);
+
+        add_listing(&mut se, listing, build_money(10.0));
+        se.investors.mapping.insert(
+            earner,
+            Investor {
+                monthly_expenses: build_money(DAYS_PER_MONTH * 10.0),
+                monthly_income: build_money(3000.0),
+                ..build_investor(earner, build_money(100.0))
+            },
+        );
+
+        // The grace period of the first two is over, and only the holdings of
+        // the first one repay the debt
+        for (investor_id, in_debt_since) in [
+            (liquidated, Some(0)),
+            (insolvent, Some(0)),
+            (indebted, None),
+        ] {
+            se.investors.mapping.insert(
+                investor_id,
+                Investor {
+                    debt: build_money(500.0),
+                    in_debt_since,
+                    ..build_investor(investor_id, build_money(0.0))
+                },
+            );
+        }
+
+        add_holding(
+            &mut se,
+            StockOwner::Investor(liquidated),
+            &symbol,
+            build_money(10.0),
+            100,
+        );
+
+        simulation
+            .process_investor_finances(&mut se, &time)
+            .unwrap();
+
+        let investors = &se.investors.mapping;
+
+        assert_eq!(
+            investors[&earner].liquid_cash.value,
+            build_money(3090.0).value
+        );
+        assert_eq!(
+            investors[&liquidated].liquid_cash.value,
+            build_money(500.0).value
+        );
+        assert_eq!(investors[&liquidated].in_debt_since, None);
+        assert!(!investors.contains_key(&insolvent));
+        assert_eq!(
+            investors[&indebted].in_debt_since,
+            Some(time.get_now_unix_timestamp())
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use crate::core::{money::Money, stock_exchange::StockExchange};
+use rand::{seq::SliceRandom, Rng};
+use rust_decimal::Decimal;
+
+use super::Simulation;
+
+impl Simulation {
+    /// Retail investors that subscribe to the funds with part of their cash,
+    /// or redeem part of their units, at the NAV of the day
+    pub(super) fn process_fund_flows(&mut self, se: &mut StockExchange) -> Result<(), String> {
+        let settings = &self.settings.funds;
+        let fund_ids = se.funds.mapping.keys().cloned().collect::<Vec<_>>();
+
+        if fund_ids.is_empty() {
+            return Ok(());
+        }
+
+        let investor_ids = se.investors.mapping.keys().cloned().collect::<Vec<_>>();
+
+        for investor_id in investor_ids {
+            if !self.r.gen_bool(settings.daily_flow_probability) {
+                continue;
+            }
+
+            let ratio = settings.flow_ratio.sample(&mut self.r).clamp(0.0, 1.0);
+            let held_funds = se.get_investor_funds(&investor_id);
+
+            // @settings
+            if !held_funds.is_empty() && self.r.gen_bool(0.5) {
+                let fund_id = held_funds.choose(&mut self.r).unwrap();
+                let units = se.funds.mapping[fund_id].get_investor_units(&investor_id) * ratio;
+
+                se.redeem_from_fund(&investor_id, fund_id, units)?;
+
+                continue;
+            }
+
+            let liquid_cash = se.investors.mapping[&investor_id].liquid_cash;
+            let amount = Money {
+                currency: liquid_cash.currency,
+                value: Money::from_f64(liquid_cash.to_f64() * ratio).min(liquid_cash.value),
+            };
+
+            if amount.value <= Decimal::ZERO {
+                continue;
+            }
+
+            let fund_id = fund_ids.choose(&mut self.r).unwrap();
+
+            se.subscribe_to_fund(&investor_id, fund_id, &amount)?;
+        }
+
+        Ok(())
+    }
+}
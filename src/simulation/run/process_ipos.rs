@@ -0,0 +1,61 @@
+use crate::core::{stock_exchange::StockExchange, time::TimeHandler};
+use rand::Rng;
+use rust_decimal::prelude::ToPrimitive;
+
+use super::Simulation;
+
+impl Simulation {
+    fn create_ipo_subscriptions(&mut self, se: &mut StockExchange, time: &TimeHandler) {
+        let open_symbols = se
+            .ipos
+            .mapping
+            .values()
+            .filter(|ipo| ipo.is_offer_open(time))
+            .map(|ipo| ipo.symbol.clone())
+            .collect::<Vec<_>>();
+
+        for symbol in open_symbols {
+            let new_subscriptions_num = self
+                .r
+                .gen_range(0..=self.settings.max_ipo_subscriptions_per_tick);
+
+            for _ in 0..new_subscriptions_num {
+                let investor_id = se.investors.get_random(&mut self.r).id;
+                let ipo = se.ipos.mapping.get_mut(&symbol).unwrap();
+                let investor = se.investors.mapping.get_mut(&investor_id).unwrap();
+
+                let Ok(lot_price) = ipo.get_lots_price(1) else {
+                    continue;
+                };
+                let max_affordable_lots = (investor.liquid_cash.value / lot_price.value)
+                    .floor()
+                    .to_u64()
+                    .unwrap_or(0)
+                    .min(ipo.get_total_lots());
+
+                if max_affordable_lots == 0 {
+                    continue;
+                }
+
+                let lots = self.r.gen_range(1..=max_affordable_lots);
+
+                // Investors that already subscribed are skipped
+                let _ = ipo.subscribe(investor, lots, time);
+            }
+        }
+    }
+
+    pub(super) fn process_ipos(
+        &mut self,
+        se: &mut StockExchange,
+        time: &TimeHandler,
+    ) -> Result<(), String> {
+        self.create_ipo_subscriptions(se, time);
+
+        se.allot_ipos(time, &mut self.r)?;
+        se.list_ipos(time)?;
+        se.record_ipos_first_day_close(time);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::core::{
+    fund::FundId,
+    order::{Order, OrderSide, OrderStatus, OrderType},
+    stock::StockOwner,
+    stock_exchange::StockExchange,
+    time::TimeHandler,
+};
+use log::debug;
+use std::collections::BTreeSet;
+
+use super::Simulation;
+
+impl Simulation {
+    /// Orders that move the holdings of the fund towards the targets of the
+    /// mandate. The size of each order is limited, so the big deviations take
+    /// several days to close.
+    fn get_fund_rebalance_orders(&self, se: &StockExchange, fund_id: &FundId) -> Vec<Order> {
+        let owner = StockOwner::Fund(*fund_id);
+        let Some(nav) = se.get_fund_nav(fund_id) else {
+            return vec![];
+        };
+        let targets = se.get_fund_target_weights(fund_id);
+        let mut symbols = targets.keys().cloned().collect::<BTreeSet<_>>();
+
+        if let Some(stocks) = se.owned_stocks.0.get(&owner) {
+            symbols.extend(stocks.iter().map(|stock| stock.symbol.clone()));
+        }
+
+        let settings = &self.settings.funds;
+        let mut available_cash = se.funds.mapping[fund_id].cash.to_f64();
+        let mut orders = vec![];
+
+        for symbol in symbols {
+            let (Some(company), Some(price)) = (
+                se.listed_companies.mapping.get(&symbol),
+                se.prices.get_average_price(&symbol),
+            ) else {
+                continue;
+            };
+
+            if !se.is_tradeable(&symbol) {
+                continue;
+            }
+
+            let price = price.to_f64();
+            let held = se.owned_stocks.get_quantity(&owner, &symbol);
+            let target_value = targets.get(&symbol).cloned().unwrap_or(0.0) * nav;
+            let deviation = target_value - held as f64 * price;
+
+            if deviation.abs() < settings.rebalance_threshold * nav || price <= 0.0 {
+                continue;
+            }
+
+            let max_shares =
+                (company.get_free_float_shares() as f64 * settings.max_order_free_float) as u64;
+
+            let (order_side, shares) = if deviation > 0.0 {
+                let shares = ((deviation / price) as u64)
+                    .min(max_shares)
+                    .min((available_cash / price) as u64);
+                let shares = shares - shares % company.lot_size;
+
+                available_cash -= shares as f64 * price;
+
+                (OrderSide::Buy, shares)
+            } else {
+                let shares = ((-deviation / price) as u64).min(max_shares).min(held);
+
+                // The odd lots are only sold when leaving the position
+                if shares == held {
+                    (OrderSide::Sell, shares)
+                } else {
+                    (OrderSide::Sell, shares - shares % company.lot_size)
+                }
+            };
+
+            if shares == 0 {
+                continue;
+            }
+
+            orders.push(Order {
+                order_side,
+                order_type: OrderType::Market,
+                owner_id: owner,
+                shares,
+                status: OrderStatus::Init,
+                symbol,
+            });
+        }
+
+        orders
+    }
+
+    /// The funds rebalance once per day, on the first trading tick, through
+    /// the order book like the rest of the investors
+    pub(super) fn rebalance_funds(&mut self, se: &mut StockExchange, time: &TimeHandler) {
+        let current_day = time.get_virtual_day_formatted();
+
+        if self.progress.funds_rebalance_day.as_ref() == Some(&current_day) {
+            return;
+        }
+
+        self.progress.funds_rebalance_day = Some(current_day);
+
+        let fund_ids = se.funds.mapping.keys().cloned().collect::<Vec<_>>();
+
+        for fund_id in fund_ids {
+            let owner = StockOwner::Fund(fund_id);
+
+            se.orders_book.0.retain(|order| order.owner_id != owner);
+
+            for order in self.get_fund_rebalance_orders(se, &fund_id) {
+                if let Err(e) = se.place_order(&order, time) {
+                    debug!("Fund {:?} could not place an order: {:?}", fund_id, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::core::{stock_exchange::StockExchange, time::TimeHandler};
+
+use super::Simulation;
+
+impl Simulation {
+    /// While all the exchanges are closed, the time jumps to the next session
+    /// open. It stops at each midnight, so the daily steps of the skipped
+    /// days still run.
+    pub fn skip_closed_time(&self, se: &StockExchange, time: &mut TimeHandler) {
+        if !self.settings.skip_closed_time || se.can_trade_now(time) {
+            return;
+        }
+
+        let next_midnight = time.get_next_midnight_unix_timestamp();
+        let target = se
+            .get_next_open_timestamp(time)
+            .map_or(next_midnight, |next_open| next_open.min(next_midnight));
+
+        // A normal tick gets there anyway
+        if (target as f64) <= time.get_now_unix_timestamp() as f64 + time.get_tick_seconds() {
+            return;
+        }
+
+        time.jump_to(target);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        server::storage_wrappers::RedisPriceStorage, simulation::settings::SimulationSettings,
+        storage::memory::StorageMemoryImpl,
+    };
+
+    #[test]
+    fn test_skip_closed_time_stops_at_each_midnight() {
+        let settings = SimulationSettings {
+            skip_closed_time: true,
+            ..Default::default()
+        };
+        let se = StockExchange::new(settings.exchanges.list.clone());
+        let price_storage = RedisPriceStorage::with_storage(StorageMemoryImpl::default());
+        let simulation = Simulation::new([0; 32], settings, price_storage);
+        // 2024-12-20 17:00 HKT, a Friday after the close of every exchange
+        let mut time = TimeHandler::new(1_734_685_200, Some(1), 100);
+        let mut stops = vec![];
+
+        while !se.can_trade_now(&time) {
+            time.tick();
+            simulation.skip_closed_time(&se, &mut time);
+            stops.push(time.get_virtual_time_formatted());
+        }
+
+        // The weekend days are visited for their daily checks, and the
+        // pre-open of HK comes before the one of SH on Monday
+        assert_eq!(
+            stops,
+            [
+                "2024-12-21 00:00:00 HKT",
+                "2024-12-22 00:00:00 HKT",
+                "2024-12-23 00:00:00 HKT",
+                "2024-12-23 09:00:00 HKT",
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{core::stock_exchange::StockExchange, simulation::price_model::sample_standard_normal};
+
+use super::Simulation;
+
+// @settings
+const FAIR_VALUE_DAILY_VOLATILITY: f64 = 0.01;
+
+impl Simulation {
+    /// The fair values start at the first known price, and then move with the
+    /// daily news about each company
+    pub(super) fn update_fair_values(&mut self, se: &mut StockExchange) {
+        se.fair_values
+            .retain(|symbol, _| se.prices.0.contains_key(symbol));
+
+        for (symbol, price) in se.prices.0.iter() {
+            // The value of an ETF is the one of what it holds
+            if let Some(etf) = se.etfs.get_by_symbol(symbol) {
+                se.fair_values.insert(symbol.clone(), etf.inav);
+                continue;
+            }
+
+            // The secondary listings follow the primary one, below
+            if se.get_primary_symbol(symbol) != symbol {
+                continue;
+            }
+
+            match se.fair_values.get_mut(symbol) {
+                Some(fair_value) => {
+                    let news = sample_standard_normal(&mut self.r) * FAIR_VALUE_DAILY_VOLATILITY;
+
+                    *fair_value *= news.exp();
+                }
+                None => {
+                    if let Ok(average) = price.get_average() {
+                        se.fair_values.insert(symbol.clone(), average.to_f64());
+                    }
+                }
+            }
+        }
+
+        // The same company is worth the same in every exchange
+        for listed in se.listed_companies.mapping.values() {
+            let Some(primary_symbol) = &listed.primary_symbol else {
+                continue;
+            };
+            let (Some(fair_value), Some(exchange)) = (
+                se.fair_values.get(primary_symbol).cloned(),
+                se.exchanges.get(&listed.exchange),
+            ) else {
+                continue;
+            };
+            let Some(rate) = se
+                .fx_rates
+                .get_rate(&se.get_currency(), &exchange.settings.currency)
+            else {
+                continue;
+            };
+
+            se.fair_values
+                .insert(listed.symbol.clone(), fair_value * rate);
+        }
+    }
+}
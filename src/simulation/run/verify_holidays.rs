@@ -1,6 +1,9 @@
 use std::collections::BTreeSet;
 
-use crate::core::{stock_exchange::StockExchange, time::TimeHandler};
+use crate::{
+    core::{journal::JournalEvent, stock_exchange::StockExchange, time::TimeHandler},
+    simulation::holidays::load_holidays_file,
+};
 use rand::Rng;
 
 use super::Simulation;
@@ -11,10 +14,37 @@ impl Simulation {
         se: &mut StockExchange,
         time: &TimeHandler,
     ) -> Result<(), String> {
-        let current_year = time.get_virtual_year_formatted();
-        let current_holidays = se.holidays.get(&current_year);
+        let mut events = Vec::new();
+
+        // Each exchange has its own holidays, in its local year
+        for exchange in se.exchanges.values_mut() {
+            let current_year = exchange.get_local_year(time);
+
+            if exchange.holidays.contains_key(&current_year) {
+                continue;
+            }
+
+            // All the years of the calendar file are known in advance, and
+            // the random ones are only for the years it doesn't have
+            if let Some(path) = &exchange.settings.holidays_file {
+                for (year, year_holidays) in load_holidays_file(path)? {
+                    if exchange.holidays.contains_key(&year) {
+                        continue;
+                    }
+
+                    events.push(JournalEvent::HolidaysSet {
+                        exchange: exchange.settings.id.clone(),
+                        holidays: year_holidays.clone(),
+                        year: year.clone(),
+                    });
+                    exchange.holidays.insert(year, year_holidays);
+                }
+
+                if exchange.holidays.contains_key(&current_year) {
+                    continue;
+                }
+            }
 
-        if current_holidays.is_none() {
             let mut year_holidays = BTreeSet::<String>::new();
 
             // https://en.wikipedia.org/wiki/List_of_countries_by_number_of_public_holidays
@@ -37,9 +67,18 @@ impl Simulation {
                 }
             }
 
-            se.holidays.insert(current_year.clone(), year_holidays);
+            events.push(JournalEvent::HolidaysSet {
+                exchange: exchange.settings.id.clone(),
+                holidays: year_holidays.clone(),
+                year: current_year.clone(),
+            });
+            exchange
+                .holidays
+                .insert(current_year.clone(), year_holidays);
+        }
 
-            return Ok(());
+        for event in events {
+            se.record_event(event);
         }
 
         Ok(())
@@ -1,16 +1,44 @@
-use crate::core::{stock_exchange::StockExchange, time::TimeHandler};
+use crate::{
+    core::{investor::InvestorId, stock_exchange::StockExchange, time::TimeHandler},
+    simulation::price_model::sample_poisson,
+};
 use log::debug;
-use rand::Rng;
+use rand::{seq::SliceRandom, Rng};
 
 use super::Simulation;
 
 impl Simulation {
+    fn choose_heir(
+        &mut self,
+        se: &mut StockExchange,
+        investor_id: &InvestorId,
+        time: &TimeHandler,
+    ) -> Result<Option<InvestorId>, String> {
+        // @settings
+        match self.r.gen_range(0..10) {
+            0..=5 => {
+                let candidates = se
+                    .investors
+                    .mapping
+                    .keys()
+                    .filter(|id| *id != investor_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                Ok(candidates.choose(&mut self.r).cloned())
+            }
+            6..=7 => self.add_new_investor(se, time).map(Some),
+            _ => Ok(None),
+        }
+    }
+
     pub(super) fn verify_investors(
         &mut self,
         se: &mut StockExchange,
         time: &TimeHandler,
     ) -> Result<(), String> {
-        // - TODO: Check if any investor is too poor
+        self.process_investor_finances(se, time)?;
+
         let mut investors_to_remove = Vec::new();
 
         for investor in se.investors.mapping.values() {
@@ -25,12 +53,13 @@ impl Simulation {
                 continue;
             }
 
-            // @settings
-            let age_death_rate = (0.25 * investor_age / 365.0).ceil() as u32;
+            let death_rate = self
+                .settings
+                .demographics
+                .mortality
+                .get_daily_rate(investor_age);
 
-            let is_investor_dead = self.r.gen_ratio(age_death_rate, 100);
-
-            if is_investor_dead {
+            if self.r.gen_bool(death_rate) {
                 investors_to_remove.push(investor.id);
                 debug!(
                     "Removed investor in simulation because dead: {:?}",
@@ -41,16 +70,19 @@ impl Simulation {
         }
 
         for investor_id in investors_to_remove {
-            se.investors.mapping.remove(&investor_id);
+            let heir = self.choose_heir(se, &investor_id, time)?;
+
+            se.settle_estate(&investor_id, heir, time)?;
         }
 
-        let investors_to_add = self.r.gen_range(0..=10) - 7;
-        if investors_to_add > 0 {
-            for _ in 0..investors_to_add {
-                let new_investor = self.create_valid_new_investor(se, time)?;
+        let expected_entries = self
+            .settings
+            .demographics
+            .get_daily_entries(se.investors.mapping.len());
+        let investors_to_add = sample_poisson(expected_entries, &mut self.r);
 
-                se.investors.mapping.insert(new_investor.id, new_investor);
-            }
+        for _ in 0..investors_to_add {
+            self.add_new_investor(se, time)?;
         }
 
         Ok(())
@@ -0,0 +1,82 @@
+use crate::core::{
+    company::{CompanyStatus, DelistingReason},
+    money::{Money, RoundingMode},
+    stock_exchange::StockExchange,
+    time::TimeHandler,
+};
+use log::debug;
+use rand::Rng;
+
+use super::Simulation;
+
+impl Simulation {
+    pub(super) fn verify_listings(
+        &mut self,
+        se: &mut StockExchange,
+        time: &TimeHandler,
+    ) -> Result<(), String> {
+        let min_price = Money::from_f64(self.settings.delisting_min_price);
+        let mut companies_to_delist = Vec::new();
+
+        for symbol in se
+            .listed_companies
+            .mapping
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            // The ETFs are not companies, they stay listed, and the secondary
+            // listings follow the primary one
+            if se.is_etf(&symbol) || se.get_primary_symbol(&symbol) != &symbol {
+                continue;
+            }
+
+            let company = se.companies.mapping.get(&symbol).unwrap();
+
+            if company.status == CompanyStatus::Suspended {
+                if company.get_status_days(time) > self.settings.max_suspension_days {
+                    companies_to_delist.push((symbol, DelistingReason::Suspension));
+                    continue;
+                }
+
+                // @settings
+                if self.r.gen_ratio(1, 30) {
+                    se.resume_trading(&symbol, time)?;
+                }
+
+                continue;
+            }
+
+            let average_price = se.prices.get_average_price(&symbol);
+
+            if average_price.is_some_and(|price| price.value < min_price) {
+                companies_to_delist.push((symbol, DelistingReason::LowPrice));
+                continue;
+            }
+
+            // @settings
+            if self.r.gen_ratio(1, 2000) {
+                se.suspend_trading(&symbol, time)?;
+                continue;
+            }
+
+            // @settings
+            if let Some(price) = average_price.filter(|_| self.r.gen_ratio(1, 5000)) {
+                let premium = Money::gen_from_range(&mut self.r, (1.1, 1.5));
+                let offer_price = price
+                    .checked_scale(premium, RoundingMode::Bankers)
+                    .map_err(|e| e.to_string())?;
+
+                companies_to_delist.push((symbol, DelistingReason::Privatization { offer_price }));
+            }
+        }
+
+        for (symbol, reason) in companies_to_delist {
+            debug!("Delisting company {}: {:?}", symbol, reason);
+
+            se.delist(&symbol, reason, time)?;
+        }
+
+        Ok(())
+    }
+}
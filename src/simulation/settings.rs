@@ -1,39 +1,94 @@
-use crate::storage_interface::StorageConfigFile;
-use serde::Deserialize;
+use super::{
+    demographics::DemographicsSettings,
+    etfs::EtfsSettings,
+    exchanges::ExchangesSettings,
+    funds::FundsSettings,
+    fx::FxSettings,
+    investor_profile::InvestorProfileSettings,
+    price_model::{FactorLoadings, PriceModelSettings},
+    strategy::StrategyMix,
+    wealth::WealthSettings,
+};
+use crate::{
+    core::{company::CompanySymbol, market_index::MarketIndexSettings},
+    storage_interface::StorageConfigFile,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone)]
+/// The settings are saved in the journal, to replay the simulation with them
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SimulationSettings {
     pub address: String,
+    pub delisting_min_price: f64,
+    pub demographics: DemographicsSettings,
+    pub etfs: EtfsSettings,
+    pub exchanges: ExchangesSettings,
+    pub factor_loadings: FactorLoadings,
     pub flush_storage: bool,
+    pub funds: FundsSettings,
+    pub fx: FxSettings,
+    pub index: MarketIndexSettings,
+    pub investor_profile: InvestorProfileSettings,
     pub max_duration_seconds: Option<u64>,
     pub max_investor_age: u64,
+    pub max_ipo_subscriptions_per_tick: u64,
     pub max_orders_per_tick: u64,
+    pub max_suspension_days: u64,
     pub port: String,
+    pub price_impact: f64,
+    pub price_model: PriceModelSettings,
     pub prometheus_job_name: String,
     pub prometheus_url: String,
     pub redis_url: String,
+    /** Jump to the next session open, or the next day, while all the exchanges are closed */
+    pub skip_closed_time: bool,
+    pub strategy_mix: StrategyMix,
+    pub symbol_price_models: BTreeMap<String, PriceModelSettings>,
     pub time_to_wait_millis: u64,
+    pub wealth: WealthSettings,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct SimulationSettingsBuilder {
     pub address: Option<String>,
+    pub delisting_min_price: Option<f64>,
+    pub demographics: Option<DemographicsSettings>,
+    pub etfs: Option<EtfsSettings>,
+    pub exchanges: Option<ExchangesSettings>,
+    pub factor_loadings: Option<FactorLoadings>,
     pub flush_storage: Option<bool>,
+    pub funds: Option<FundsSettings>,
+    pub fx: Option<FxSettings>,
+    pub index: Option<MarketIndexSettings>,
+    pub investor_profile: Option<InvestorProfileSettings>,
     pub max_duration_seconds: Option<u64>,
     pub max_investor_age: Option<u64>,
+    pub max_ipo_subscriptions_per_tick: Option<u64>,
     pub max_orders_per_tick: Option<u64>,
+    pub max_suspension_days: Option<u64>,
     pub port: Option<String>,
+    pub price_impact: Option<f64>,
+    pub price_model: Option<PriceModelSettings>,
     pub prometheus_job_name: Option<String>,
     pub prometheus_url: Option<String>,
     pub redis_url: Option<String>,
+    pub skip_closed_time: Option<bool>,
+    pub strategy_mix: Option<StrategyMix>,
+    pub symbol_price_models: Option<BTreeMap<String, PriceModelSettings>>,
     pub time_to_wait_millis: Option<u64>,
+    pub wealth: Option<WealthSettings>,
 }
 
 const SETTINGS_FILE_NAME: &str = "market-sim-settings.json";
 
 const DEFAULT_ADDRESS: &str = "0.0.0.0";
+const DEFAULT_DELISTING_MIN_PRICE: f64 = 0.1;
 const DEFAULT_MAX_INVESTOR_AGE: u64 = 100;
+const DEFAULT_MAX_IPO_SUBSCRIPTIONS_PER_TICK: u64 = 20;
+const DEFAULT_MAX_SUSPENSION_DAYS: u64 = 90;
 const DEFAULT_ORDERS_PER_TICK: u64 = 4000;
+const DEFAULT_PRICE_IMPACT: f64 = 0.1;
 const DEFAULT_PROMETHEUS_JOB_NAME: &str = "market-sim";
 const DEFAULT_PROMETHEUS_URL: &str = "http://localhost:9090";
 const DEFAULT_REDIS_URL: &str = "redis://127.0.0.1";
@@ -44,18 +99,46 @@ impl SimulationSettingsBuilder {
     fn merge(&self, other: &Self) -> Self {
         Self {
             address: other.address.clone().or(self.address.clone()),
+            delisting_min_price: other.delisting_min_price.or(self.delisting_min_price),
+            demographics: other.demographics.clone().or(self.demographics.clone()),
+            etfs: other.etfs.clone().or(self.etfs.clone()),
+            exchanges: other.exchanges.clone().or(self.exchanges.clone()),
+            factor_loadings: other
+                .factor_loadings
+                .clone()
+                .or(self.factor_loadings.clone()),
             flush_storage: other.flush_storage.or(self.flush_storage),
+            funds: other.funds.clone().or(self.funds.clone()),
+            fx: other.fx.clone().or(self.fx.clone()),
+            index: other.index.clone().or(self.index.clone()),
+            investor_profile: other
+                .investor_profile
+                .clone()
+                .or(self.investor_profile.clone()),
             max_orders_per_tick: other.max_orders_per_tick.or(self.max_orders_per_tick),
+            max_suspension_days: other.max_suspension_days.or(self.max_suspension_days),
             port: other.port.clone().or(self.port.clone()),
+            price_impact: other.price_impact.or(self.price_impact),
+            price_model: other.price_model.clone().or(self.price_model.clone()),
             max_investor_age: other.max_investor_age.or(self.max_investor_age),
+            max_ipo_subscriptions_per_tick: other
+                .max_ipo_subscriptions_per_tick
+                .or(self.max_ipo_subscriptions_per_tick),
             redis_url: other.redis_url.clone().or(self.redis_url.clone()),
             prometheus_job_name: other
                 .prometheus_job_name
                 .clone()
                 .or(self.prometheus_job_name.clone()),
             prometheus_url: other.prometheus_url.clone().or(self.prometheus_url.clone()),
+            skip_closed_time: other.skip_closed_time.or(self.skip_closed_time),
+            strategy_mix: other.strategy_mix.clone().or(self.strategy_mix.clone()),
+            symbol_price_models: other
+                .symbol_price_models
+                .clone()
+                .or(self.symbol_price_models.clone()),
             max_duration_seconds: other.max_duration_seconds.or(self.max_duration_seconds),
             time_to_wait_millis: other.time_to_wait_millis.or(self.time_to_wait_millis),
+            wealth: other.wealth.clone().or(self.wealth.clone()),
         }
     }
 
@@ -78,7 +161,42 @@ impl SimulationSettingsBuilder {
             builder = builder.merge(&file_settings);
         }
 
-        Ok(builder.into())
+        let settings: SimulationSettings = builder.into();
+
+        settings.factor_loadings.verify()?;
+        settings.price_model.verify()?;
+        settings.strategy_mix.verify()?;
+        settings.investor_profile.verify()?;
+        settings.wealth.verify()?;
+        settings.demographics.verify()?;
+        settings.funds.verify()?;
+        settings.etfs.verify()?;
+        settings.fx.verify()?;
+        settings.exchanges.verify(&settings.fx)?;
+
+        if settings.price_impact < 0.0 {
+            return Err("The price impact can't be negative".to_string());
+        }
+
+        if settings.index.max_constituents == 0 {
+            return Err("The index needs at least one constituent".to_string());
+        }
+
+        for (symbol, price_model) in settings.symbol_price_models.iter() {
+            price_model
+                .verify()
+                .map_err(|e| format!("Invalid price model for {}: {}", symbol, e))?;
+        }
+
+        Ok(settings)
+    }
+}
+
+impl SimulationSettings {
+    pub fn get_price_model(&self, symbol: &CompanySymbol) -> &PriceModelSettings {
+        self.symbol_price_models
+            .get(&symbol.to_string())
+            .unwrap_or(&self.price_model)
     }
 }
 
@@ -86,12 +204,31 @@ impl From<SimulationSettingsBuilder> for SimulationSettings {
     fn from(builder: SimulationSettingsBuilder) -> Self {
         Self {
             address: builder.address.unwrap_or(DEFAULT_ADDRESS.to_string()),
+            delisting_min_price: builder
+                .delisting_min_price
+                .unwrap_or(DEFAULT_DELISTING_MIN_PRICE),
+            demographics: builder.demographics.unwrap_or_default(),
+            etfs: builder.etfs.unwrap_or_default(),
+            exchanges: builder.exchanges.unwrap_or_default(),
+            factor_loadings: builder.factor_loadings.unwrap_or_default(),
             flush_storage: builder.flush_storage.unwrap_or(false),
+            funds: builder.funds.unwrap_or_default(),
+            fx: builder.fx.unwrap_or_default(),
+            index: builder.index.unwrap_or_default(),
+            investor_profile: builder.investor_profile.unwrap_or_default(),
             max_orders_per_tick: builder
                 .max_orders_per_tick
                 .unwrap_or(DEFAULT_ORDERS_PER_TICK),
+            max_suspension_days: builder
+                .max_suspension_days
+                .unwrap_or(DEFAULT_MAX_SUSPENSION_DAYS),
             port: builder.port.unwrap_or(DEFAULT_PORT.to_string()),
+            price_impact: builder.price_impact.unwrap_or(DEFAULT_PRICE_IMPACT),
+            price_model: builder.price_model.unwrap_or_default(),
             max_investor_age: builder.max_investor_age.unwrap_or(DEFAULT_MAX_INVESTOR_AGE),
+            max_ipo_subscriptions_per_tick: builder
+                .max_ipo_subscriptions_per_tick
+                .unwrap_or(DEFAULT_MAX_IPO_SUBSCRIPTIONS_PER_TICK),
             prometheus_job_name: builder
                 .prometheus_job_name
                 .unwrap_or(DEFAULT_PROMETHEUS_JOB_NAME.to_string()),
@@ -99,8 +236,12 @@ impl From<SimulationSettingsBuilder> for SimulationSettings {
                 .prometheus_url
                 .unwrap_or(DEFAULT_PROMETHEUS_URL.to_string()),
             redis_url: builder.redis_url.unwrap_or(DEFAULT_REDIS_URL.to_string()),
+            skip_closed_time: builder.skip_closed_time.unwrap_or(false),
+            strategy_mix: builder.strategy_mix.unwrap_or_default(),
+            symbol_price_models: builder.symbol_price_models.unwrap_or_default(),
             max_duration_seconds: builder.max_duration_seconds,
             time_to_wait_millis: builder.time_to_wait_millis.unwrap_or(DEFAULT_TIME_TO_WAIT),
+            wealth: builder.wealth.unwrap_or_default(),
         }
     }
 }
@@ -109,15 +250,32 @@ impl Default for SimulationSettings {
     fn default() -> Self {
         Self {
             address: DEFAULT_ADDRESS.to_string(),
+            delisting_min_price: DEFAULT_DELISTING_MIN_PRICE,
+            demographics: Default::default(),
+            etfs: Default::default(),
+            exchanges: Default::default(),
+            factor_loadings: Default::default(),
             flush_storage: false,
+            funds: Default::default(),
+            fx: Default::default(),
+            index: Default::default(),
+            investor_profile: Default::default(),
             max_duration_seconds: None,
             max_investor_age: DEFAULT_MAX_INVESTOR_AGE,
+            max_ipo_subscriptions_per_tick: DEFAULT_MAX_IPO_SUBSCRIPTIONS_PER_TICK,
             max_orders_per_tick: DEFAULT_ORDERS_PER_TICK,
+            max_suspension_days: DEFAULT_MAX_SUSPENSION_DAYS,
             port: DEFAULT_PORT.to_string(),
+            price_impact: DEFAULT_PRICE_IMPACT,
+            price_model: Default::default(),
             prometheus_job_name: DEFAULT_PROMETHEUS_JOB_NAME.to_string(),
             prometheus_url: DEFAULT_PROMETHEUS_URL.to_string(),
             redis_url: DEFAULT_REDIS_URL.to_string(),
+            skip_closed_time: false,
+            strategy_mix: Default::default(),
+            symbol_price_models: Default::default(),
             time_to_wait_millis: DEFAULT_TIME_TO_WAIT,
+            wealth: Default::default(),
         }
     }
 }
@@ -0,0 +1,41 @@
+use super::{decide_by_score, InvestorStrategy, OrderIntent, SymbolScores};
+use crate::core::{investor::Investor, stock_exchange::StockExchange};
+use rand::rngs::StdRng;
+
+// @settings
+const MISPRICING_THRESHOLD: f64 = 0.1;
+const MAX_LOTS_FRACTION: f64 = 0.25;
+
+/// Buys the symbols trading below their fair value and sells the held ones
+/// trading above it
+#[derive(Default)]
+pub struct FundamentalStrategy {
+    scores: SymbolScores,
+}
+
+impl InvestorStrategy for FundamentalStrategy {
+    fn update(&mut self, se: &StockExchange) {
+        self.scores.update(se, |symbol| {
+            let fair_value = se.fair_values.get(symbol).filter(|v| **v > 0.0)?;
+            let price = se.prices.get_average_price(symbol)?.to_f64();
+
+            Some(price / fair_value - 1.0)
+        });
+    }
+
+    fn decide(
+        &self,
+        investor: &Investor,
+        se: &StockExchange,
+        rng: &mut StdRng,
+    ) -> Option<OrderIntent> {
+        decide_by_score(
+            investor,
+            se,
+            rng,
+            &self.scores,
+            MISPRICING_THRESHOLD,
+            MAX_LOTS_FRACTION,
+        )
+    }
+}
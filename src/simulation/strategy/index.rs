@@ -0,0 +1,111 @@
+use super::{
+    can_afford_lot, gen_lots, get_affordable_lots, get_held_lots, get_held_symbols,
+    get_listing_lot, InvestorStrategy, ListingLot, OrderIntent,
+};
+use crate::core::{
+    company::CompanySymbol, etf::EtfTracking, investor::Investor, order::OrderSide,
+    stock_exchange::StockExchange,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+// @settings
+const MAX_LOTS_FRACTION: f64 = 0.1;
+// @settings
+const ETF_PROBABILITY: f64 = 0.3;
+
+fn get_index_etfs(se: &StockExchange) -> Vec<CompanySymbol> {
+    se.etfs
+        .mapping
+        .values()
+        .filter(|etf| matches!(etf.tracking, EtfTracking::Index))
+        .map(|etf| etf.symbol.clone())
+        .collect()
+}
+
+/// Tracks the market index: sells the holdings that are not constituents and
+/// buys constituents in proportion to their weight, or the ETFs that track
+/// the index
+#[derive(Default)]
+pub struct IndexStrategy {
+    /** Lots of the tradeable constituents, with their market cap in the index */
+    constituents: Vec<(ListingLot, f64)>,
+    index_etfs: Vec<CompanySymbol>,
+}
+
+impl InvestorStrategy for IndexStrategy {
+    fn update(&mut self, se: &StockExchange) {
+        self.constituents = se
+            .index
+            .constituents
+            .iter()
+            .filter(|(symbol, _)| se.is_tradeable(symbol))
+            .filter_map(|(symbol, shares)| {
+                let price = se.prices.get_average_price(symbol)?.to_f64();
+                let lot = get_listing_lot(se, symbol)?;
+
+                Some((lot, price * *shares as f64))
+            })
+            .collect();
+        self.index_etfs = get_index_etfs(se);
+    }
+
+    fn decide(
+        &self,
+        investor: &Investor,
+        se: &StockExchange,
+        rng: &mut StdRng,
+    ) -> Option<OrderIntent> {
+        let index_etfs = &self.index_etfs;
+        let non_constituent = get_held_symbols(investor, se).into_iter().find(|symbol| {
+            !se.index.constituents.contains_key(symbol) && !index_etfs.contains(symbol)
+        });
+
+        if let Some(symbol) = non_constituent {
+            return Some(OrderIntent {
+                lots: get_held_lots(investor, se, &symbol),
+                side: OrderSide::Sell,
+                symbol,
+            });
+        }
+
+        let etf = index_etfs
+            .choose(rng)
+            .filter(|symbol| can_afford_lot(investor, se, symbol))
+            .cloned();
+
+        if let Some(symbol) = etf.filter(|_| rng.gen_bool(ETF_PROBABILITY)) {
+            return Some(OrderIntent {
+                lots: gen_lots(
+                    rng,
+                    get_affordable_lots(investor, se, &symbol),
+                    MAX_LOTS_FRACTION,
+                ),
+                side: OrderSide::Buy,
+                symbol,
+            });
+        }
+
+        let candidates = self
+            .constituents
+            .iter()
+            .filter(|(lot, _)| lot.is_affordable(investor, se))
+            .collect::<Vec<_>>();
+        let symbol = candidates
+            .choose_weighted(rng, |(_, market_cap)| *market_cap)
+            .ok()?
+            .0
+            .symbol
+            .clone();
+        let lots = gen_lots(
+            rng,
+            get_affordable_lots(investor, se, &symbol),
+            MAX_LOTS_FRACTION,
+        );
+
+        Some(OrderIntent {
+            lots,
+            side: OrderSide::Buy,
+            symbol,
+        })
+    }
+}
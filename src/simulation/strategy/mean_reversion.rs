@@ -0,0 +1,38 @@
+use super::{decide_by_score, get_trend, InvestorStrategy, OrderIntent, SymbolScores};
+use crate::core::{investor::Investor, stock_exchange::StockExchange};
+use rand::rngs::StdRng;
+
+// @settings
+const LOOKBACK_TICKS: usize = 160;
+const DEVIATION_THRESHOLD: f64 = 0.05;
+const MAX_LOTS_FRACTION: f64 = 0.25;
+
+/// Buys the symbols that fell the most recently and sells the held ones that
+/// rose, expecting the moves to revert
+#[derive(Default)]
+pub struct MeanReversionStrategy {
+    scores: SymbolScores,
+}
+
+impl InvestorStrategy for MeanReversionStrategy {
+    fn update(&mut self, se: &StockExchange) {
+        self.scores
+            .update(se, |symbol| get_trend(se, symbol, LOOKBACK_TICKS));
+    }
+
+    fn decide(
+        &self,
+        investor: &Investor,
+        se: &StockExchange,
+        rng: &mut StdRng,
+    ) -> Option<OrderIntent> {
+        decide_by_score(
+            investor,
+            se,
+            rng,
+            &self.scores,
+            DEVIATION_THRESHOLD,
+            MAX_LOTS_FRACTION,
+        )
+    }
+}
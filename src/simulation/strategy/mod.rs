@@ -0,0 +1,678 @@
+use crate::core::{
+    company::CompanySymbol,
+    investor::{Investor, InvestorStrategyKind, OrderStyle},
+    money::Money,
+    order::{OrderSide, OrderType},
+    stock::StockOwner,
+    stock_exchange::{ExchangeId, StockExchange},
+};
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+mod fundamental;
+mod index;
+mod mean_reversion;
+mod momentum;
+mod noise;
+
+pub use fundamental::FundamentalStrategy;
+pub use index::IndexStrategy;
+pub use mean_reversion::MeanReversionStrategy;
+pub use momentum::MomentumStrategy;
+pub use noise::NoiseStrategy;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderIntent {
+    pub lots: u64,
+    pub side: OrderSide,
+    pub symbol: CompanySymbol,
+}
+
+pub trait InvestorStrategy {
+    /// Reads what the strategy needs of the market once per tick, before the
+    /// investors decide, since it's the same for all of them
+    fn update(&mut self, se: &StockExchange);
+
+    /// Decides the order that the investor places in this tick, if any
+    fn decide(
+        &self,
+        investor: &Investor,
+        se: &StockExchange,
+        rng: &mut StdRng,
+    ) -> Option<OrderIntent>;
+}
+
+impl InvestorStrategyKind {
+    pub fn build(&self) -> Box<dyn InvestorStrategy> {
+        match self {
+            InvestorStrategyKind::Fundamental => Box::<FundamentalStrategy>::default(),
+            InvestorStrategyKind::Index => Box::<IndexStrategy>::default(),
+            InvestorStrategyKind::MeanReversion => Box::<MeanReversionStrategy>::default(),
+            InvestorStrategyKind::Momentum => Box::<MomentumStrategy>::default(),
+            InvestorStrategyKind::Noise => Box::<NoiseStrategy>::default(),
+        }
+    }
+}
+
+/// Relative weights of each strategy in the population of investors
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StrategyMix {
+    pub fundamental: f64,
+    pub index: f64,
+    pub mean_reversion: f64,
+    pub momentum: f64,
+    pub noise: f64,
+}
+
+impl Default for StrategyMix {
+    fn default() -> Self {
+        Self {
+            fundamental: 0.15,
+            index: 0.05,
+            mean_reversion: 0.15,
+            momentum: 0.15,
+            noise: 0.5,
+        }
+    }
+}
+
+impl StrategyMix {
+    fn get_weights(&self) -> [(InvestorStrategyKind, f64); 5] {
+        [
+            (InvestorStrategyKind::Fundamental, self.fundamental),
+            (InvestorStrategyKind::Index, self.index),
+            (InvestorStrategyKind::MeanReversion, self.mean_reversion),
+            (InvestorStrategyKind::Momentum, self.momentum),
+            (InvestorStrategyKind::Noise, self.noise),
+        ]
+    }
+
+    pub fn verify(&self) -> Result<(), String> {
+        let weights = self.get_weights();
+
+        if weights.iter().any(|(_, weight)| *weight < 0.0) {
+            return Err("The strategy weights can't be negative".to_string());
+        }
+
+        if weights.iter().map(|(_, weight)| weight).sum::<f64>() <= 0.0 {
+            return Err("At least one strategy needs a positive weight".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn gen_strategy(&self, rng: &mut StdRng) -> InvestorStrategyKind {
+        let weights = self.get_weights();
+        let total = weights.iter().map(|(_, weight)| weight).sum::<f64>();
+        let mut remaining = rng.gen_range(0.0..total);
+
+        for (kind, weight) in weights {
+            if remaining < weight {
+                return kind;
+            }
+
+            remaining -= weight;
+        }
+
+        InvestorStrategyKind::Noise
+    }
+}
+
+// @settings
+const HORIZON_REFERENCE_DAYS: f64 = 30.0;
+
+/// Adjusts the intent of the strategy to the profile of the investor, and
+/// picks the order type. Returns `None` when the investor doesn't act on it.
+pub fn apply_profile(
+    intent: OrderIntent,
+    investor: &Investor,
+    se: &StockExchange,
+    rng: &mut StdRng,
+) -> Option<(OrderIntent, OrderType)> {
+    let profile = &investor.profile;
+    let market_price = se.prices.get_average_price(&intent.symbol)?;
+    let mut intent = intent;
+
+    match intent.side {
+        OrderSide::Sell => {
+            let average_cost = se
+                .owned_stocks
+                .get_average_cost(&StockOwner::Investor(investor.id), &intent.symbol);
+            let is_loss = average_cost.is_some_and(|cost| market_price.value < cost.value);
+
+            // Realizing a loss is avoided more the more loss averse the investor is
+            if is_loss && !rng.gen_bool(1.0 / profile.loss_aversion.max(1.0)) {
+                return None;
+            }
+
+            let horizon_ratio =
+                (HORIZON_REFERENCE_DAYS / profile.investment_horizon_days.max(1) as f64).sqrt();
+
+            if !rng.gen_bool(horizon_ratio.min(1.0)) {
+                return None;
+            }
+        }
+        OrderSide::Buy => {
+            intent.lots = ((intent.lots as f64 * profile.risk_tolerance).round() as u64).max(1);
+        }
+    }
+
+    let order_type = match profile.order_style {
+        OrderStyle::Market => OrderType::Market,
+        OrderStyle::Limit => OrderType::Limit {
+            price: profile
+                .get_limit_price(&intent.side, &market_price)
+                .ok()?
+                .value
+                .to_string(),
+        },
+    };
+
+    Some((intent, order_type))
+}
+
+fn get_tradeable_symbols(se: &StockExchange) -> Vec<CompanySymbol> {
+    se.listed_companies
+        .mapping
+        .keys()
+        .filter(|symbol| se.is_tradeable(symbol))
+        .cloned()
+        .collect()
+}
+
+fn get_held_lots(investor: &Investor, se: &StockExchange, symbol: &CompanySymbol) -> u64 {
+    let Some(company) = se.listed_companies.mapping.get(symbol) else {
+        return 0;
+    };
+
+    se.owned_stocks
+        .get_quantity(&StockOwner::Investor(investor.id), symbol)
+        / company.lot_size
+}
+
+/// Tradeable symbols of which the investor holds at least one lot
+fn get_held_symbols(investor: &Investor, se: &StockExchange) -> Vec<CompanySymbol> {
+    let Some(stocks) = se.owned_stocks.0.get(&StockOwner::Investor(investor.id)) else {
+        return vec![];
+    };
+
+    // The shares of each symbol are added in one pass, since the holdings
+    // can have many entries
+    let mut quantities: BTreeMap<&CompanySymbol, u64> = BTreeMap::new();
+
+    for stock in stocks {
+        *quantities.entry(&stock.symbol).or_default() += stock.quantity;
+    }
+
+    quantities
+        .into_iter()
+        .filter(|(symbol, quantity)| {
+            se.is_tradeable(symbol)
+                && se.can_investor_trade(investor, symbol)
+                && se
+                    .listed_companies
+                    .mapping
+                    .get(*symbol)
+                    .is_some_and(|company| quantity / company.lot_size > 0)
+        })
+        .map(|(symbol, _)| symbol.clone())
+        .collect()
+}
+
+/// Lot of a listing, whose price is the same for all the investors
+struct ListingLot {
+    exchange: ExchangeId,
+    price: Money,
+    symbol: CompanySymbol,
+}
+
+impl ListingLot {
+    /// Whether the investor can trade the listing and the cash covers the lot
+    fn is_affordable(&self, investor: &Investor, se: &StockExchange) -> bool {
+        se.can_investor_trade_in(investor, &self.exchange)
+            && investor.get_cash(&self.price.currency).value >= self.price.value
+    }
+}
+
+fn get_listing_lot(se: &StockExchange, symbol: &CompanySymbol) -> Option<ListingLot> {
+    let company = se.listed_companies.mapping.get(symbol)?;
+    let ask = se.prices.get_ask_price(symbol)?;
+    let price = ask
+        .checked_mul(company.lot_size)
+        .ok()
+        .filter(|lot_price| !lot_price.value.is_zero())?;
+
+    Some(ListingLot {
+        exchange: company.exchange.clone(),
+        price,
+        symbol: symbol.clone(),
+    })
+}
+
+fn get_tradeable_lots(se: &StockExchange) -> Vec<ListingLot> {
+    get_tradeable_symbols(se)
+        .iter()
+        .filter_map(|symbol| get_listing_lot(se, symbol))
+        .collect()
+}
+
+/// Price of a lot of the listing, if the investor can trade it
+fn get_lot_price(investor: &Investor, se: &StockExchange, symbol: &CompanySymbol) -> Option<Money> {
+    if !se.can_investor_trade(investor, symbol) {
+        return None;
+    }
+
+    get_listing_lot(se, symbol).map(|lot| lot.price)
+}
+
+fn get_affordable_lots(investor: &Investor, se: &StockExchange, symbol: &CompanySymbol) -> u64 {
+    let Some(lot_price) = get_lot_price(investor, se, symbol) else {
+        return 0;
+    };
+
+    // The listings of other exchanges are paid with the cash in their currency
+    (investor.get_cash(&lot_price.currency).value / lot_price.value)
+        .floor()
+        .try_into()
+        .unwrap_or(0)
+}
+
+/// Whether the cash covers a lot, without dividing as counting them does
+fn can_afford_lot(investor: &Investor, se: &StockExchange, symbol: &CompanySymbol) -> bool {
+    get_listing_lot(se, symbol).is_some_and(|lot| lot.is_affordable(investor, se))
+}
+
+/// Sum of the last `lookback` log returns of the symbol
+fn get_trend(se: &StockExchange, symbol: &CompanySymbol, lookback: usize) -> Option<f64> {
+    let returns = se.returns.0.get(symbol)?;
+
+    if returns.len() < lookback {
+        return None;
+    }
+
+    Some(returns.iter().rev().take(lookback).sum())
+}
+
+/// Random number of lots, up to a fraction of the available ones
+fn gen_lots(rng: &mut StdRng, available_lots: u64, max_fraction: f64) -> u64 {
+    let max_lots = ((available_lots as f64 * max_fraction).ceil() as u64).max(1);
+
+    rng.gen_range(1..=max_lots.min(available_lots))
+}
+
+/// Score of each tradeable symbol, which is the same for all the investors
+#[derive(Default)]
+struct SymbolScores {
+    by_symbol: BTreeMap<CompanySymbol, f64>,
+    /** Ascending by score, and by symbol between the same scores */
+    ranked: Vec<(CompanySymbol, f64)>,
+}
+
+impl SymbolScores {
+    fn update(&mut self, se: &StockExchange, get_score: impl Fn(&CompanySymbol) -> Option<f64>) {
+        self.by_symbol = get_tradeable_symbols(se)
+            .into_iter()
+            .filter_map(|symbol| get_score(&symbol).map(|score| (symbol, score)))
+            .collect();
+        self.ranked = self
+            .by_symbol
+            .iter()
+            .map(|(symbol, score)| (symbol.clone(), *score))
+            .collect();
+        self.ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+    }
+}
+
+/// Sells the held symbol with the highest score above the threshold, or buys
+/// the tradeable one with the lowest score below the negative threshold
+fn decide_by_score(
+    investor: &Investor,
+    se: &StockExchange,
+    rng: &mut StdRng,
+    scores: &SymbolScores,
+    threshold: f64,
+    max_fraction: f64,
+) -> Option<OrderIntent> {
+    let to_sell = get_held_symbols(investor, se)
+        .into_iter()
+        .filter_map(|symbol| {
+            let score = *scores.by_symbol.get(&symbol)?;
+
+            Some((symbol, score))
+        })
+        .filter(|(_, score)| *score > threshold)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+
+    if let Some((symbol, _)) = to_sell {
+        let lots = gen_lots(rng, get_held_lots(investor, se, &symbol), max_fraction);
+
+        return Some(OrderIntent {
+            lots,
+            side: OrderSide::Sell,
+            symbol,
+        });
+    }
+
+    // The first affordable one in the ranking has the lowest score
+    let (symbol, _) = scores
+        .ranked
+        .iter()
+        .find(|(symbol, score)| *score < -threshold && can_afford_lot(investor, se, symbol))?;
+    let lots = gen_lots(rng, get_affordable_lots(investor, se, symbol), max_fraction);
+
+    Some(OrderIntent {
+        lots,
+        side: OrderSide::Buy,
+        symbol: symbol.clone(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{
+        company::ListedCompany,
+        etf::EtfTracking,
+        investor::{InvestorId, InvestorProfile},
+        money::Currency,
+        stock_exchange::ExchangeId,
+        test_fixtures::{
+            add_holding, add_listing, build_investor, build_listing, build_money, build_money_in,
+            build_symbol,
+        },
+    };
+    use rand::SeedableRng;
+    use std::collections::VecDeque;
+
+    /// FOO and BAR are listed at 10 with lots of 10 shares. The investor holds
+    /// 10 lots of each and has cash for 100 more.
+    fn build_exchange() -> (StockExchange, Investor) {
+        let mut se = StockExchange::default();
+        let investor_id = InvestorId::new(&InvestorId::init());
+
+        for symbol in ["FOO", "BAR"].map(build_symbol) {
+            let price = build_money(10.0);
+            let listing = build_listing(&symbol, &se.home_exchange);
+
+            add_listing(&mut se, listing, price);
+            add_holding(
+                &mut se,
+                StockOwner::Investor(investor_id),
+                &symbol,
+                price,
+                100,
+            );
+        }
+
+        (se, build_investor(investor_id, build_money(10_000.0)))
+    }
+
+    /// Side and symbol that the strategy decides
+    fn decide(
+        strategy: InvestorStrategyKind,
+        investor: &Investor,
+        se: &StockExchange,
+        seed: u8,
+    ) -> Option<(OrderSide, String)> {
+        let mut strategy = strategy.build();
+
+        strategy.update(se);
+        strategy
+            .decide(investor, se, &mut StdRng::from_seed([seed; 32]))
+            .map(|intent| (intent.side, intent.symbol.to_string()))
+    }
+
+    /// FOO rose on every one of the last ticks, and BAR fell
+    fn set_trends(se: &mut StockExchange) {
+        for (symbol, tick_return) in [("FOO", 0.001), ("BAR", -0.001)] {
+            se.returns
+                .0
+                .insert(build_symbol(symbol), VecDeque::from(vec![tick_return; 200]));
+        }
+    }
+
+    #[test]
+    fn test_fundamental_strategy() {
+        let (mut se, investor) = build_exchange();
+
+        se.fair_values.insert(build_symbol("FOO"), 20.0);
+        se.fair_values.insert(build_symbol("BAR"), 5.0);
+
+        // The overvalued holding is sold before buying the undervalued one
+        assert_eq!(
+            decide(InvestorStrategyKind::Fundamental, &investor, &se, 0),
+            Some((OrderSide::Sell, "BAR".to_string()))
+        );
+
+        se.owned_stocks.0.clear();
+
+        assert_eq!(
+            decide(InvestorStrategyKind::Fundamental, &investor, &se, 0),
+            Some((OrderSide::Buy, "FOO".to_string()))
+        );
+
+        // Within the threshold, the prices are fair
+        se.fair_values.insert(build_symbol("FOO"), 10.5);
+
+        assert_eq!(
+            decide(InvestorStrategyKind::Fundamental, &investor, &se, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_foreign_listings_need_an_account() {
+        let (mut se, mut investor) = build_exchange();
+        let exchange_id = ExchangeId("SH".to_string());
+        let primary_symbol = build_symbol("FOO");
+        let symbol = primary_symbol.get_secondary(&exchange_id);
+        let listing = ListedCompany {
+            primary_symbol: Some(primary_symbol.clone()),
+            ..build_listing(&symbol, &exchange_id)
+        };
+
+        add_listing(&mut se, listing, build_money_in(Currency::Cny, 10.0));
+        se.fair_values.insert(symbol.clone(), 20.0);
+        se.owned_stocks.0.clear();
+
+        // Without an account, the listings of the other exchanges aren't
+        // considered
+        assert!(!se.can_investor_trade(&investor, &symbol));
+        assert!(se.can_investor_trade(&investor, &primary_symbol));
+        assert_eq!(
+            decide(InvestorStrategyKind::Fundamental, &investor, &se, 0),
+            None
+        );
+
+        // Nor with it, until there is cash in the currency of the exchange
+        investor.accounts.insert(exchange_id);
+
+        assert!(se.can_investor_trade(&investor, &symbol));
+        assert_eq!(
+            decide(InvestorStrategyKind::Fundamental, &investor, &se, 0),
+            None
+        );
+
+        investor
+            .foreign_cash
+            .insert(Currency::Cny, build_money_in(Currency::Cny, 1000.0));
+
+        assert_eq!(
+            decide(InvestorStrategyKind::Fundamental, &investor, &se, 0),
+            Some((OrderSide::Buy, symbol.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_momentum_strategy() {
+        let (mut se, investor) = build_exchange();
+
+        assert_eq!(
+            decide(InvestorStrategyKind::Momentum, &investor, &se, 0),
+            None
+        );
+
+        set_trends(&mut se);
+
+        assert_eq!(
+            decide(InvestorStrategyKind::Momentum, &investor, &se, 0),
+            Some((OrderSide::Sell, "BAR".to_string()))
+        );
+
+        se.owned_stocks.0.clear();
+
+        assert_eq!(
+            decide(InvestorStrategyKind::Momentum, &investor, &se, 0),
+            Some((OrderSide::Buy, "FOO".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_mean_reversion_strategy() {
+        let (mut se, investor) = build_exchange();
+
+        set_trends(&mut se);
+
+        assert_eq!(
+            decide(InvestorStrategyKind::MeanReversion, &investor, &se, 0),
+            Some((OrderSide::Sell, "FOO".to_string()))
+        );
+
+        se.owned_stocks.0.clear();
+
+        assert_eq!(
+            decide(InvestorStrategyKind::MeanReversion, &investor, &se, 0),
+            Some((OrderSide::Buy, "BAR".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_noise_strategy() {
+        let (mut se, mut investor) = build_exchange();
+        let sides = (0..20)
+            .filter_map(|seed| decide(InvestorStrategyKind::Noise, &investor, &se, seed))
+            .map(|(side, _)| side)
+            .collect::<Vec<_>>();
+
+        assert!(sides.contains(&OrderSide::Buy) && sides.contains(&OrderSide::Sell));
+
+        // Without cash the holdings are sold, and without them nothing is done
+        investor.liquid_cash = build_money(0.0);
+
+        assert!((0..20).all(|seed| {
+            decide(InvestorStrategyKind::Noise, &investor, &se, seed)
+                .is_some_and(|(side, _)| side == OrderSide::Sell)
+        }));
+
+        se.owned_stocks.0.clear();
+
+        assert_eq!(decide(InvestorStrategyKind::Noise, &investor, &se, 0), None);
+    }
+
+    #[test]
+    fn test_index_strategy() {
+        let (mut se, investor) = build_exchange();
+
+        se.index.constituents.insert(build_symbol("FOO"), 1000);
+
+        // The holdings outside of the index are sold first
+        assert_eq!(
+            decide(InvestorStrategyKind::Index, &investor, &se, 0),
+            Some((OrderSide::Sell, "BAR".to_string()))
+        );
+
+        se.owned_stocks.0.clear();
+        se.list_etf(
+            "Index ETF",
+            &build_symbol("IDX"),
+            EtfTracking::Index,
+            100,
+            10,
+        )
+        .unwrap();
+
+        let bought = (0..50)
+            .filter_map(|seed| decide(InvestorStrategyKind::Index, &investor, &se, seed))
+            .map(|(side, symbol)| {
+                assert_eq!(side, OrderSide::Buy);
+
+                symbol
+            })
+            .collect::<Vec<_>>();
+
+        assert!(bought
+            .iter()
+            .all(|symbol| symbol == "FOO" || symbol == "IDX"));
+        assert!(bought.contains(&"FOO".to_string()) && bought.contains(&"IDX".to_string()));
+    }
+
+    #[test]
+    fn test_gen_strategy() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let mix = StrategyMix {
+            fundamental: 0.0,
+            index: 1.0,
+            mean_reversion: 0.0,
+            momentum: 3.0,
+            noise: 0.0,
+        };
+        let kinds = (0..1000)
+            .map(|_| mix.gen_strategy(&mut rng))
+            .collect::<Vec<_>>();
+        let momentum = kinds
+            .iter()
+            .filter(|kind| **kind == InvestorStrategyKind::Momentum)
+            .count();
+
+        assert!(kinds
+            .iter()
+            .all(|kind| *kind == InvestorStrategyKind::Momentum
+                || *kind == InvestorStrategyKind::Index));
+        assert!((700..800).contains(&momentum));
+
+        assert!(StrategyMix {
+            noise: -1.0,
+            ..Default::default()
+        }
+        .verify()
+        .is_err());
+    }
+
+    #[test]
+    fn test_apply_profile_risk_sizing() {
+        let (se, investor) = build_exchange();
+        let mut rng = StdRng::from_seed([0; 32]);
+        let intent = |lots| OrderIntent {
+            lots,
+            side: OrderSide::Buy,
+            symbol: build_symbol("FOO"),
+        };
+        let apply = |investor: &Investor, lots, rng: &mut StdRng| {
+            apply_profile(intent(lots), investor, &se, rng).unwrap()
+        };
+
+        // The whole size with the full risk tolerance
+        assert_eq!(
+            apply(&investor, 10, &mut rng),
+            (intent(10), OrderType::Market)
+        );
+
+        let investor = Investor {
+            profile: InvestorProfile {
+                limit_aggressiveness: 0.5,
+                order_style: OrderStyle::Limit,
+                risk_tolerance: 0.25,
+                ..Default::default()
+            },
+            ..investor
+        };
+        let limit = OrderType::Limit {
+            price: "9.90".to_string(),
+        };
+
+        assert_eq!(apply(&investor, 10, &mut rng), (intent(3), limit.clone()));
+        // But never below a lot
+        assert_eq!(apply(&investor, 1, &mut rng), (intent(1), limit));
+    }
+}
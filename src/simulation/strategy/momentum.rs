@@ -0,0 +1,39 @@
+use super::{decide_by_score, get_trend, InvestorStrategy, OrderIntent, SymbolScores};
+use crate::core::{investor::Investor, stock_exchange::StockExchange};
+use rand::rngs::StdRng;
+
+// @settings
+const LOOKBACK_TICKS: usize = 64;
+const TREND_THRESHOLD: f64 = 0.03;
+const MAX_LOTS_FRACTION: f64 = 0.25;
+
+/// Buys the symbols with the strongest recent rise and sells the held ones
+/// that are falling
+#[derive(Default)]
+pub struct MomentumStrategy {
+    scores: SymbolScores,
+}
+
+impl InvestorStrategy for MomentumStrategy {
+    fn update(&mut self, se: &StockExchange) {
+        self.scores.update(se, |symbol| {
+            get_trend(se, symbol, LOOKBACK_TICKS).map(|trend| -trend)
+        });
+    }
+
+    fn decide(
+        &self,
+        investor: &Investor,
+        se: &StockExchange,
+        rng: &mut StdRng,
+    ) -> Option<OrderIntent> {
+        decide_by_score(
+            investor,
+            se,
+            rng,
+            &self.scores,
+            TREND_THRESHOLD,
+            MAX_LOTS_FRACTION,
+        )
+    }
+}
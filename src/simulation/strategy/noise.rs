@@ -0,0 +1,81 @@
+use super::{
+    get_affordable_lots, get_held_lots, get_held_symbols, get_tradeable_lots, InvestorStrategy,
+    ListingLot, OrderIntent,
+};
+use crate::core::{
+    investor::Investor,
+    money::{Currency, Money},
+    order::OrderSide,
+    stock_exchange::StockExchange,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use std::collections::BTreeMap;
+
+/// Buys or sells a random symbol, with a random size
+#[derive(Default)]
+pub struct NoiseStrategy {
+    lowest_bid_prices: BTreeMap<Currency, Option<Money>>,
+    tradeable_lots: Vec<ListingLot>,
+}
+
+impl InvestorStrategy for NoiseStrategy {
+    fn update(&mut self, se: &StockExchange) {
+        self.lowest_bid_prices = Currency::all()
+            .into_iter()
+            .map(|currency| {
+                let price = se.prices.get_lowest_bid_price(&se.fx_rates, &currency);
+
+                (currency, price)
+            })
+            .collect();
+        self.tradeable_lots = get_tradeable_lots(se);
+    }
+
+    fn decide(
+        &self,
+        investor: &Investor,
+        se: &StockExchange,
+        rng: &mut StdRng,
+    ) -> Option<OrderIntent> {
+        let can_buy = self
+            .lowest_bid_prices
+            .get(&investor.liquid_cash.currency)
+            .copied()
+            .flatten()
+            .is_none_or(|lowest_bid_price| investor.liquid_cash.value > lowest_bid_price.value);
+        let held_symbols = get_held_symbols(investor, se);
+
+        let side = match (can_buy, !held_symbols.is_empty()) {
+            (true, true) => {
+                if rng.gen_bool(0.5) {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                }
+            }
+            (true, false) => OrderSide::Buy,
+            (false, true) => OrderSide::Sell,
+            (false, false) => return None,
+        };
+
+        match side {
+            OrderSide::Sell => {
+                let symbol = held_symbols.choose(rng)?.clone();
+                let lots = rng.gen_range(1..=get_held_lots(investor, se, &symbol));
+
+                Some(OrderIntent { lots, side, symbol })
+            }
+            OrderSide::Buy => {
+                let afforded_lots = self
+                    .tradeable_lots
+                    .iter()
+                    .filter(|lot| lot.is_affordable(investor, se))
+                    .collect::<Vec<_>>();
+                let symbol = afforded_lots.choose(rng)?.symbol.clone();
+                let lots = rng.gen_range(1..=get_affordable_lots(investor, se, &symbol));
+
+                Some(OrderIntent { lots, side, symbol })
+            }
+        }
+    }
+}
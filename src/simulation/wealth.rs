@@ -0,0 +1,165 @@
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{investor_profile::Distribution, price_model::sample_standard_normal};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WealthDistribution {
+    LogNormal { median: f64, sigma: f64 },
+    Pareto { min: f64, alpha: f64 },
+    Uniform { min: f64, max: f64 },
+}
+
+impl WealthDistribution {
+    pub fn verify(&self) -> Result<(), String> {
+        match self {
+            WealthDistribution::LogNormal { median, sigma } if *median <= 0.0 || *sigma < 0.0 => {
+                Err("The log-normal wealth parameters must be positive".to_string())
+            }
+            WealthDistribution::Pareto { min, alpha } if *min <= 0.0 || *alpha <= 0.0 => {
+                Err("The Pareto wealth parameters must be positive".to_string())
+            }
+            WealthDistribution::Uniform { min, max } if *min < 0.0 || min > max => {
+                Err("The uniform wealth range is invalid".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut StdRng) -> f64 {
+        match self {
+            WealthDistribution::LogNormal { median, sigma } => {
+                median * (sigma * sample_standard_normal(rng)).exp()
+            }
+            WealthDistribution::Pareto { min, alpha } => {
+                let u: f64 = 1.0 - rng.gen::<f64>();
+
+                min / u.powf(1.0 / alpha)
+            }
+            WealthDistribution::Uniform { min, max } => {
+                if min == max {
+                    *min
+                } else {
+                    rng.gen_range(*min..*max)
+                }
+            }
+        }
+    }
+}
+
+/// Amounts are in the currency of the exchange
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WealthSettings {
+    /** Probability that an investor keeps part of the initial cash in a foreign currency */
+    pub foreign_cash_probability: f64,
+    /** Ratio of the initial cash that is kept in the foreign currency */
+    pub foreign_cash_ratio: Distribution,
+    pub initial_cash: WealthDistribution,
+    /** Days that an investor can be in debt before the holdings are liquidated */
+    pub insolvency_grace_days: u64,
+    /** Monthly living expenses, as a ratio of the monthly income */
+    pub monthly_expenses_ratio: Distribution,
+    pub monthly_income: Distribution,
+}
+
+impl Default for WealthSettings {
+    fn default() -> Self {
+        Self {
+            foreign_cash_probability: 0.2,
+            foreign_cash_ratio: Distribution::Uniform { min: 0.1, max: 0.5 },
+            initial_cash: WealthDistribution::LogNormal {
+                median: 30_000.0,
+                sigma: 1.2,
+            },
+            insolvency_grace_days: 30,
+            monthly_expenses_ratio: Distribution::Uniform {
+                min: 0.6,
+                max: 1.05,
+            },
+            monthly_income: Distribution::Normal {
+                mean: 20_000.0,
+                std_dev: 8_000.0,
+                min: 2_000.0,
+                max: 200_000.0,
+            },
+        }
+    }
+}
+
+impl WealthSettings {
+    pub fn verify(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.foreign_cash_probability) {
+            return Err("The foreign cash probability must be between 0 and 1".to_string());
+        }
+
+        self.foreign_cash_ratio.verify()?;
+        self.initial_cash.verify()?;
+        self.monthly_expenses_ratio.verify()?;
+        self.monthly_income.verify()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_wealth_distribution() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let sample = |distribution: &WealthDistribution, rng: &mut StdRng| {
+            let mut values = (0..1001)
+                .map(|_| distribution.sample(rng))
+                .collect::<Vec<_>>();
+
+            values.sort_by(|a, b| a.total_cmp(b));
+            values
+        };
+
+        // Half of the investors have less than the median
+        let values = sample(
+            &WealthDistribution::LogNormal {
+                median: 30_000.0,
+                sigma: 1.2,
+            },
+            &mut rng,
+        );
+
+        assert!(values[0] > 0.0);
+        assert!((25_000.0..35_000.0).contains(&values[500]));
+
+        // Nobody has less than the minimum, and the top is far from the median
+        let values = sample(
+            &WealthDistribution::Pareto {
+                min: 1_000.0,
+                alpha: 1.5,
+            },
+            &mut rng,
+        );
+
+        assert!(values[0] >= 1_000.0);
+        assert!(values[1000] > values[500] * 10.0);
+
+        let values = sample(
+            &WealthDistribution::Uniform { min: 5.0, max: 5.0 },
+            &mut rng,
+        );
+
+        assert!(values.iter().all(|value| *value == 5.0));
+
+        for distribution in [
+            WealthDistribution::LogNormal {
+                median: 0.0,
+                sigma: 1.0,
+            },
+            WealthDistribution::Pareto {
+                min: 1.0,
+                alpha: 0.0,
+            },
+            WealthDistribution::Uniform { min: 2.0, max: 1.0 },
+        ] {
+            assert!(distribution.verify().is_err());
+        }
+    }
+}
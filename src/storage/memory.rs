@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::storage_interface::StorageRedis;
+
+/// Storage with the semantics of Redis kept in the process, for the runs
+/// without a server
+#[derive(Default)]
+struct SortedSet {
+    by_score: BTreeSet<(u64, String)>,
+    scores: BTreeMap<String, u64>,
+}
+
+#[derive(Default)]
+pub struct StorageMemoryImpl {
+    keys: BTreeMap<String, String>,
+    sorted_sets: BTreeMap<String, SortedSet>,
+}
+
+impl StorageRedis for StorageMemoryImpl {
+    fn append_sorted_set(&mut self, key: &str, score: u64, value: &str) -> Result<(), String> {
+        let sorted_set = self.sorted_sets.entry(key.to_string()).or_default();
+
+        // As in Redis, the members are unique and take the last score
+        if let Some(previous_score) = sorted_set.scores.insert(value.to_string(), score) {
+            sorted_set
+                .by_score
+                .remove(&(previous_score, value.to_string()));
+        }
+
+        sorted_set.by_score.insert((score, value.to_string()));
+
+        Ok(())
+    }
+
+    fn get_sorted_set_range(
+        &mut self,
+        key: &str,
+        min: u64,
+        max: u64,
+    ) -> Result<Vec<String>, String> {
+        let values = self
+            .sorted_sets
+            .get(key)
+            .map(|sorted_set| {
+                sorted_set
+                    .by_score
+                    .range((min, String::new())..)
+                    .take_while(|(score, _)| *score <= max)
+                    .map(|(_, member)| member.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(values)
+    }
+
+    fn save_key(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.keys.insert(key.to_string(), value.to_string());
+
+        Ok(())
+    }
+
+    fn load_key(&mut self, key: &str) -> Result<String, String> {
+        self.keys
+            .get(key)
+            .cloned()
+            .ok_or_else(|| format!("Failed to load key: {} is missing", key))
+    }
+
+    fn flush_data(&mut self) -> Result<(), String> {
+        self.keys.clear();
+        self.sorted_sets.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sorted_set() {
+        let mut storage = StorageMemoryImpl::default();
+
+        storage.append_sorted_set("prices", 20, "b").unwrap();
+        storage.append_sorted_set("prices", 10, "a").unwrap();
+        storage.append_sorted_set("prices", 30, "c").unwrap();
+        storage.append_sorted_set("prices", 5, "c").unwrap();
+
+        assert_eq!(
+            storage.get_sorted_set_range("prices", 0, 20).unwrap(),
+            ["c", "a", "b"]
+        );
+        assert_eq!(
+            storage.get_sorted_set_range("prices", 11, 30).unwrap(),
+            ["b"]
+        );
+        assert!(storage.load_key("state").is_err());
+    }
+}
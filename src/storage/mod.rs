@@ -1,3 +1,4 @@
 pub mod config_file;
+pub mod memory;
 pub mod prometheus;
 pub mod redis;
@@ -17,14 +17,14 @@ impl StoragePrometheus for StoragePrometheusImpl {
             let mut labels_text = String::new();
 
             if !metric.labels.is_empty() {
-                labels_text.push_str("{");
+                labels_text.push('{');
 
                 for (label_name, label_value) in metric.labels.iter() {
                     labels_text.push_str(&format!(r#"{}="{}","#, label_name, label_value));
                 }
 
                 labels_text.pop();
-                labels_text.push_str("}");
+                labels_text.push('}');
             }
             metrics_text.push_str(&format!(
                 "{}_{}{} {}\n",
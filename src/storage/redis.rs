@@ -31,6 +31,22 @@ impl StorageRedis for StorageRedisImpl {
         Ok(())
     }
 
+    fn get_sorted_set_range(
+        &mut self,
+        key: &str,
+        min: u64,
+        max: u64,
+    ) -> Result<Vec<String>, String> {
+        let values: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(key)
+            .arg(min)
+            .arg(max)
+            .query(&mut self.connection)
+            .map_err(|e| format!("Failed to read sorted set: {}", e))?;
+
+        Ok(values)
+    }
+
     fn save_key(&mut self, key: &str, value: &str) -> Result<(), String> {
         redis::cmd("SET")
             .arg(key)
@@ -6,6 +6,12 @@ pub trait StorageConfigFile {
 
 pub trait StorageRedis {
     fn append_sorted_set(&mut self, key: &str, score: u64, value: &str) -> Result<(), String>;
+    fn get_sorted_set_range(
+        &mut self,
+        key: &str,
+        min: u64,
+        max: u64,
+    ) -> Result<Vec<String>, String>;
     fn save_key(&mut self, key: &str, value: &str) -> Result<(), String>;
     fn load_key(&mut self, key: &str) -> Result<String, String>;
     fn flush_data(&mut self) -> Result<(), String>;